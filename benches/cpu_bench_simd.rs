@@ -98,9 +98,8 @@
 // );
 // criterion_main!(benches);
 
-use binius_rust::simd::binary_ntt_cache_gfni::WI_EVAL_CACHE;
-use binius_rust::simd::pcs::{commit, prove, verifier};
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use binius_rust::bench::run_roundtrip;
+use criterion::{criterion_group, criterion_main, Criterion};
 #[cfg(target_arch = "x86_64")]
 use std::arch::is_x86_feature_detected;
 
@@ -114,135 +113,27 @@ fn print_simd_features() {
     );
 }
 
-fn benchmark_group_1(c: &mut Criterion) {
-    let evaluations = vec![1; 1 << 20];
-    let evaluation_point = vec![1; 23];
-
-    {
-        let _unused = WI_EVAL_CACHE.lock().unwrap();
-    }
-
-    let commitment = commit(&evaluations);
-
-    c.bench_function("group_1_commit", |b| {
-        b.iter(|| {
-            let result = commit(black_box(&evaluations));
-            black_box(result);
-        })
-    });
-
-    c.bench_function("group_1_prove", |b| {
-        b.iter(|| {
-            let result = prove(
-                black_box(&commitment),
-                black_box(&evaluations),
-                black_box(&evaluation_point),
-            );
-            black_box(result);
-        })
-    });
-
-    c.bench_function("group_1_verifier", |b| {
-        let proof = prove(&commitment, &evaluations, &evaluation_point);
-        b.iter(|| {
-            let result = verifier(
-                black_box(&commitment),
-                black_box(&proof),
-                black_box(&evaluation_point),
-            );
-            black_box(result);
-        })
-    });
-}
-
-fn benchmark_group_2(c: &mut Criterion) {
-    let evaluations = vec![1; 1 << 24];
-    let evaluation_point = vec![1; 27];
-
-    {
-        let _unused = WI_EVAL_CACHE.lock().unwrap();
-    }
-
-    let commitment = commit(&evaluations);
-
-    c.bench_function("group_2_commit", |b| {
-        b.iter(|| {
-            let result = commit(black_box(&evaluations));
-            black_box(result);
-        })
-    });
-
-    c.bench_function("group_2_prove", |b| {
-        b.iter(|| {
-            let result = prove(
-                black_box(&commitment),
-                black_box(&evaluations),
-                black_box(&evaluation_point),
-            );
-            black_box(result);
-        })
-    });
-
-    c.bench_function("group_2_verifier", |b| {
-        let proof = prove(&commitment, &evaluations, &evaluation_point);
-        b.iter(|| {
-            let result = verifier(
-                black_box(&commitment),
-                black_box(&proof),
-                black_box(&evaluation_point),
-            );
-            black_box(result);
-        })
-    });
-}
-
-fn benchmark_group_3(c: &mut Criterion) {
-    let evaluations = vec![1; 1 << 28];
-    let evaluation_point = vec![1; 31];
-
-    {
-        let _unused = WI_EVAL_CACHE.lock().unwrap();
-    }
-
-    let commitment = commit(&evaluations);
-
-    c.bench_function("group_3_commit", |b| {
-        b.iter(|| {
-            let result = commit(black_box(&evaluations));
-            black_box(result);
-        })
+// one group per size: 2^log_evals bytes of evaluations, opened at a (log_evals + 3)-coordinate point
+fn benchmark_group(c: &mut Criterion, group: usize, log_evals: usize) {
+    c.bench_function(&format!("group_{}_commit", group), |b| {
+        b.iter_custom(|iters| (0..iters).map(|_| run_roundtrip(log_evals).commit).sum())
     });
 
-    c.bench_function("group_3_prove", |b| {
-        b.iter(|| {
-            let result = prove(
-                black_box(&commitment),
-                black_box(&evaluations),
-                black_box(&evaluation_point),
-            );
-            black_box(result);
-        })
+    c.bench_function(&format!("group_{}_prove", group), |b| {
+        b.iter_custom(|iters| (0..iters).map(|_| run_roundtrip(log_evals).prove).sum())
     });
 
-    c.bench_function("group_3_verifier", |b| {
-        let proof = prove(&commitment, &evaluations, &evaluation_point);
-        b.iter(|| {
-            let result = verifier(
-                black_box(&commitment),
-                black_box(&proof),
-                black_box(&evaluation_point),
-            );
-            black_box(result);
-        })
+    c.bench_function(&format!("group_{}_verifier", group), |b| {
+        b.iter_custom(|iters| (0..iters).map(|_| run_roundtrip(log_evals).verify).sum())
     });
 }
 
 fn benchmark_with_simd_detection(c: &mut Criterion) {
     #[cfg(target_arch = "x86_64")]
     print_simd_features(); // Print SIMD feature detection at the start
-    benchmark_group_1(c);
-    benchmark_group_2(c);
-    benchmark_group_3(c);
+    benchmark_group(c, 1, 20);
+    benchmark_group(c, 2, 24);
+    benchmark_group(c, 3, 28);
 }
 
 criterion_group!(benches, benchmark_with_simd_detection);
@@ -1,62 +1,24 @@
-use binius_rust::vanilla::binary_ntt_cache::WI_EVAL_CACHE;
-use binius_rust::vanilla::pcs::{commit, prove, verifier};
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use binius_rust::bench::run_roundtrip;
+use criterion::{criterion_group, criterion_main, Criterion};
 
-fn benchmark_commit(c: &mut Criterion) {
-    let evaluations = vec![1; 1 << 20];
-
-    {
-        let _unused = WI_EVAL_CACHE.lock().unwrap();
-    }
+// 2^20 bytes of evaluations, opened at a 23-coordinate point
+const LOG_EVALS: usize = 20;
 
+fn benchmark_commit(c: &mut Criterion) {
     c.bench_function("commit_benchmark", |b| {
-        b.iter(|| {
-            let result = commit(black_box(&evaluations));
-            black_box(result);
-        })
+        b.iter_custom(|iters| (0..iters).map(|_| run_roundtrip(LOG_EVALS).commit).sum())
     });
 }
 
 fn benchmark_prove(c: &mut Criterion) {
-    let evaluations = vec![1; 1 << 20];
-    let evaluation_point = vec![1; 23];
-
-    {
-        let _unused = WI_EVAL_CACHE.lock().unwrap();
-    }
-    let commitment = commit(&evaluations);
-
     c.bench_function("prove_benchmark", |b| {
-        b.iter(|| {
-            let result = prove(
-                black_box(&commitment),
-                black_box(&evaluations),
-                black_box(&evaluation_point),
-            );
-            black_box(result);
-        })
+        b.iter_custom(|iters| (0..iters).map(|_| run_roundtrip(LOG_EVALS).prove).sum())
     });
 }
 
 fn benchmark_verifier(c: &mut Criterion) {
-    let evaluations = vec![1; 1 << 20];
-    let evaluation_point = vec![1; 23];
-
-    {
-        let _unused = WI_EVAL_CACHE.lock().unwrap();
-    }
-    let commitment = commit(&evaluations);
-    let proof = prove(&commitment, &evaluations, &evaluation_point);
-
     c.bench_function("verifier_benchmark", |b| {
-        b.iter(|| {
-            let result = verifier(
-                black_box(&commitment),
-                black_box(&proof),
-                black_box(&evaluation_point),
-            );
-            black_box(result);
-        })
+        b.iter_custom(|iters| (0..iters).map(|_| run_roundtrip(LOG_EVALS).verify).sum())
     });
 }
 
@@ -0,0 +1,60 @@
+//! A `#![no_std]` crate on top of the alloc-only core of binius_rust.
+//!
+//! It only builds if the field arithmetic and the NTT do not need std,
+//! the tests check they still compute the same values as with std.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use binius_rust::binary_field16::BinaryFieldElement16 as B16;
+use binius_rust::vanilla::binary_ntt_cache::{extend_with_cache, WiEvalCache};
+
+/** Reed-Solomon extension of a row of u16 values, without std
+
+Args:
+    row: the values of the row, its length a power of two
+    expansion_factor: the expansion factor
+    wi_eval_cache: a built cache covering row.len() * expansion_factor points
+
+Returns:
+    the values of the extended row
+*/
+pub fn extend_row(row: &[u16], expansion_factor: usize, wi_eval_cache: &WiEvalCache) -> Vec<u16> {
+    let row: Vec<B16> = row.iter().map(|&x| B16::new(x)).collect();
+    extend_with_cache(&row, expansion_factor, wi_eval_cache)
+        .iter()
+        .map(|x| x.value)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use binius_rust::binary_field16::{bin_mul, int_to_bigbin};
+
+    #[test]
+    fn test_bin_mul() {
+        assert_eq!(bin_mul(3, 5, None), 15);
+        assert_eq!(bin_mul(7, 11, None), 4);
+        assert_eq!(bin_mul(32147, 48725, None), 43100);
+        assert_eq!(
+            int_to_bigbin(0x1234567890abcdef),
+            vec![52719, 37035, 22136, 4660, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut cache = WiEvalCache::with_max_dim(4);
+        cache.build_Wi_eval_cache();
+        // the same vector as test_extend in binary_ntt_cache.rs
+        let extended = extend_row(&[1, 3, 9, 15], 2, &cache);
+        assert_eq!(extended, vec![1, 3, 9, 15, 14, 15, 14, 11]);
+        // a codeword agrees with the row it extends
+        let row: Vec<u16> = (0..8).map(|i| i * 3 + 1).collect();
+        assert_eq!(extend_row(&row, 2, &cache)[..8], row[..]);
+    }
+}
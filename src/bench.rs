@@ -0,0 +1,155 @@
+//! The commit → prove → verify pipeline of the benches, as a library function.
+//!
+//! `run_roundtrip` runs the pcs selected by the `vanilla` / `simd` feature once and times each step,
+//! so the criterion benches, external benchmarking harnesses and CI regression tracking
+//! all measure the same pipeline, on the pseudorandom input of `deterministic_evaluations`.
+
+use crate::pcs::{commit, prove, verifier};
+#[cfg(all(feature = "simd", not(feature = "vanilla")))]
+use crate::simd::binary_ntt_cache_gfni::WI_EVAL_CACHE;
+#[cfg(feature = "vanilla")]
+use crate::vanilla::{binary_ntt_cache::warmup_caches, pcs::PcsParams};
+use std::time::{Duration, Instant};
+
+// the seed of the benchmark evaluations, fixed so every run measures the same input
+pub const BENCH_SEED: u64 = 0x5eed;
+
+/** The timings of one commit → prove → verify round trip
+
+Fields:
+    commit: the time spent in commit
+    prove: the time spent in prove
+    verify: the time spent in verifier
+    verified: whether the verifier accepted the proof
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundtripTimings {
+    pub commit: Duration,
+    pub prove: Duration,
+    pub verify: Duration,
+    pub verified: bool,
+}
+
+/** Pseudorandom but reproducible evaluation bytes
+
+a splitmix64 stream of seed, the same bytes for the same seed on every platform,
+    unlike all-ones inputs they do not give a mostly-zero t_prime
+
+Args:
+    log_bytes: the log2 of the number of bytes
+    seed: the seed of the stream
+
+Returns:
+    Vec<u8>: 2^log_bytes bytes
+*/
+pub fn deterministic_evaluations(log_bytes: usize, seed: u64) -> Vec<u8> {
+    let len = 1usize << log_bytes;
+    let mut evaluations = Vec::with_capacity(len + 8);
+    let mut state = seed;
+    while evaluations.len() < len {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        evaluations.extend_from_slice(&(z ^ (z >> 31)).to_le_bytes());
+    }
+    evaluations.truncate(len);
+    evaluations
+}
+
+/** Commit, prove and verify the benchmark polynomial once, timing each step
+
+the polynomial is the one the benches use: the 2^log_evals bytes of deterministic_evaluations with BENCH_SEED,
+    i.e. 2^(log_evals + 3) evaluations, opened at the point whose log_evals + 3 coordinates are all 1;
+    the lazily built caches are warmed up first, so the timings only cover the pcs itself
+
+Args:
+    log_evals: the log2 of the number of bytes of evaluations, e.g. 20 for the 1MB benchmark
+
+Returns:
+    RoundtripTimings: the durations of commit, prove and verify, and whether the proof verified
+*/
+pub fn run_roundtrip(log_evals: usize) -> RoundtripTimings {
+    let evaluations = deterministic_evaluations(log_evals, BENCH_SEED);
+    let evaluation_point = vec![1u128; log_evals + 3];
+    roundtrip(&evaluations, &evaluation_point)
+}
+
+#[cfg(feature = "vanilla")]
+fn roundtrip(evaluations: &[u8], evaluation_point: &Vec<u128>) -> RoundtripTimings {
+    let params = PcsParams::default();
+    warmup_caches();
+
+    let start = Instant::now();
+    let commitment = commit(evaluations, &params);
+    let commit_time = start.elapsed();
+
+    let start = Instant::now();
+    let proof = prove(&commitment, evaluations, evaluation_point, &params);
+    let prove_time = start.elapsed();
+
+    let start = Instant::now();
+    let result = verifier(&commitment, &proof, evaluation_point, &params);
+    let verify_time = start.elapsed();
+
+    RoundtripTimings {
+        commit: commit_time,
+        prove: prove_time,
+        verify: verify_time,
+        verified: result.is_ok(),
+    }
+}
+
+#[cfg(all(feature = "simd", not(feature = "vanilla")))]
+fn roundtrip(evaluations: &[u8], evaluation_point: &Vec<u128>) -> RoundtripTimings {
+    {
+        let _unused = WI_EVAL_CACHE.lock().unwrap();
+    }
+
+    let start = Instant::now();
+    let commitment = commit(evaluations);
+    let commit_time = start.elapsed();
+
+    let start = Instant::now();
+    let proof = prove(&commitment, evaluations, evaluation_point);
+    let prove_time = start.elapsed();
+
+    let start = Instant::now();
+    let verified = verifier(&commitment, &proof, evaluation_point);
+    let verify_time = start.elapsed();
+
+    RoundtripTimings {
+        commit: commit_time,
+        prove: prove_time,
+        verify: verify_time,
+        verified,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_roundtrip() {
+        let timings = run_roundtrip(20);
+        assert!(timings.verified);
+    }
+
+    #[test]
+    fn test_deterministic_evaluations() {
+        let evaluations = deterministic_evaluations(12, 7);
+        assert_eq!(evaluations.len(), 1 << 12);
+        assert_eq!(evaluations, deterministic_evaluations(12, 7));
+        assert_ne!(evaluations, deterministic_evaluations(12, 8));
+        // a prefix of the longer stream of the same seed
+        assert_eq!(deterministic_evaluations(1, 7), evaluations[..2]);
+
+        // not the degenerate all-ones input, every byte value shows up
+        let mut seen = [false; 256];
+        for &b in &evaluations {
+            seen[b as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+    }
+}
@@ -0,0 +1,71 @@
+//! The vanilla and the simd pcs on the same input, built when both features are enabled.
+//!
+//! `assert_pcs_consistent` runs both pipelines and checks every stage they share: the packed rows,
+//! the layout of the extended columns and the Merkle tree over the packed columns, then that each
+//! pipeline verifies its own proof. The stages after the packing run in different fields: the GFNI
+//! multiply of simd is another GF(2^16) than the binary tower field (8 * 5 is 10 there, 6 in vanilla),
+//! and its t_prime and eval are products in its own GF(2^128), so the extended columns, and with them
+//! the committed roots and the evals, differ for the same input. The Merkle trees are compared on the
+//! same leaves instead, each tree must give the other's root on the other's packed columns.
+
+use crate::simd::merkle_tree as simd_merkle_tree;
+use crate::simd::pcs as simd_pcs;
+use crate::vanilla::merkle_tree::{get_root, merkelize, Sha256Hasher};
+use crate::vanilla::pcs::{self as vanilla_pcs, PcsParams};
+
+/** Run the vanilla and the simd pcs on evaluations and assert that their shared stages agree
+
+Args:
+    evaluations: the evaluations, a power-of-two number of bits
+    evaluation_point: the point both pipelines open, log2(evaluations.len() * 8) coordinates
+
+Panics:
+    at the first stage where the pipelines disagree, or if either rejects its own proof
+*/
+pub fn assert_pcs_consistent(evaluations: &[u8], evaluation_point: &Vec<u128>) {
+    let params = PcsParams::default();
+    let vanilla = vanilla_pcs::commit(evaluations, &params);
+    let simd = simd_pcs::commit(evaluations);
+
+    // the rows are packed before either field is used
+    let vanilla_rows: Vec<Vec<u16>> = vanilla
+        .rows
+        .iter()
+        .map(|row| row.iter().map(|x| x.value).collect())
+        .collect();
+    let simd_rows: Vec<Vec<u16>> = simd
+        .rows
+        .iter()
+        .map(|row| row.iter().map(|x| x.value).collect())
+        .collect();
+    assert_eq!(vanilla_rows, simd_rows, "the packed rows differ");
+    assert_eq!(
+        (vanilla.columns.len(), vanilla.columns[0].len()),
+        (simd.columns.len(), simd.columns[0].len()),
+        "the extended columns have different shapes"
+    );
+
+    // the same leaves give the same root in either tree
+    assert_eq!(
+        simd_merkle_tree::get_root(&simd_merkle_tree::merkelize(&vanilla.packed_columns)),
+        vanilla.root,
+        "the simd Merkle tree of the vanilla columns is not the vanilla root"
+    );
+    assert_eq!(
+        get_root(&merkelize::<Sha256Hasher>(&simd.packed_columns)),
+        simd.root,
+        "the vanilla Merkle tree of the simd columns is not the simd root"
+    );
+
+    let vanilla_proof = vanilla_pcs::prove(&vanilla, evaluations, evaluation_point, &params);
+    assert_eq!(
+        vanilla_pcs::verifier(&vanilla, &vanilla_proof, evaluation_point, &params),
+        Ok(()),
+        "the vanilla verifier rejects the vanilla proof"
+    );
+    let simd_proof = simd_pcs::prove(&simd, evaluations, evaluation_point);
+    assert!(
+        simd_pcs::verifier(&simd, &simd_proof, evaluation_point),
+        "the simd verifier rejects the simd proof"
+    );
+}
@@ -1,3 +1,6 @@
+// without the std feature only the field arithmetic and the NTT are built, on top of alloc
+#![cfg_attr(not(feature = "std"), no_std)]
+
 // // mod binary_field16;
 // mod binary_field16_simd;
 // mod binary_ntt;
@@ -12,8 +15,62 @@
 // mod utils;
 // pub mod verifier;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(not(any(feature = "vanilla", feature = "simd")))]
+compile_error!("enable one of the features `vanilla` or `simd`");
+
 // vanilla version
+#[cfg(feature = "vanilla")]
 pub mod vanilla;
 
 // simd version
+#[cfg(feature = "simd")]
 pub mod simd;
+
+// commit → prove → verify with timings, for the benches and external harnesses
+#[cfg(feature = "std")]
+pub mod bench;
+
+// vanilla against simd on the same input, only built with both features
+#[cfg(all(feature = "vanilla", feature = "simd"))]
+pub mod consistency;
+
+// the implementation selected by the `vanilla` / `simd` feature, vanilla when both are enabled
+#[cfg(feature = "vanilla")]
+pub use vanilla::binary_field16;
+// the next tower level, only the vanilla field has it
+#[cfg(feature = "vanilla")]
+pub use vanilla::binary_field32;
+#[cfg(all(feature = "vanilla", feature = "std"))]
+pub use vanilla::pcs;
+
+#[cfg(all(feature = "simd", not(feature = "vanilla")))]
+pub use simd::{binary_field16_simd_gfni_x86 as binary_field16, pcs};
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::binary_field16::BinaryFieldElement16;
+    use super::pcs;
+
+    #[test]
+    fn test_selected_implementation() {
+        let a = BinaryFieldElement16::new(8);
+        let b = BinaryFieldElement16::new(5);
+        assert_eq!(a + b, BinaryFieldElement16::new(13));
+        // the pcs entry points resolve whichever implementation is selected
+        let _ = (pcs::commit, pcs::prove, pcs::verifier);
+    }
+
+    #[cfg(feature = "vanilla")]
+    #[test]
+    fn test_binary_field32_is_public() {
+        use super::binary_field32::BinaryFieldElement32;
+
+        let a = BinaryFieldElement16::new(8);
+        let b = BinaryFieldElement16::new(5);
+        assert_eq!(a.embed() * b.embed(), (a * b).embed());
+        assert_eq!(a.embed(), BinaryFieldElement32::new(8));
+    }
+}
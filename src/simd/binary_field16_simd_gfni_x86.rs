@@ -391,6 +391,32 @@ pub fn big_mul(x1: u128, x2: u128) -> u128 {
     montgomery_multiply(x1, x2)
 }
 
+/** Multiply two slices of 128-bit elements element-wise
+
+the products are independent, so the CLMUL/PMULL instructions of consecutive elements
+    can overlap in the pipeline instead of waiting on a fold accumulator between multiplies,
+    each element still takes one Montgomery multiplication, the gain is latency hiding only
+    (not measured here, the portable fallback gains nothing)
+
+Args:
+    a: the left factors
+    b: the right factors, as many as a
+
+Returns:
+    Vec<u128>: big_mul(a[i], b[i]) for every i
+*/
+pub fn big_mul_batch(a: &[u128], b: &[u128]) -> Vec<u128> {
+    assert_eq!(
+        a.len(),
+        b.len(),
+        "big_mul_batch takes slices of the same length"
+    );
+    a.iter()
+        .zip(b.iter())
+        .map(|(&x, &y)| montgomery_multiply(x, y))
+        .collect()
+}
+
 #[inline]
 fn montgomery_multiply(a: u128, b: u128) -> u128 {
     #[cfg(target_arch = "aarch64")]
@@ -862,6 +888,19 @@ mod tests {
     //     );
     // }
 
+    #[test]
+    fn test_big_mul_batch() {
+        let a: Vec<u128> = (1..=37u128).map(|i| i.pow(9) ^ (i << 100)).collect();
+        let b: Vec<u128> = (1..=37u128).map(|i| 3u128.pow(i as u32 % 80) ^ i).collect();
+        let expected: Vec<u128> = a
+            .iter()
+            .zip(b.iter())
+            .map(|(&x, &y)| big_mul(x, y))
+            .collect();
+        assert_eq!(big_mul_batch(&a, &b), expected);
+        assert_eq!(big_mul_batch(&[], &[]), Vec::<u128>::new());
+    }
+
     #[test]
     fn test_uint16s_to_bits() {
         let data = vec![BinaryFieldElement16::new(1u16)];
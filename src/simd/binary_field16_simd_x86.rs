@@ -12,6 +12,9 @@ use core::arch::x86_64::*;
 
 use core::mem;
 
+#[cfg(target_arch = "x86_64")]
+use lazy_static::lazy_static;
+
 use rayon::{result, str};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -386,12 +389,32 @@ Returns:
 // $n$  multiple times, it adds multiples of
 // $n$  to cancel out the lower bits and then just discards the lower bits.
 
+/** Multiply two big binary numbers
+
+the implementation is picked at runtime on x86_64: the CLMUL path when the CPU supports
+    pclmulqdq (detected once and cached in HAS_CLMUL), the software path otherwise,
+    so a build without `-C target-feature=+pclmulqdq` is still correct and still uses CLMUL
+
+Args:
+    x1: the first big binary number
+    x2: the second big binary number
+
+Returns:
+    u128: the product of the two big binary numbers
+*/
 pub fn big_mul(x1: u128, x2: u128) -> u128 {
     // Main function that multiplies two 128-bit integers `x1` and `x2` using the Montgomery multiplication.
     // The actual implementation depends on the platform: aarch64, x86_64 with CLMUL, or a portable fallback.
     montgomery_multiply(x1, x2)
 }
 
+#[cfg(target_arch = "x86_64")]
+lazy_static! {
+    // Whether the CPU running this binary has CLMUL (and SSE4.1 for the lane extraction)
+    static ref HAS_CLMUL: bool =
+        is_x86_feature_detected!("pclmulqdq") && is_x86_feature_detected!("sse4.1");
+}
+
 #[inline]
 fn montgomery_multiply(a: u128, b: u128) -> u128 {
     #[cfg(target_arch = "aarch64")]
@@ -409,38 +432,101 @@ fn montgomery_multiply(a: u128, b: u128) -> u128 {
         vreinterpretq_p128_u8(mont_reduce(h, l))
     }
 
-    #[cfg(all(target_arch = "x86_64", target_feature = "pclmulqdq"))]
-    unsafe {
-        // x86_64 implementation using CLMUL instructions.
-        // Step 1: Convert 128-bit integers to two 64-bit halves for SIMD processing.
-        let a = _mm_set_epi64x((a >> 64) as i64, (a & 0xFFFF_FFFF_FFFF_FFFF) as i64);
-        let b = _mm_set_epi64x((b >> 64) as i64, (b & 0xFFFF_FFFF_FFFF_FFFF) as i64);
+    #[cfg(target_arch = "x86_64")]
+    {
+        // x86_64: dispatch on the CPU features detected at runtime.
+        if *HAS_CLMUL {
+            // Safety: HAS_CLMUL checked pclmulqdq and sse4.1
+            unsafe { montgomery_multiply_x86(a, b) }
+        } else {
+            montgomery_multiply_soft(a, b)
+        }
+    }
 
-        // Step 2: Perform Karatsuba decomposition to get high, mid, and low parts.
-        let (h, m, l) = karatsuba1_x86(a, b);
+    #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
+    {
+        // Portable fallback implementation using a software carry-less multiply.
+        montgomery_multiply_soft(a, b)
+    }
+}
+
+// x86_64 implementation using CLMUL instructions, only called once HAS_CLMUL holds
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "pclmulqdq,sse4.1")]
+unsafe fn montgomery_multiply_x86(a: u128, b: u128) -> u128 {
+    // Step 1: Convert 128-bit integers to two 64-bit halves for SIMD processing.
+    let a = _mm_set_epi64x((a >> 64) as i64, (a & 0xFFFF_FFFF_FFFF_FFFF) as i64);
+    let b = _mm_set_epi64x((b >> 64) as i64, (b & 0xFFFF_FFFF_FFFF_FFFF) as i64);
 
-        // Step 3: Combine the results using Karatsuba combine logic.
-        let (h, l) = karatsuba2_x86(h, m, l);
+    // Step 2: Perform Karatsuba decomposition to get high, mid, and low parts.
+    let (h, m, l) = karatsuba1_x86(a, b);
 
-        // Step 4: Apply Montgomery reduction using CLMUL to finalize the result.
-        mont_reduce_x86(h, l)
-    }
+    // Step 3: Combine the results using Karatsuba combine logic.
+    let (h, l) = karatsuba2_x86(h, m, l);
 
-    #[cfg(not(any(
-        target_arch = "aarch64",
-        all(target_arch = "x86_64", target_feature = "pclmulqdq")
-    )))]
-    {
-        // Portable fallback implementation using basic arithmetic.
-        // Split the 128-bit integers into high and low 64-bit halves.
-        let l = (a & 0xFFFF_FFFF_FFFF_FFFF) * (b & 0xFFFF_FFFF_FFFF_FFFF); // Low part
-        let h = (a >> 64) * (b >> 64); // High part
-        let mid = ((a & 0xFFFF_FFFF_FFFF_FFFF) + (a >> 64))
-            * ((b & 0xFFFF_FFFF_FFFF_FFFF) + (b >> 64))
-            - l
-            - h; // Middle terms
-        l ^ (mid << 64) ^ h // Combine the results into the final 128-bit value.
+    // Step 4: Apply Montgomery reduction using CLMUL to finalize the result.
+    mont_reduce_x86(h, l)
+}
+
+// Original implementation: ordinary integer arithmetic, which is not a carry-less product
+//     {
+//         // Portable fallback implementation using basic arithmetic.
+//         // Split the 128-bit integers into high and low 64-bit halves.
+//         let l = (a & 0xFFFF_FFFF_FFFF_FFFF) * (b & 0xFFFF_FFFF_FFFF_FFFF); // Low part
+//         let h = (a >> 64) * (b >> 64); // High part
+//         let mid = ((a & 0xFFFF_FFFF_FFFF_FFFF) + (a >> 64))
+//             * ((b & 0xFFFF_FFFF_FFFF_FFFF) + (b >> 64))
+//             - l
+//             - h; // Middle terms
+//         l ^ (mid << 64) ^ h // Combine the results into the final 128-bit value.
+//     }
+
+// Polynomial for the field reduction, the same one used by mont_reduce and mont_reduce_x86
+const MONT_POLY: u128 = 0x1B;
+
+/** Software Montgomery multiplication
+
+the portable counterpart of the NEON / CLMUL paths: the same Karatsuba product and
+    the same Montgomery reduction, with the 64-bit carry-less multiply done bit by bit
+
+Args:
+    a: the first operand
+    b: the second operand
+
+Returns:
+    u128: the same value the NEON / CLMUL paths return for a * b
+*/
+#[cfg_attr(target_arch = "aarch64", allow(dead_code))]
+fn montgomery_multiply_soft(a: u128, b: u128) -> u128 {
+    let (a1, a0) = ((a >> 64) as u64, a as u64);
+    let (b1, b0) = ((b >> 64) as u64, b as u64);
+
+    // Karatsuba: the 256-bit carry-less product as high and low 128-bit halves
+    let h = clmul64(a1, b1);
+    let l = clmul64(a0, b0);
+    let m = clmul64(a0 ^ a1, b0 ^ b1) ^ h ^ l;
+    let x23 = h ^ (m >> 64);
+    let x01 = l ^ (m << 64);
+
+    // Montgomery reduction, lane by lane as in mont_reduce
+    let poly_lo = MONT_POLY as u64;
+    let poly_hi = (MONT_POLY >> 64) as u64;
+    let a = clmul64(x01 as u64, poly_lo);
+    let b = x01 ^ a.rotate_left(64);
+    let c = clmul64((b >> 64) as u64, poly_hi);
+    x23 ^ c ^ b
+}
+
+// Carry-less multiplication of two 64-bit words into a 128-bit product
+#[inline]
+fn clmul64(a: u64, b: u64) -> u128 {
+    let mut result = 0u128;
+    for i in 0..64 {
+        if (b >> i) & 1 == 1 {
+            result ^= (a as u128) << i;
+        }
     }
+    result
 }
 
 // aarch64 implementation using NEON instructions
@@ -463,7 +549,9 @@ unsafe fn karatsuba1(x: uint8x16_t, y: uint8x16_t) -> (uint8x16_t, uint8x16_t, u
 /// Combines the high, mid, and low components into two final components.
 #[inline]
 unsafe fn karatsuba2(h: uint8x16_t, m: uint8x16_t, l: uint8x16_t) -> (uint8x16_t, uint8x16_t) {
-    let t = veorq_u8(veorq_u8(h, l), m); // Intermediate term
+    // let t = veorq_u8(veorq_u8(h, l), m); // Intermediate term
+    // {m0 ^ l1 ^ h0 ^ l0, m1 ^ h0 ^ h1 ^ l1}
+    let t = veorq_u8(veorq_u8(h, l), veorq_u8(m, vextq_u8(l, h, 8))); // Intermediate term
     let x01 = vextq_u8(vextq_u8(l, l, 8), t, 8); // Low result
     let x23 = vextq_u8(t, vextq_u8(h, h, 8), 8); // High result
     (x23, x01)
@@ -509,10 +597,11 @@ unsafe fn pmull2(a: uint8x16_t, b: uint8x16_t) -> uint8x16_t {
 }
 
 // x86 implementation using CLMUL instructions
-#[cfg(all(target_arch = "x86_64", target_feature = "pclmulqdq"))]
+#[cfg(target_arch = "x86_64")]
 /// Karatsuba decomposition for `x * y` on x86_64.
 /// Decomposes the inputs into high, mid, and low components for efficient multiplication.
 #[inline]
+#[target_feature(enable = "pclmulqdq,sse4.1")]
 unsafe fn karatsuba1_x86(x: __m128i, y: __m128i) -> (__m128i, __m128i, __m128i) {
     let m = _mm_clmulepi64_si128(
         _mm_xor_si128(x, _mm_shuffle_epi32(x, 0x4E)), // x.hi ^ x.lo
@@ -524,28 +613,40 @@ unsafe fn karatsuba1_x86(x: __m128i, y: __m128i) -> (__m128i, __m128i, __m128i)
     (h, m, l)
 }
 
-#[cfg(all(target_arch = "x86_64", target_feature = "pclmulqdq"))]
+#[cfg(target_arch = "x86_64")]
 /// Karatsuba combine for x86_64.
 /// Combines the high, mid, and low components into two final components.
 #[inline]
+#[target_feature(enable = "pclmulqdq,sse4.1")]
 unsafe fn karatsuba2_x86(h: __m128i, m: __m128i, l: __m128i) -> (__m128i, __m128i) {
-    let t = _mm_xor_si128(_mm_xor_si128(h, l), m); // Intermediate term
-    let x01 = _mm_alignr_epi8(l, t, 8); // Low result
-    let x23 = _mm_alignr_epi8(t, h, 8); // High result
+    // let t = _mm_xor_si128(_mm_xor_si128(h, l), m); // Intermediate term
+    // let x01 = _mm_alignr_epi8(l, t, 8); // Low result
+    // let x23 = _mm_alignr_epi8(t, h, 8); // High result
+    // {m0 ^ l1 ^ h0 ^ l0, m1 ^ h0 ^ h1 ^ l1}
+    let t = _mm_xor_si128(
+        _mm_xor_si128(h, l),
+        _mm_xor_si128(m, _mm_unpacklo_epi64(_mm_unpackhi_epi64(l, l), h)),
+    ); // Intermediate term
+    let x01 = _mm_unpacklo_epi64(l, t); // Low result: {l0, t0}
+    let x23 = _mm_unpackhi_epi64(t, h); // High result: {t1, h1}
     (x23, x01)
 }
 
-#[cfg(all(target_arch = "x86_64", target_feature = "pclmulqdq"))]
+#[cfg(target_arch = "x86_64")]
 /// Montgomery reduction for x86_64 using CLMUL.
 /// Performs modular reduction to ensure the result is in the correct field.
 #[inline]
+#[target_feature(enable = "pclmulqdq,sse4.1")]
 unsafe fn mont_reduce_x86(h: __m128i, l: __m128i) -> u128 {
     let poly = _mm_set_epi64x(0, 0x1B); // Polynomial for the field
     let a = _mm_clmulepi64_si128(l, poly, 0x00); // First partial reduction
     let b = _mm_xor_si128(l, _mm_shuffle_epi32(a, 0x4E)); // Combine results
     let c = _mm_clmulepi64_si128(b, poly, 0x11); // Second partial reduction
     let reduced = _mm_xor_si128(h, _mm_xor_si128(c, b)); // Final result
-    _mm_extract_epi64(reduced, 0) as u128 | ((_mm_extract_epi64(reduced, 1) as u128) << 64)
+
+    // go through u64 so a negative lane is not sign-extended into the high half
+    _mm_extract_epi64(reduced, 0) as u64 as u128
+        | ((_mm_extract_epi64(reduced, 1) as u64 as u128) << 64)
 }
 
 /** Multiply a big binary number by Xi
@@ -849,6 +950,73 @@ mod tests {
         assert_eq!(result, 0x1234567890abcdef);
     }
 
+    #[test]
+    fn test_clmul64() {
+        assert_eq!(clmul64(0b11, 0b11), 0b101);
+        assert_eq!(clmul64(u64::MAX, 2), (u64::MAX as u128) << 1);
+        assert_eq!(clmul64(1 << 63, 1 << 63), 1 << 126);
+    }
+
+    #[test]
+    fn test_montgomery_multiply_soft() {
+        let vectors: [(u128, u128); 5] = [
+            (0, 0x1234567890abcdef),
+            (1, 1),
+            (3u128.pow(29), 5u128.pow(29)),
+            (u128::MAX, u128::MAX),
+            (
+                0x0123_4567_89ab_cdef_fedc_ba98_7654_3210,
+                0xdead_beef_0bad_f00d_cafe_babe_1337_c0de,
+            ),
+        ];
+        for &(a, b) in vectors.iter() {
+            let soft = montgomery_multiply_soft(a, b);
+            // the NEON / CLMUL path must agree with the software one
+            assert_eq!(big_mul(a, b), soft);
+            assert_eq!(montgomery_multiply_soft(b, a), soft);
+        }
+        assert_eq!(montgomery_multiply_soft(0, 0x1234567890abcdef), 0);
+        // known vectors, computed with the CLMUL path
+        assert_eq!(
+            montgomery_multiply_soft(3u128.pow(29), 5u128.pow(29)),
+            0xfe610e533aa7432285fb63a2eb551422
+        );
+        assert_eq!(
+            montgomery_multiply_soft(
+                0x0123_4567_89ab_cdef_fedc_ba98_7654_3210,
+                0xdead_beef_0bad_f00d_cafe_babe_1337_c0de
+            ),
+            0x0154abb8d69ed310f1b53e6cd7d00fc7
+        );
+    }
+
+    #[test]
+    fn test_big_mul_dispatch() {
+        // xorshift, so the operands cover all the bits of both halves
+        let mut state = 0x9E37_79B9_7F4A_7C15_F39C_C060_5CED_C835u128;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..1000 {
+            let (a, b) = (next(), next());
+            assert_eq!(big_mul(a, b), montgomery_multiply_soft(a, b));
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_montgomery_multiply_x86() {
+        if !*HAS_CLMUL {
+            return;
+        }
+        let (a, b) = (3u128.pow(29), 5u128.pow(29));
+        let result = unsafe { montgomery_multiply_x86(a, b) };
+        assert_eq!(result, montgomery_multiply_soft(a, b));
+    }
+
     // #[test]
     // fn test_big_mul() {
     //     // big_mul(int_to_bigbin(3**29), int_to_bigbin(5**29))= [46732 49627 26993 63626 14101 27237 21150     0]
@@ -63,6 +63,12 @@ Returns:
     the hash path of the Merkle tree
  */
 pub fn get_branch(tree: &Vec<Vec<u8>>, pos: usize) -> Vec<Vec<u8>> {
+    assert!(
+        pos < tree.len() / 2,
+        "leaf position {} is out of {} leaves",
+        pos,
+        tree.len() / 2
+    );
     let offset_pos = pos + tree.len() / 2;
     let branch_length = (tree.len() as f64).log2() as usize - 1;
     let mut branch = vec![];
@@ -159,4 +165,11 @@ mod tests {
         let result = verify_branch(&tree[1], pos, &vals[1], &branch);
         assert_eq!(result, true);
     }
+
+    #[test]
+    #[should_panic(expected = "leaf position 2 is out of 2 leaves")]
+    fn test_get_branch_out_of_range() {
+        let vals = vec![vec![1, 2], vec![3, 4]];
+        get_branch(&merkelize(&vals), 2);
+    }
 }
@@ -1,9 +1,9 @@
 // mod binary_field16_simd;
-mod binary_field16_simd_gfni_x86;
+pub mod binary_field16_simd_gfni_x86;
 // pub mod binary_ntt_cache;
 pub mod binary_ntt_cache_gfni;
 mod challenger;
-mod merkle_tree;
+pub(crate) mod merkle_tree;
 pub mod pcs;
 // mod utils;
 mod utils_gfni;
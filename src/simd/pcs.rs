@@ -10,7 +10,7 @@ use super::merkle_tree::get_branch;
 use p3_util::log2_strict_usize;
 
 use super::binary_field16_simd_gfni_x86::{
-    big_mul, uint16_to_bit, uint16s_to_bits, BinaryFieldElement16,
+    big_mul_batch, uint16_to_bit, uint16s_to_bits, BinaryFieldElement16,
 };
 use super::challenger::get_challenges;
 use super::merkle_tree::{get_root, merkelize, verify_branch};
@@ -35,6 +35,22 @@ pub struct Proof {
     pub branches: Vec<Vec<Vec<u8>>>,
 }
 
+/** The number of coordinates of the evaluation point to prove evaluations with
+
+the evaluations are a multilinear polynomial over len * 8 bits, one coordinate per bit of the
+    evaluation index, so prove and verifier take a point of log2(len * 8) coordinates,
+    e.g. 23 for 1 << 20 bytes
+
+Args:
+    evaluations: the committed evaluations, a power-of-two number of bits
+
+Returns:
+    usize: the length of the evaluation point
+*/
+pub fn required_point_len(evaluations: &[u8]) -> usize {
+    log2_strict_usize(evaluations.len() * 8)
+}
+
 pub fn commit(evaluations: &[u8]) -> Commitment {
     let log_evaluation_count = log2_strict_usize(evaluations.len() * 8);
     let (log_row_length, log_row_count, row_length, row_count) =
@@ -89,11 +105,10 @@ pub fn prove(commitment: &Commitment, evaluations: &[u8], evaluation_point: &Vec
 
     // Compute evaluation
     let col_combination = evaluation_tensor_product(&evaluation_point[..log_row_length].to_vec());
-    // for each row in t_prime and each row in col_combination, use big_mul to multiply them
-    let computed_eval = t_prime
+    // for each row in t_prime and each row in col_combination, multiply them with big_mul_batch and xor the products
+    let computed_eval = big_mul_batch(&t_prime, &col_combination)
         .iter()
-        .zip(col_combination.iter())
-        .fold(0u128, |acc, (&t, &c)| acc ^ big_mul(t, c));
+        .fold(0u128, |acc, &p| acc ^ p);
 
     Proof {
         evaluation_point: evaluation_point.clone(),
@@ -196,16 +211,31 @@ pub fn verifier(commitment: &Commitment, proof: &Proof, evaluation_point: &Vec<u
 
     // Compute the evaluation
     let col_combination = evaluation_tensor_product(&evaluation_point[..log_row_length].to_vec());
-    let computed_eval = t_prime
+    let computed_eval = big_mul_batch(&t_prime, &col_combination)
         .iter()
-        .zip(col_combination.iter())
-        .fold(0u128, |acc, (&t, &c)| acc ^ big_mul(t, c));
+        .fold(0u128, |acc, &p| acc ^ p);
     assert_eq!(computed_eval, *value);
     true
 }
 
+/** The eval of a proof as 8 uint16 limbs, least significant first, the layout of the vanilla Proof.eval
+
+only the encoding is shared: the simd eval is a big_mul product, not a tower-field element
+    of vanilla, so the limbs of the same input differ between the two (see mod.rs)
+
+Args:
+    proof: the proof
+
+Returns:
+    Vec<u16>: int_to_bigbin of the eval
+*/
+pub fn eval_as_limbs(proof: &Proof) -> Vec<u16> {
+    int_to_bigbin(proof.eval)
+}
+
 #[cfg(test)]
 mod tests {
+    use super::super::binary_field16_simd_gfni_x86::bigbin_to_int;
     use super::*;
 
     #[test]
@@ -241,6 +271,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_eval_as_limbs() {
+        let evaluations: Vec<u8> = (0..1u32 << 12).map(|i| (i * 7 + 1) as u8).collect();
+        let commitment = commit(&evaluations);
+        let evaluation_point: Vec<u128> = (1..=15).collect();
+        let proof = prove(&commitment, &evaluations, &evaluation_point);
+        let limbs = eval_as_limbs(&proof);
+        assert_eq!(limbs.len(), 8);
+        assert_eq!(bigbin_to_int(&limbs), proof.eval);
+
+        // the zero polynomial evaluates to 0 in both implementations
+        let zeros = vec![0u8; 1 << 12];
+        let proof = prove(&commit(&zeros), &zeros, &evaluation_point);
+        assert_eq!(eval_as_limbs(&proof), vec![0; 8]);
+    }
+
     #[test]
     fn test_verifier() {
         let evaluations = vec![1; 1 << 20];
@@ -249,4 +295,10 @@ mod tests {
         let proof = prove(&commitment, &evaluations, &evaluation_point);
         assert!(verifier(&commitment, &proof, &evaluation_point));
     }
+
+    #[test]
+    fn test_required_point_len() {
+        assert_eq!(required_point_len(&vec![0u8; 1 << 20]), 23);
+        assert_eq!(required_point_len(&vec![0u8; 1 << 16]), 19);
+    }
 }
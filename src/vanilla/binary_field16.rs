@@ -9,20 +9,55 @@
 //! - `ToU16` to convert a `BinaryFieldElement16` into a `u16`.
 //!
 //! Additionally, the `BinaryFieldElement16` struct provides the following functions:
-//! - `int_to_bigbin`: Converts a 128-bit integer into a length-8 vector of `u16`.
-//! - `uint16s_to_bits`: Converts a vector of `u16` into bits.
+//! - `int_to_bigbin`: Converts a 128-bit integer into a length-8 vector of `u16` (`int_to_bigbin_n` and `bigbin_to_int_n` take the limb count, `int_to_bigbin_wide` and `bigbin_to_words` go past 128 bits).
+//! - `uint16s_to_bits`: Converts a vector of `u16` into bits, with SSSE3 or NEON when the CPU has them (`uint16s_to_bits_ordered` takes the `BitOrder`).
 //! - `uint16_to_bit`: Converts a `BinaryFieldElement16` into bits.
 //! - `bin_mul`: Multiplies two binary numbers in the binary tower field.
 //! - `big_mul`: Multiplies two large binary numbers.
-//! - `mul_by_Xi`: Multiplies a large binary number by `Xi`.
-
+//! - `mul_by_Xi`: Multiplies a large binary number by `Xi` (`mul_by_Xi_at` for lanes of a lower tower level).
+//! - `embed`: Embeds a `BinaryFieldElement16` into the low half of a `BinaryFieldElement32`.
+//! - `from_bits`: Builds a `BinaryFieldElement16` from the bits `uint16_to_bit` emits.
+
+use super::binary_field32::BinaryFieldElement32;
+use alloc::{format, string::String, vec, vec::Vec};
+use core::ops::{Add, Div, Mul, Neg, Sub};
+#[cfg(feature = "inv-table")]
+use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
-use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// the inverse of every element of GF(2^16), 65536 * 2 bytes = 128KB, built once on first access
+#[cfg(feature = "inv-table")]
+lazy_static! {
+    pub static ref INV_TABLE: Vec<u16> = build_inv_table();
+}
+
+/** Build the inverse table
+
+inv_table[x] = x^(-1) for every nonzero x, computed with the exponentiation path,
+    inv_table[0] is left as 0 and never looked up
+
+Returns:
+    Vec<u16>: the inverse table, indexed by the element value
+ */
+#[cfg(feature = "inv-table")]
+fn build_inv_table() -> Vec<u16> {
+    let mut table = vec![0u16; 1 << 16];
+    for x in 1..=u16::MAX {
+        // optimization trick: inv(inv(x)) = x, so every exponentiation fills two entries
+        if table[x as usize] == 0 {
+            let inv = BinaryFieldElement16::new(x).inv_by_pow().value;
+            table[x as usize] = inv;
+            table[inv as usize] = x;
+        }
+    }
+    table
+}
 
 /**
 A binary field element：a wrapper of u64
  */
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+// Ord only orders the values, so the element can key the no_std BTreeMap of WiEvalCache
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash, Default)]
 pub struct BinaryFieldElement16 {
     pub value: u16,
 }
@@ -32,56 +67,119 @@ impl BinaryFieldElement16 {
         BinaryFieldElement16 { value }
     }
 
-    /** Get the bit length of the element
+    /** Build an element from its bits, the inverse of uint16_to_bit
 
-    find the smallest power of 2 that is greater than the element, and count the number zeros before the first 1
-        then return 2^count
+    bits[i] is bit i of the value (LSB-first, the order uint16_to_bit emits),
+        only the low bit of each byte is read
+
+    Args:
+        bits: exactly 16 bits, one per byte
 
     Returns:
-        usize: the bit length of the element
+        BinaryFieldElement: the element with these bits
+
+    Panics:
+        if bits does not hold exactly 16 bits
      */
-    fn bit_length(&self) -> u16 {
-        16 - self.value.leading_zeros() as u16
+    pub fn from_bits(bits: &[u8]) -> Self {
+        assert_eq!(bits.len(), 16, "expected 16 bits, got {}", bits.len());
+        let value = bits
+            .iter()
+            .enumerate()
+            .fold(0u16, |acc, (i, &bit)| acc | (((bit & 1) as u16) << i));
+        BinaryFieldElement16 { value }
+    }
+
+    /** Get the inverse of the element by exponentiation
+
+    inverse = element^(2^16 - 2), the multiplicative group of GF(2^16) has order 2^16 - 1.
+        the Python version uses 2^L - 2 for the smallest subfield GF(2^L) holding the element,
+        which is the same inverse, but 2^16 overflows a u16 for L = 16. 0 maps to 0
+
+    Returns:
+        BinaryFieldElement: the inverse of the element
+
+    */
+    fn inv_by_pow(&self) -> Self {
+        self.pow(0xFFFE)
     }
 
     /** Get the inverse of the element
 
-    inverse = element^(2^(bit_length(element) - 2))
+    nonzero elements look up the precomputed INV_TABLE, the exponentiation path is only used
+        when the `inv-table` feature is disabled
 
     Returns:
         BinaryFieldElement: the inverse of the element
 
     */
+    #[cfg(feature = "inv-table")]
+    pub fn inv(&self) -> Self {
+        if self.value == 0 {
+            return self.inv_by_pow();
+        }
+        BinaryFieldElement16::new(INV_TABLE[self.value as usize])
+    }
+
+    #[cfg(not(feature = "inv-table"))]
     pub fn inv(&self) -> Self {
-        // L = 1 << (self.value.bit_length() - 1).bit_length()
-        // return self ** (2**L - 2)
-        let l = 1 << (16 - (self.bit_length() - 1).leading_zeros());
-        self.pow(2u16.pow(l as u32) - 2)
+        self.inv_by_pow()
+    }
+
+    /** Embed the element into GF(2^32)
+
+    GF(2^16) is the subfield of GF(2^32) made of the elements whose high 16 bits are 0,
+        so the embedding is just a zero-extension and it preserves addition and multiplication
+
+    Returns:
+        BinaryFieldElement32: the same element, seen in the wider field
+     */
+    pub fn embed(self) -> BinaryFieldElement32 {
+        BinaryFieldElement32::new(self.value as u32)
+    }
+
+    /** Get the square of the element
+
+    square = element * element
+
+    Returns:
+        BinaryFieldElement: the square of the element
+     */
+    pub fn square(&self) -> Self {
+        *self * *self
     }
 
     /** Get the power of the element
 
-    power = element^(exp), and it is calculated recursively, using the following rules:
-        1. if exp = 0, return 1
-        2. if exp = 1, return element
-        3. if exp = 2, return element * element
-        4. if exp is even, return (element^(exp/2))^2
-        5. if exp is odd, return element * (element^(exp - 1))
+    power = element^(exp), and it is calculated with iterative square-and-multiply:
+        walk the bits of exp from the most significant to the least significant,
+        square the accumulator at every bit, and multiply it by the element when the bit is 1
 
     Args:
         exp (u16): the exponent, important: exp is not binary field element, it is u16
 
      */
+    // original recursive implementation
+    // fn pow(&self, exp: u16) -> Self {
+    //     if exp == 0 {
+    //         BinaryFieldElement16::new(1)
+    //     } else if exp == 1 {
+    //         *self
+    //     } else if exp == 2 {
+    //         *self * *self
+    //     } else {
+    //         self.pow(exp % 2) * self.pow(exp / 2).pow(2)
+    //     }
+    // }
     fn pow(&self, exp: u16) -> Self {
-        if exp == 0 {
-            BinaryFieldElement16::new(1)
-        } else if exp == 1 {
-            *self
-        } else if exp == 2 {
-            *self * *self
-        } else {
-            self.pow(exp % 2) * self.pow(exp / 2).pow(2)
+        let mut result = BinaryFieldElement16::new(1);
+        for i in (0..16 - exp.leading_zeros()).rev() {
+            result = result.square();
+            if (exp >> i) & 1 == 1 {
+                result = result * *self;
+            }
         }
+        result
     }
 }
 
@@ -407,13 +505,39 @@ Returns:
 //     result
 // }
 pub fn mul_by_Xi(x: &Vec<u16>, n: usize) -> Vec<u16> {
+    mul_by_Xi_at(x, n, MUL_BY_XI_LEVEL)
+}
+
+// the tower level of a u16 lane, GF(2^(2^4)) = GF(2^16)
+pub const MUL_BY_XI_LEVEL: usize = 4;
+
+/** Multiply a big binary number by Xi, the lanes being elements of the tower level level
+
+same recursion as mul_by_Xi, only the leaf changes: a lane of level level is in GF(2^(2^level)),
+    its top generator is x_(level-1) = 1 << 2^(level-1), 256 for the default level 4,
+    so with level < 4 every lane must hold an element of that level, i.e. be below 2^(2^level)
+
+Args:
+    x: the big binary number, the type is Vec<u16>
+    n: the length of the big binary number
+    level: the tower level of the lanes, 1..=4
+
+Returns:
+    Vec<u16>: the product of the big binary number and Xi
+*/
+pub fn mul_by_Xi_at(x: &Vec<u16>, n: usize, level: usize) -> Vec<u16> {
+    assert!(
+        (1..=MUL_BY_XI_LEVEL).contains(&level),
+        "a u16 lane holds tower levels 1 to 4, got {}",
+        level
+    );
     if x.len() == 1 {
-        return vec![bin_mul(x[0], 256, None)];
+        return vec![bin_mul(x[0], 1 << (1 << (level - 1)), None)];
     }
 
     let (l, r) = x.split_at(n / 2);
 
-    let out_r = mul_by_Xi(&r.to_vec(), n / 2)
+    let out_r = mul_by_Xi_at(&r.to_vec(), n / 2, level)
         .iter()
         .zip(l.iter())
         .map(|(a, b)| a ^ b)
@@ -437,14 +561,98 @@ Returns:
 
  */
 pub fn int_to_bigbin(x: u128) -> Vec<u16> {
-    let mut result = Vec::new();
-    for k in 0..8 {
-        result.push(((x >> (k * 16)) & 65535) as u16);
-    }
-    result
+    int_to_bigbin_n(x, BIGBIN_LIMBS)
+}
+
+// the uint16 limbs of a 128-bit element, the length of int_to_bigbin
+pub const BIGBIN_LIMBS: usize = 8;
+
+/** Convert an integer into a given number of uint16 limbs, least significant first
+
+limbs past the 8 of a u128 are 0, so the result can be the low half of a wider element
+
+Args:
+    x: the integer, it must fit in limbs * 16 bits
+    limbs: the number of uint16's
+
+Returns:
+    Vec<u16>: the limbs
+*/
+pub fn int_to_bigbin_n(x: u128, limbs: usize) -> Vec<u16> {
+    assert!(
+        limbs >= BIGBIN_LIMBS || x >> (limbs * 16) == 0,
+        "{:#x} does not fit in {} limbs",
+        x,
+        limbs
+    );
+    (0..limbs)
+        .map(|k| {
+            if k < BIGBIN_LIMBS {
+                (x >> (k * 16)) as u16
+            } else {
+                0
+            }
+        })
+        .collect()
+}
+
+/** Convert a given number of uint16 limbs back to an integer, the inverse of int_to_bigbin_n
+
+Args:
+    x: the limbs, least significant first
+    limbs: the number of limbs x must have, the ones past the 8 of a u128 must be 0
+
+Returns:
+    value: the integer
+*/
+pub fn bigbin_to_int_n(x: &[u16], limbs: usize) -> u128 {
+    assert_eq!(x.len(), limbs, "expected {} limbs, got {}", limbs, x.len());
+    assert!(
+        x.iter().skip(BIGBIN_LIMBS).all(|&v| v == 0),
+        "{} limbs do not fit in 128 bits",
+        limbs
+    );
+    bigbin_to_int(&x[..limbs.min(BIGBIN_LIMBS)])
 }
 
-pub fn bigbin_to_int(x: &Vec<u16>) -> u128 {
+/** Convert an integer wider than 128 bits into uint16 limbs, least significant first
+
+Args:
+    words: the 128-bit words of the integer, least significant first (2 for a 256-bit element)
+
+Returns:
+    Vec<u16>: 8 limbs per word
+*/
+pub fn int_to_bigbin_wide(words: &[u128]) -> Vec<u16> {
+    words.iter().flat_map(|&w| int_to_bigbin(w)).collect()
+}
+
+/** Convert uint16 limbs back to 128-bit words, the inverse of int_to_bigbin_wide
+
+Args:
+    x: the limbs, least significant first, a multiple of 8 of them
+
+Returns:
+    Vec<u128>: the words, least significant first
+*/
+pub fn bigbin_to_words(x: &[u16]) -> Vec<u128> {
+    assert!(
+        x.len() % BIGBIN_LIMBS == 0,
+        "{} limbs are not whole 128-bit words",
+        x.len()
+    );
+    x.chunks(BIGBIN_LIMBS).map(bigbin_to_int).collect()
+}
+
+/** Convert a big binary number back to a 128-bit integer, the inverse of int_to_bigbin
+
+Args:
+    x: the big binary number, up to 8 uint16 limbs, least significant first
+
+Returns:
+    value: the 128-bit integer
+*/
+pub fn bigbin_to_int(x: &[u16]) -> u128 {
     x.iter()
         .enumerate()
         .fold(0, |acc, (i, &v)| acc | ((v as u128) << (i * 16)))
@@ -476,6 +684,22 @@ impl ToU16 for BinaryFieldElement16 {
     }
 }
 
+/** The order of the bits within a byte or a u16
+
+for the bytes given to utils::pack:
+    Lsb0: bit k of a byte is evaluation 8 * byte + k, the layout of the committed evaluations,
+        so a packed element's bit i (what uint16s_to_bits reads as bit i) is evaluation i of the element
+    Msb0: bit 7 - k of a byte is entry 8 * byte + k, the layout transpose_bits produces
+for the u16s given to uint16s_to_bits_ordered:
+    Lsb0: bit 0 of each value comes first, what uint16s_to_bits emits
+    Msb0: bit 15 of each value comes first, for systems that expect big-endian bits
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitOrder {
+    Lsb0,
+    Msb0,
+}
+
 // original implementation
 // pub fn uint16s_to_bits<T: ToU16>(data: &Vec<T>) -> Vec<u8> {
 //     let mut result = Vec::with_capacity(data.len() * 16);
@@ -490,27 +714,100 @@ impl ToU16 for BinaryFieldElement16 {
 //     result
 // }
 
-// optimized implementation: save 45% prover time
+/** Convert u16s into bits, bit 0 of each value first, one bit per byte
+
+the bits of every row go through here in prove and verifier,
+    so with the std feature it expands 16 bits at a time with SSSE3 on x86_64 or NEON on aarch64
+    when the CPU has them, and falls back to the scalar loop otherwise, all paths give the same bytes
+
+Args:
+    data: the values to convert
+
+Returns:
+    Vec<u8>: 16 bytes per value, each 0 or 1
+*/
 pub fn uint16s_to_bits<T: ToU16>(data: &Vec<T>) -> Vec<u8> {
+    #[cfg(all(feature = "std", target_arch = "x86_64"))]
+    if std::is_x86_feature_detected!("ssse3") {
+        return unsafe { uint16s_to_bits_ssse3(data) };
+    }
+    #[cfg(all(feature = "std", target_arch = "aarch64"))]
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        return unsafe { uint16s_to_bits_neon(data) };
+    }
+    uint16s_to_bits_scalar(data)
+}
+
+// optimized implementation: save 45% prover time
+fn uint16s_to_bits_scalar<T: ToU16>(data: &[T]) -> Vec<u8> {
     let len = data.len() * 16;
-    let mut result = Vec::with_capacity(len);
+    let mut result: Vec<u8> = Vec::with_capacity(len);
 
     // optimize trick: use unsafe code directly to avoid the overhead of bounds checking
+    // every byte is written through the pointer before set_len
     unsafe {
-        result.set_len(len);
+        let out = result.as_mut_ptr();
         let mut index = 0;
         for value in data {
             let value_u16 = value.to_u16();
             for i in 0..16 {
-                *result.get_unchecked_mut(index) = ((value_u16 >> i) & 1) as u8;
+                out.add(index).write(((value_u16 >> i) & 1) as u8);
                 index += 1;
             }
         }
+        result.set_len(len);
     }
 
     result
 }
 
+// the byte i of each bit mask is bit i % 8 of the low (i < 8) or the high byte of a value
+#[cfg(all(feature = "std", any(target_arch = "x86_64", target_arch = "aarch64")))]
+const BYTE_BITS: [u8; 16] = [1, 2, 4, 8, 16, 32, 64, 128, 1, 2, 4, 8, 16, 32, 64, 128];
+// spreads the low byte of a value over the first 8 bytes and the high byte over the last 8
+#[cfg(all(feature = "std", any(target_arch = "x86_64", target_arch = "aarch64")))]
+const SPREAD_BYTES: [u8; 16] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1];
+
+// one value per vector: spread its bytes, keep bit i in byte i, turn every nonzero byte into 1
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+#[target_feature(enable = "ssse3")]
+unsafe fn uint16s_to_bits_ssse3<T: ToU16>(data: &[T]) -> Vec<u8> {
+    use core::arch::x86_64::*;
+
+    let len = data.len() * 16;
+    let mut result: Vec<u8> = Vec::with_capacity(len);
+    let spread = _mm_loadu_si128(SPREAD_BYTES.as_ptr() as *const __m128i);
+    let bits = _mm_loadu_si128(BYTE_BITS.as_ptr() as *const __m128i);
+    let ones = _mm_set1_epi8(1);
+    let out = result.as_mut_ptr();
+    for (i, value) in data.iter().enumerate() {
+        let bytes = _mm_shuffle_epi8(_mm_set1_epi16(value.to_u16() as i16), spread);
+        let set = _mm_cmpeq_epi8(_mm_and_si128(bytes, bits), bits);
+        _mm_storeu_si128(out.add(i * 16) as *mut __m128i, _mm_and_si128(set, ones));
+    }
+    result.set_len(len);
+    result
+}
+
+#[cfg(all(feature = "std", target_arch = "aarch64"))]
+#[target_feature(enable = "neon")]
+unsafe fn uint16s_to_bits_neon<T: ToU16>(data: &[T]) -> Vec<u8> {
+    use core::arch::aarch64::*;
+
+    let len = data.len() * 16;
+    let mut result: Vec<u8> = Vec::with_capacity(len);
+    let spread = vld1q_u8(SPREAD_BYTES.as_ptr());
+    let bits = vld1q_u8(BYTE_BITS.as_ptr());
+    let ones = vdupq_n_u8(1);
+    let out = result.as_mut_ptr();
+    for (i, value) in data.iter().enumerate() {
+        let bytes = vqtbl1q_u8(vreinterpretq_u8_u16(vdupq_n_u16(value.to_u16())), spread);
+        vst1q_u8(out.add(i * 16), vandq_u8(vtstq_u8(bytes, bits), ones));
+    }
+    result.set_len(len);
+    result
+}
+
 // pub fn uint16s_to_bits<T: ToU16>(data: &Vec<T>) -> Vec<u8> {
 //     // 每个u16需要2个u8来存储，最后会有16个bit转换成2个u8
 //     let mut result = Vec::with_capacity(data.len() * 2);
@@ -526,6 +823,33 @@ pub fn uint16s_to_bits<T: ToU16>(data: &Vec<T>) -> Vec<u8> {
 //     result
 // }
 
+/** Convert u16s into bits in a given bit order
+
+same as uint16s_to_bits, 16 bits per value, one bit per byte,
+    but the bits of each value come in bit_order, uint16s_to_bits is the Lsb0 case
+
+Args:
+    data: the values to convert
+    bit_order: Lsb0 for bit 0 first, Msb0 for bit 15 first
+
+Returns:
+    Vec<u8>: the bits
+*/
+pub fn uint16s_to_bits_ordered<T: ToU16>(data: &[T], bit_order: BitOrder) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len() * 16);
+    for value in data {
+        let value_u16 = value.to_u16();
+        for i in 0..16 {
+            let shift = match bit_order {
+                BitOrder::Lsb0 => i,
+                BitOrder::Msb0 => 15 - i,
+            };
+            result.push(((value_u16 >> shift) & 1) as u8);
+        }
+    }
+    result
+}
+
 pub fn uint16_to_bit(value: &BinaryFieldElement16) -> Vec<u8> {
     let mut result = Vec::with_capacity(16);
     for i in 0..16 {
@@ -628,12 +952,67 @@ mod tests {
         assert_eq!(a.inv(), BinaryFieldElement16::new(1));
     }
 
+    #[cfg(feature = "inv-table")]
+    #[test]
+    fn test_inv_table() {
+        for x in 1..=u16::MAX {
+            let a = BinaryFieldElement16::new(x);
+            assert_eq!(
+                BinaryFieldElement16::new(INV_TABLE[x as usize]),
+                a.inv_by_pow()
+            );
+            assert_eq!(a * a.inv(), BinaryFieldElement16::new(1));
+        }
+    }
+
     #[test]
     fn test_binary_field_element_pow() {
         let a = BinaryFieldElement16::new(2);
         assert_eq!(a.pow(3), BinaryFieldElement16::new(1));
     }
 
+    // the original recursive pow, kept as a reference for the iterative version
+    fn pow_recursive(x: BinaryFieldElement16, exp: u16) -> BinaryFieldElement16 {
+        if exp == 0 {
+            BinaryFieldElement16::new(1)
+        } else if exp == 1 {
+            x
+        } else if exp == 2 {
+            x * x
+        } else {
+            pow_recursive(x, exp % 2) * pow_recursive(pow_recursive(x, exp / 2), 2)
+        }
+    }
+
+    #[test]
+    fn test_pow_matches_recursive() {
+        for base in 0..=255u16 {
+            let x = BinaryFieldElement16::new(base);
+            for exp in 0..=255u16 {
+                assert_eq!(x.pow(exp), pow_recursive(x, exp));
+            }
+        }
+    }
+
+    #[test]
+    fn test_square() {
+        let a = BinaryFieldElement16::new(2);
+        assert_eq!(a.square(), a * a);
+        assert_eq!(a.square(), BinaryFieldElement16::new(3));
+    }
+
+    #[test]
+    fn test_embed() {
+        for x in (0..=u16::MAX).step_by(257) {
+            for y in (0..=u16::MAX).step_by(263) {
+                let a = BinaryFieldElement16::new(x);
+                let b = BinaryFieldElement16::new(y);
+                assert_eq!(a.embed() * b.embed(), (a * b).embed());
+                assert_eq!(a.embed() + b.embed(), (a + b).embed());
+            }
+        }
+    }
+
     #[test]
     fn test_big_mul() {
         // big_mul(int_to_bigbin(3**29), int_to_bigbin(5**29))= [46732 49627 26993 63626 14101 27237 21150     0]
@@ -646,17 +1025,153 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mul_by_Xi_at() {
+        let x = int_to_bigbin(3u128.pow(70));
+        for n in [1, 2, 4, 8] {
+            let x = x[..n].to_vec();
+            assert_eq!(mul_by_Xi_at(&x, n, MUL_BY_XI_LEVEL), mul_by_Xi(&x, n));
+        }
+
+        // two 8-bit lanes form a 16-bit element, multiplying by its Xi is multiplying by x_3 = 256
+        for v in [0x1234u16, 0xbeef, 0x0100, 0x00ff] {
+            let lanes = vec![v & 0xff, v >> 8];
+            let product = bin_mul(v, 256, None);
+            assert_eq!(
+                mul_by_Xi_at(&lanes, 2, 3),
+                vec![product & 0xff, product >> 8]
+            );
+        }
+    }
+
     #[test]
     fn test_uint16s_to_bits() {
+        // one byte per bit, bit 0 of each value first
         let data = vec![BinaryFieldElement16::new(1u16)];
         let result = uint16s_to_bits(&data);
-        assert_eq!(result, vec![1u8, 0u8]);
-        // test on [[1,3]],check result != [1 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 1 1 0 0 0 0 0 0 0 0 0 0 0 0 0 0]
+        assert_eq!(
+            result,
+            vec![1u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+        // test on [[1,3]], result = [1 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 1 1 0 0 0 0 0 0 0 0 0 0 0 0 0 0]
         let data = vec![
             BinaryFieldElement16::new(1u16),
             BinaryFieldElement16::new(3u16),
         ];
         let result = uint16s_to_bits(&data);
-        assert_eq!(result, vec![1u8, 0u8, 3u8, 0u8]);
+        let mut expected = vec![0u8; 32];
+        expected[0] = 1;
+        expected[16] = 1;
+        expected[17] = 1;
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_uint16s_to_bits_matches_scalar() {
+        // the vectors of test_uint16s_to_bits
+        let data = vec![
+            BinaryFieldElement16::new(1u16),
+            BinaryFieldElement16::new(3u16),
+        ];
+        assert_eq!(uint16s_to_bits(&data), uint16s_to_bits_scalar(&data));
+
+        // every single bit, all ones and mixed bytes
+        let mut values: Vec<u16> = (0..16).map(|i| 1u16 << i).collect();
+        values.extend([0, 0xffff, 0x00ff, 0xff00, 0x8001, 0x1234, 0xabcd]);
+        values.extend((0..1000u32).map(|i| (i * 40503 + 7) as u16));
+        let bits = uint16s_to_bits(&values);
+        assert_eq!(bits, uint16s_to_bits_scalar(&values));
+        assert_eq!(bits, uint16s_to_bits_ordered(&values, BitOrder::Lsb0));
+        assert!(uint16s_to_bits(&Vec::<u16>::new()).is_empty());
+    }
+
+    #[test]
+    fn test_uint16s_to_bits_ordered() {
+        let data = vec![1u16, 3u16];
+        let lsb = uint16s_to_bits_ordered(&data, BitOrder::Lsb0);
+        assert_eq!(
+            lsb,
+            vec![
+                1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0, 0, 0, 0
+            ]
+        );
+        assert_eq!(lsb, uint16s_to_bits(&data));
+        assert_eq!(
+            uint16s_to_bits_ordered(&data, BitOrder::Msb0),
+            vec![
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                0, 0, 1, 1
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_bits() {
+        let mut bits = vec![0u8; 16];
+        bits[0] = 1;
+        bits[15] = 1;
+        assert_eq!(
+            BinaryFieldElement16::from_bits(&bits),
+            BinaryFieldElement16::new(0x8001)
+        );
+        for x in 0..=u16::MAX {
+            let e = BinaryFieldElement16::new(x);
+            assert_eq!(BinaryFieldElement16::from_bits(&uint16_to_bit(&e)), e);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 16 bits")]
+    fn test_from_bits_wrong_length() {
+        BinaryFieldElement16::from_bits(&[1, 0, 1]);
+    }
+
+    #[test]
+    fn test_bigbin_to_int() {
+        // bigbin_to_int([52719 37035 22136  4660     0     0     0     0])= 0x1234567890abcdef
+        let data = vec![52719, 37035, 22136, 4660, 0, 0, 0, 0];
+        assert_eq!(bigbin_to_int(&data), 0x1234567890abcdef);
+        assert_eq!(int_to_bigbin(0x1234567890abcdef), data);
+
+        for v in [
+            0,
+            1,
+            0xffff,
+            0x1_0000,
+            u128::MAX,
+            0x0123_4567_89ab_cdef_fedc_ba98_7654_3210,
+        ] {
+            assert_eq!(bigbin_to_int(&int_to_bigbin(v)), v);
+        }
+    }
+
+    #[test]
+    fn test_bigbin_limb_counts() {
+        // the 8-limb vector with the limb count explicit
+        let data = vec![52719, 37035, 22136, 4660, 0, 0, 0, 0];
+        assert_eq!(int_to_bigbin_n(0x1234567890abcdef, 8), data);
+        assert_eq!(bigbin_to_int_n(&data, 8), 0x1234567890abcdef);
+
+        // a 64-bit element in 4 limbs
+        assert_eq!(int_to_bigbin_n(0x1234567890abcdef, 4), data[..4].to_vec());
+        for v in [0, 1, 0xffff_0000, u64::MAX as u128] {
+            assert_eq!(bigbin_to_int_n(&int_to_bigbin_n(v, 4), 4), v);
+        }
+
+        // a 256-bit element, low word first
+        let words = vec![u128::MAX - 5, 0x1234567890abcdef];
+        let limbs = int_to_bigbin_wide(&words);
+        assert_eq!(limbs.len(), 16);
+        assert_eq!(limbs[8..12], data[..4]);
+        assert_eq!(bigbin_to_words(&limbs), words);
+        assert_eq!(int_to_bigbin_n(7, 16), int_to_bigbin_wide(&[7, 0]));
+        assert_eq!(bigbin_to_int_n(&int_to_bigbin_wide(&[7, 0]), 16), 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit in 4 limbs")]
+    fn test_int_to_bigbin_n_overflow() {
+        int_to_bigbin_n(1 << 64, 4);
     }
 }
@@ -0,0 +1,119 @@
+//! This module defines the `BinaryFieldElement32` struct and its methods.
+//! The `BinaryFieldElement32` struct is a wrapper around `u32`, one level above `BinaryFieldElement16` in the binary tower.
+//! GF(2^32) is built as GF(2^16)[X4] / (X4^2 + X3 * X4 + 1), so the low 16 bits of an element are its GF(2^16) part,
+//! and `BinaryFieldElement16::embed` places a GF(2^16) element into that low half.
+//!
+//! The `BinaryFieldElement32` struct implements the following traits:
+//! - `Add`, `Sub`, `Mul`, and `Neg` for arithmetic operations.
+//!
+//! Additionally, this module provides the following functions:
+//! - `bin_mul32`: Multiplies two 32-bit binary numbers in the binary tower field.
+
+use super::binary_field16::bin_mul;
+use core::ops::{Add, Mul, Neg, Sub};
+
+/**
+A binary field element：a wrapper of u32
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct BinaryFieldElement32 {
+    pub value: u32,
+}
+
+impl BinaryFieldElement32 {
+    pub fn new(value: u32) -> Self {
+        BinaryFieldElement32 { value }
+    }
+}
+
+/** Implement the Add trait for BinaryFieldElement32
+
+   The addition of two binary field elements is the XOR of the two elements
+*/
+impl Add for BinaryFieldElement32 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        BinaryFieldElement32::new(self.value ^ other.value)
+    }
+}
+
+/** Implement the Sub trait for BinaryFieldElement32
+
+   The subtraction of two binary field elements is the same as the addition
+*/
+impl Sub for BinaryFieldElement32 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        self + other
+    }
+}
+
+/** Implement the Neg trait for BinaryFieldElement32
+
+   The negation of a binary field element is the element itself
+*/
+impl Neg for BinaryFieldElement32 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self
+    }
+}
+
+/** Implement the Mul trait for BinaryFieldElement32
+
+   The multiplication of two binary field elements is calculated using the Karatsuba algorithm(implemented in bin_mul32)
+*/
+impl Mul for BinaryFieldElement32 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        BinaryFieldElement32::new(bin_mul32(self.value, other.value))
+    }
+}
+
+/** Multiply v1 * v2 in the binary tower field, one level above bin_mul
+
+   split each input into two GF(2^16) halves and use Karatsuba over them,
+       the same recursion as bin_mul with length = 32, so the halves are multiplied by bin_mul
+
+   Args:
+       v1 (u32): the first element
+       v2 (u32): the second element
+
+   Returns:
+       u32: the product of the two elements
+*/
+pub fn bin_mul32(v1: u32, v2: u32) -> u32 {
+    let (l1, r1) = ((v1 & 0xFFFF) as u16, (v1 >> 16) as u16);
+    let (l2, r2) = ((v2 & 0xFFFF) as u16, (v2 >> 16) as u16);
+
+    // X4^2 reduces to 1 + X4 * X3, and X3 is 1 << 8 inside GF(2^16)
+    let l1l2 = bin_mul(l1, l2, None);
+    let r1r2 = bin_mul(r1, r2, None);
+    let r1r2_high = bin_mul(1 << 8, r1r2, None);
+    let z3 = bin_mul(l1 ^ r1, l2 ^ r2, None);
+    (l1l2 ^ r1r2) as u32 | (((z3 ^ l1l2 ^ r1r2 ^ r1r2_high) as u32) << 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bin_mul32() {
+        // X4 * X4 = X4 * X3 + 1
+        assert_eq!(bin_mul32(1 << 16, 1 << 16), (1 << 24) | 1);
+        assert_eq!(bin_mul32(1, 0x12345678), 0x12345678);
+        assert_eq!(bin_mul32(0, 0x12345678), 0);
+    }
+
+    #[test]
+    fn test_binary_field_element32_mul_commutative() {
+        let a = BinaryFieldElement32::new(0x8765_4321);
+        let b = BinaryFieldElement32::new(0x0bad_f00d);
+        assert_eq!(a * b, b * a);
+    }
+}
@@ -11,18 +11,32 @@
 //! 3. build big mul cache(not work)
 
 use super::binary_field16::BinaryFieldElement16 as B16;
+#[cfg(feature = "inv-table")]
+use super::binary_field16::INV_TABLE;
+use alloc::{vec, vec::Vec};
+use core::fmt;
+#[cfg(feature = "std")]
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+#[cfg(feature = "std")]
 use std::fs;
-use std::sync::Mutex;
 
+// HashMap needs std, without it the cache falls back to a BTreeMap, both serialize as the same map
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as Map;
+#[cfg(feature = "std")]
+use std::collections::HashMap as Map;
+
+// the cache is read-only once built, so it is shared by reference instead of behind a Mutex,
+// concurrent NTTs (e.g. extending rows in parallel) never contend on it
+#[cfg(feature = "std")]
 lazy_static! {
-    pub static ref WI_EVAL_CACHE: Mutex<WiEvalCache> = Mutex::new(load_or_build_wi_eval_cache());
+    pub static ref WI_EVAL_CACHE: WiEvalCache = load_or_build_wi_eval_cache();
 }
+// the default (and largest) cache dimension: the points are GF(2^16) elements, so there are at most 2^16 of them
 const MAX_DIM: usize = 16;
-const MAX_SIZE: usize = 1 << MAX_DIM;
 
+#[cfg(feature = "std")]
 fn load_or_build_wi_eval_cache() -> WiEvalCache {
     let cache_file = "wi_eval_cache.json";
     if let Ok(data) = fs::read_to_string(cache_file) {
@@ -48,23 +62,79 @@ fn load_or_build_wi_eval_cache() -> WiEvalCache {
     cache
 }
 
+/** Build every lazily initialized table before the first commit/prove
+
+WI_EVAL_CACHE (and INV_TABLE with the inv-table feature) are built or loaded from wi_eval_cache.json on first access,
+    so without a warmup the first commit/prove pays that one-time cost;
+    call this once at startup (or before benchmarking) so later calls only read resident tables,
+    calling it again is cheap, the tables are only built once
+*/
+#[cfg(feature = "std")]
+pub fn warmup_caches() {
+    lazy_static::initialize(&WI_EVAL_CACHE);
+    #[cfg(feature = "inv-table")]
+    lazy_static::initialize(&INV_TABLE);
+}
+
+fn default_max_dim() -> usize {
+    MAX_DIM
+}
+
+/** The cache of W_i evaluations
+
+cache[dim][pt] = W_dim(pt) for dim in 0..max_dim and pt in 0..2^max_dim,
+    so an NTT over rows of up to 2^max_dim elements can be served from the cache
+
+Memory cost: max_dim maps of 2^max_dim entries each, i.e. max_dim * 2^max_dim (B16, B16) pairs
+    plus the HashMap overhead; the default max_dim = 16 holds about one million entries,
+    while max_dim = 8 only holds 2048
+*/
 #[derive(Serialize, Deserialize)]
 pub struct WiEvalCache {
-    cache: Vec<HashMap<B16, B16>>,
+    cache: Vec<Map<B16, B16>>,
+    // cache files written before max_dim existed were always built with MAX_DIM
+    #[serde(default = "default_max_dim")]
+    max_dim: usize,
 }
 
 impl WiEvalCache {
     pub fn new() -> Self {
-        WiEvalCache { cache: vec![] }
+        WiEvalCache::with_max_dim(MAX_DIM)
+    }
+
+    /** Create an empty cache that covers rows of up to 2^max_dim elements
+
+    Args:
+        max_dim: the number of dimensions to cache, at most 16 since the points are GF(2^16) elements
+
+    Returns:
+        the empty cache, call build_Wi_eval_cache to fill it
+    */
+    pub fn with_max_dim(max_dim: usize) -> Self {
+        assert!(
+            max_dim >= 1 && max_dim <= MAX_DIM,
+            "max_dim must be in 1..={}, got {}",
+            MAX_DIM,
+            max_dim
+        );
+        WiEvalCache {
+            cache: vec![],
+            max_dim,
+        }
+    }
+
+    pub fn max_dim(&self) -> usize {
+        self.max_dim
     }
 
     pub fn build_Wi_eval_cache(&mut self) -> &mut Self {
-        let mut Wi_eval_cache = vec![HashMap::new(); MAX_DIM];
+        let max_dim = self.max_dim;
+        let mut Wi_eval_cache = vec![Map::new(); max_dim];
         // for wi_eval_cache[0], for all key, value = key
-        for pt in 0..MAX_SIZE {
+        for pt in 0..1usize << max_dim {
             Wi_eval_cache[0].insert(B16::new(pt as u16), B16::new(pt as u16));
         }
-        for dim in 1..MAX_DIM {
+        for dim in 1..max_dim {
             let prev = Wi_eval_cache[dim - 1].clone();
             let prev_quot = Wi_eval_cache[dim - 1]
                 .get(&B16::new(1 << dim))
@@ -72,7 +142,7 @@ impl WiEvalCache {
                 .unwrap();
             let inv_quot = (prev_quot * (prev_quot + B16::new(1))).inv();
             // for each element in prev, get the value and conduct (value_prev_element * (value_prev_element + B16::new(1))) * inv_quot
-            let mut result = HashMap::new();
+            let mut result = Map::new();
             for (key, value) in prev.iter() {
                 result.insert(
                     key.clone(),
@@ -91,7 +161,12 @@ impl WiEvalCache {
         if dim == 0 {
             return coord;
         }
-        self.cache[dim].get(&coord).cloned().unwrap()
+        self.cache[dim].get(&coord).cloned().unwrap_or_else(|| {
+            panic!(
+                "W_{}({}) is outside the cache, which covers points below 2^{}",
+                dim, pt, self.max_dim
+            )
+        })
     }
 }
 
@@ -143,9 +218,43 @@ Appendix: page 4-5 of https://arxiv.org/pdf/1802.03932
 // }
 
 // Optimized iterative version: save 46% of the time
-fn additive_ntt(vals: &Vec<B16>, start: usize) -> Vec<B16> {
-    let mut results = vals.clone();
-    let size = results.len();
+// the allocating additive ntt, a thin wrapper of additive_ntt_inplace on a copy of vals
+#[cfg(feature = "std")]
+pub fn additive_ntt(vals: &[B16], start: usize) -> Vec<B16> {
+    let mut results = vals.to_vec();
+    additive_ntt_inplace(&mut results, start);
+    results
+}
+
+/** additive ntt in place: same as additive_ntt, but overwrites the coefficients with the evaluations
+
+lets callers that already own the buffer (e.g. extend after zero-padding) skip the clone in additive_ntt
+
+Args:
+    vals: the coefficients of the polynomial, replaced by its evaluations
+    start: the start index of the polynomial
+*/
+#[cfg(feature = "std")]
+pub fn additive_ntt_inplace(vals: &mut [B16], start: usize) {
+    additive_ntt_inplace_with_cache(vals, start, &WI_EVAL_CACHE);
+}
+
+/** additive ntt in place, with the Wi-eval cache passed explicitly
+
+same as additive_ntt_inplace, but reads wi_eval_cache instead of the global WI_EVAL_CACHE,
+    so it also runs without std, where there is no global cache to load from wi_eval_cache.json
+
+Args:
+    vals: the coefficients of the polynomial, replaced by its evaluations
+    start: the start index of the polynomial
+    wi_eval_cache: a built cache covering start + vals.len() points
+*/
+pub fn additive_ntt_inplace_with_cache(
+    vals: &mut [B16],
+    start: usize,
+    wi_eval_cache: &WiEvalCache,
+) {
+    let size = vals.len();
     let mut step = size;
 
     while step >= 2 {
@@ -153,22 +262,19 @@ fn additive_ntt(vals: &Vec<B16>, start: usize) -> Vec<B16> {
         let halflen = step;
 
         for i in (0..size).step_by(step * 2) {
-            let coeff1 = {
-                let wi_eval_cache = WI_EVAL_CACHE.lock().unwrap();
-                wi_eval_cache.get_Wi_eval((halflen as f64).log2() as usize, (start + i) as u16)
-            };
+            // halflen is a power of two, so its log2 is its number of trailing zeros
+            let coeff1 =
+                wi_eval_cache.get_Wi_eval(halflen.trailing_zeros() as usize, (start + i) as u16);
 
             for j in 0..halflen {
-                let l = results[i + j];
-                let r = results[i + j + halflen];
+                let l = vals[i + j];
+                let r = vals[i + j + halflen];
                 let sub_input1 = l + r * coeff1;
-                results[i + j] = sub_input1;
-                results[i + j + halflen] = sub_input1 + r;
+                vals[i + j] = sub_input1;
+                vals[i + j + halflen] = sub_input1 + r;
             }
         }
     }
-
-    results
 }
 
 /** inverse additive ntt: Converts evaluations into a polynomial with coefficients
@@ -210,13 +316,22 @@ Returns:
 // }
 
 // Optimized iterative version: save 15% of the time
-fn inv_additive_ntt(vals: &Vec<B16>, start: usize) -> Vec<B16> {
+#[cfg(feature = "std")]
+pub(crate) fn inv_additive_ntt(vals: &[B16], start: usize) -> Vec<B16> {
+    inv_additive_ntt_with_cache(vals, start, &WI_EVAL_CACHE)
+}
+
+fn inv_additive_ntt_with_cache(
+    vals: &[B16],
+    start: usize,
+    wi_eval_cache: &WiEvalCache,
+) -> Vec<B16> {
     let size = vals.len();
     if size == 1 {
-        return vals.clone();
+        return vals.to_vec();
     }
 
-    let mut results = vals.clone();
+    let mut results = vals.to_vec();
     let mut step = 1;
     while step < size {
         let halflen = step;
@@ -224,10 +339,8 @@ fn inv_additive_ntt(vals: &Vec<B16>, start: usize) -> Vec<B16> {
 
         for i in (0..size).step_by(step) {
             // 获取系数
-            let coeff1 = {
-                let wi_eval_cache = WI_EVAL_CACHE.lock().unwrap();
-                wi_eval_cache.get_Wi_eval((halflen as f64).log2() as usize, (start + i) as u16)
-            };
+            let coeff1 =
+                wi_eval_cache.get_Wi_eval(halflen.trailing_zeros() as usize, (start + i) as u16);
             let coeff2 = coeff1 + B16::new(1);
 
             for j in 0..halflen {
@@ -251,10 +364,12 @@ the logic of the function is that:
     then expend the row by expansion_factor times(e,g, 2 times),
     then padding the row with 0s after the original row(e.g. expansion_factor - 1 times)
     then use the additive_ntt to convert the row into evaluations
+    the NTTs only cover power-of-two lengths, so extend panics unless data.len() and expansion_factor
+    are powers of two, extend_checked returns an NttError instead
 
 Args:
     data: the coefficients of the polynomial, one row of the matrix before extension
-    expansion_factor: the expansion factor
+    expansion_factor: the expansion factor, a power of two
 
 Returns:
     the coefficients of the extended polynomial
@@ -265,9 +380,71 @@ Returns:
 //     o.extend(vec![B16::new(0); data.len() * (expansion_factor - 1)]);
 //     additive_ntt(&o, 0)
 // }
+#[cfg(feature = "std")]
 pub fn extend(data: &Vec<B16>, expansion_factor: usize) -> Vec<B16> {
+    extend_at(data, expansion_factor, 0)
+}
+
+/** Reed-Solomon extension, with the Wi-eval cache passed explicitly
+
+same as extend, but reads wi_eval_cache instead of the global WI_EVAL_CACHE, e.g. without std
+
+Args:
+    data: the coefficients of the polynomial, one row of the matrix before extension
+    expansion_factor: the expansion factor
+    wi_eval_cache: a built cache covering data.len() * expansion_factor points
+
+Returns:
+    the coefficients of the extended polynomial
+*/
+pub fn extend_with_cache(
+    data: &[B16],
+    expansion_factor: usize,
+    wi_eval_cache: &WiEvalCache,
+) -> Vec<B16> {
+    extend_at_with_cache(data, expansion_factor, 0, wi_eval_cache)
+}
+
+/** Reed-Solomon extension over the domain starting at start
+
+same as extend, but the row is read as the evaluations over start..start + data.len(),
+    and the extended row is the evaluations over start..start + data.len() * expansion_factor,
+    so extend(data, expansion_factor) = extend_at(data, expansion_factor, 0)
+    start + data.len() * expansion_factor must stay within the Wi-eval cache, i.e. at most 2^16
+
+Args:
+    data: the evaluations of the polynomial over the domain starting at start
+    expansion_factor: the expansion factor
+    start: the first point of the evaluation domain
+
+Returns:
+    the evaluations of the polynomial over the extended domain
+*/
+#[cfg(feature = "std")]
+pub fn extend_at(data: &[B16], expansion_factor: usize, start: usize) -> Vec<B16> {
+    extend_at_with_cache(data, expansion_factor, start, &WI_EVAL_CACHE)
+}
+
+// extend_at with the Wi-eval cache passed explicitly
+pub fn extend_at_with_cache(
+    data: &[B16],
+    expansion_factor: usize,
+    start: usize,
+    wi_eval_cache: &WiEvalCache,
+) -> Vec<B16> {
+    // checked in release builds too: for any other length the NTTs return a wrong codeword, not a panic
+    assert!(
+        data.len().is_power_of_two(),
+        "row length must be a power of two, got {}",
+        data.len()
+    );
+    assert!(
+        expansion_factor.is_power_of_two(),
+        "expansion factor must be a power of two, got {}",
+        expansion_factor
+    );
     // Avoid unnecessary clone by passing reference
-    let mut o = inv_additive_ntt(data, 0);
+    let mut o = inv_additive_ntt_with_cache(data, start, wi_eval_cache);
 
     // Calculate the total length after expansion
     let total_len = data.len() * expansion_factor;
@@ -278,10 +455,90 @@ pub fn extend(data: &Vec<B16>, expansion_factor: usize) -> Vec<B16> {
     // Extend the vector with zeros
     o.extend((0..(total_len - o.len())).map(|_| B16::new(0)));
 
-    additive_ntt(&o, 0)
+    // o is already owned, transform it in place instead of cloning it in additive_ntt
+    additive_ntt_inplace_with_cache(&mut o, start, wi_eval_cache);
+    o
 }
 
-#[cfg(test)]
+/** Inverse of extend: recover the original row from an extended row
+
+the logic mirrors extend:
+    first use inv_additive_ntt to convert the extended row into coefficients,
+    then truncate the coefficients to extended.len() / expansion_factor
+    (for a codeword produced by extend, the truncated coefficients are the zero padding),
+    then use the additive_ntt to convert the remaining coefficients back into the original row
+
+Args:
+    extended: the extended row, the output of extend
+    expansion_factor: the expansion factor used by extend
+
+Returns:
+    the original row before extension
+*/
+#[cfg(feature = "std")]
+pub fn unextend(extended: &[B16], expansion_factor: usize) -> Vec<B16> {
+    let mut o = inv_additive_ntt(extended, 0);
+    let row_length = extended.len() / expansion_factor;
+    debug_assert!(
+        o[row_length..].iter().all(|&c| c == B16::new(0)),
+        "not a codeword of extend: the coefficients beyond the original row are not zero"
+    );
+    o.truncate(row_length);
+    additive_ntt_inplace(&mut o, 0);
+    o
+}
+
+/** The errors extend_checked reports instead of producing a corrupt extension
+
+the iterative NTTs halve/double step until it reaches the row length,
+    which only lands exactly on the row length when it is a power of two
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NttError {
+    LengthNotPowerOfTwo(usize),
+    ExpansionFactorNotPowerOfTwo(usize),
+}
+
+impl fmt::Display for NttError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NttError::LengthNotPowerOfTwo(len) => {
+                write!(f, "row length must be a power of two, got {}", len)
+            }
+            NttError::ExpansionFactorNotPowerOfTwo(factor) => {
+                write!(f, "expansion factor must be a power of two, got {}", factor)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NttError {}
+
+/** Reed-Solomon extension that validates its input
+
+same as extend, but returns an error instead of a corrupt extension
+    when the row length or the expansion factor is not a power of two
+
+Args:
+    data: the coefficients of the polynomial, one row of the matrix before extension
+    expansion_factor: the expansion factor
+
+Returns:
+    the coefficients of the extended polynomial, or the NttError describing the invalid input
+*/
+#[cfg(feature = "std")]
+pub fn extend_checked(data: &[B16], expansion_factor: usize) -> Result<Vec<B16>, NttError> {
+    if !data.len().is_power_of_two() {
+        return Err(NttError::LengthNotPowerOfTwo(data.len()));
+    }
+    if !expansion_factor.is_power_of_two() {
+        return Err(NttError::ExpansionFactorNotPowerOfTwo(expansion_factor));
+    }
+    Ok(extend_at(data, expansion_factor, 0))
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -295,6 +552,43 @@ mod tests {
         assert_eq!(result, B16::new(1));
     }
 
+    #[test]
+    fn test_get_Wi_eval_with_max_dim() {
+        let mut wi_eval_cache = WiEvalCache::with_max_dim(8);
+        wi_eval_cache.build_Wi_eval_cache();
+        assert_eq!(wi_eval_cache.max_dim(), 8);
+        assert_eq!(wi_eval_cache.get_Wi_eval(2, 4), B16::new(1));
+
+        // the smaller cache agrees with the default one on its whole range
+        let mut full_cache = WiEvalCache::new();
+        full_cache.build_Wi_eval_cache();
+        for dim in 0..8 {
+            for pt in (0..256).step_by(17) {
+                assert_eq!(
+                    wi_eval_cache.get_Wi_eval(dim, pt),
+                    full_cache.get_Wi_eval(dim, pt)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_extend_concurrent() {
+        let rows: Vec<Vec<B16>> = (0..8u16)
+            .map(|r| (0..16u16).map(|j| B16::new(r * 31 + j * 7 + 1)).collect())
+            .collect();
+        let expected: Vec<Vec<B16>> = rows.iter().map(|row| extend(row, 4)).collect();
+
+        let results: Vec<Vec<B16>> = std::thread::scope(|s| {
+            let handles: Vec<_> = rows
+                .iter()
+                .map(|row| s.spawn(move || extend(row, 4)))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(results, expected);
+    }
+
     #[test]
     fn test_cache_file_creation() {
         let cache_file = "wi_eval_cache.json";
@@ -312,6 +606,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_additive_ntt_inplace() {
+        let mut vals = vec![B16::new(1), B16::new(2), B16::new(3), B16::new(4)];
+        additive_ntt_inplace(&mut vals, 0);
+        assert_eq!(
+            vals,
+            vec![B16::new(1), B16::new(3), B16::new(9), B16::new(15)]
+        );
+    }
+
     #[test]
     fn test_inv_additive_ntt() {
         let vals = vec![B16::new(1), B16::new(3), B16::new(9), B16::new(15)];
@@ -322,6 +626,44 @@ mod tests {
         );
     }
 
+    // the original recursive inv_additive_ntt, the reference for the iterative version
+    fn inv_additive_ntt_recursive(vals: &[B16], start: usize) -> Vec<B16> {
+        if vals.len() == 1 {
+            return vals.to_vec();
+        }
+        let halflen = vals.len() / 2;
+        let l = inv_additive_ntt_recursive(&vals[..halflen], start);
+        let r = inv_additive_ntt_recursive(&vals[halflen..], start + halflen);
+        let coeff1 = WI_EVAL_CACHE.get_Wi_eval(halflen.trailing_zeros() as usize, start as u16);
+        let coeff2 = coeff1 + B16::new(1);
+        let mut o: Vec<_> = l
+            .iter()
+            .zip(r.iter())
+            .map(|(i, j)| *i * coeff2 + *j * coeff1)
+            .collect();
+        o.extend(l.iter().zip(r.iter()).map(|(i, j)| *i + *j));
+        o
+    }
+
+    #[test]
+    fn test_inv_additive_ntt_matches_recursive() {
+        let vals: Vec<B16> = [3u16, 1, 4, 1, 5, 9, 2, 6]
+            .iter()
+            .map(|&v| B16::new(v))
+            .collect();
+        for start in [0, 4, 8] {
+            let result = inv_additive_ntt(&vals, start);
+            assert_eq!(
+                result,
+                inv_additive_ntt_recursive(&vals, start),
+                "start {}",
+                start
+            );
+            // and additive_ntt at the same start undoes it
+            assert_eq!(additive_ntt(&result, start), vals, "start {}", start);
+        }
+    }
+
     #[test]
     fn test_extend() {
         let data = vec![B16::new(1), B16::new(3), B16::new(9), B16::new(15)];
@@ -341,4 +683,96 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_extend_with_cache() {
+        // a small cache passed explicitly agrees with the global one wherever it covers the points
+        let mut cache = WiEvalCache::with_max_dim(4);
+        cache.build_Wi_eval_cache();
+        let data: Vec<B16> = (0..8).map(|i| B16::new(i * 7 + 2)).collect();
+        assert_eq!(extend_with_cache(&data, 2, &cache), extend(&data, 2));
+        assert_eq!(
+            extend_at_with_cache(&data[..4], 2, 8, &cache),
+            extend_at(&data[..4], 2, 8)
+        );
+
+        let mut vals = data.clone();
+        additive_ntt_inplace_with_cache(&mut vals, 0, &cache);
+        assert_eq!(vals, additive_ntt(&data, 0));
+    }
+
+    #[test]
+    fn test_extend_at() {
+        let data = vec![B16::new(1), B16::new(3), B16::new(9), B16::new(15)];
+        assert_eq!(extend_at(&data, 2, 0), extend(&data, 2));
+
+        // on the coset starting at 8, the extension still interpolates the polynomial behind data
+        let start = 8;
+        let result = extend_at(&data, 2, start);
+        assert_eq!(result.len(), 8);
+        assert_eq!(&result[..4], &data[..]);
+        let mut coeffs = inv_additive_ntt(&data, start);
+        coeffs.resize(8, B16::new(0));
+        assert_eq!(inv_additive_ntt(&result, start), coeffs);
+    }
+
+    #[test]
+    fn test_warmup_caches() {
+        warmup_caches();
+        let cache: *const WiEvalCache = &*WI_EVAL_CACHE;
+        warmup_caches();
+        // the second warmup reuses the resident cache instead of loading it again
+        assert!(std::ptr::eq(cache, &*WI_EVAL_CACHE));
+        assert_eq!(WI_EVAL_CACHE.get_Wi_eval(2, 4), B16::new(1));
+    }
+
+    #[test]
+    fn test_extend_checked() {
+        let data = vec![B16::new(1), B16::new(3), B16::new(9), B16::new(15)];
+        assert_eq!(extend_checked(&data, 2), Ok(extend(&data, 2)));
+        assert_eq!(
+            extend_checked(&data[..3], 2),
+            Err(NttError::LengthNotPowerOfTwo(3))
+        );
+        assert_eq!(
+            extend_checked(&data, 3),
+            Err(NttError::ExpansionFactorNotPowerOfTwo(3))
+        );
+        assert_eq!(
+            extend_checked(&[], 2),
+            Err(NttError::LengthNotPowerOfTwo(0))
+        );
+    }
+
+    #[test]
+    fn test_extend_expansion_factors() {
+        let row: Vec<B16> = (0..8).map(|i| B16::new(i * 1031 + 5)).collect();
+        for expansion_factor in [2, 4] {
+            let extended = extend(&row, expansion_factor);
+            assert_eq!(extended.len(), row.len() * expansion_factor);
+            // systematic: the row is the start of its extension
+            assert_eq!(extended[..row.len()], row[..]);
+            assert_eq!(unextend(&extended, expansion_factor), row);
+        }
+        assert_eq!(
+            extend_checked(&row, 3),
+            Err(NttError::ExpansionFactorNotPowerOfTwo(3))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expansion factor must be a power of two, got 3")]
+    fn test_extend_rejects_expansion_factor_3() {
+        let row: Vec<B16> = (0..8).map(|i| B16::new(i + 1)).collect();
+        extend(&row, 3);
+    }
+
+    #[test]
+    fn test_unextend() {
+        let data = vec![B16::new(1), B16::new(3), B16::new(9), B16::new(15)];
+        assert_eq!(unextend(&extend(&data, 2), 2), data);
+
+        let row: Vec<B16> = (0..16).map(|i| B16::new(i * 4099 + 7)).collect();
+        assert_eq!(unextend(&extend(&row, 8), 8), row);
+    }
 }
@@ -2,8 +2,12 @@
 //! Prover and Verifier can obtain challenges in a deterministic manner.
 //! The challenges are derived from the root of the Merkle tree, and the returned results are the indexes of the columns.
 //! Note: This oracle is intended for testing purposes only. In a real-world scenario, it should be replaced by the Fiat-Shamir heuristic.
+//! Transcript is that Fiat-Shamir heuristic: the challenges depend on everything absorbed, not only on the root.
 
-use super::merkle_tree::hash;
+use super::merkle_tree::{MerkleHasher, Sha256Hasher};
+use std::collections::HashSet;
+use std::fmt;
+use std::marker::PhantomData;
 
 /** Get challenges from the root of the Merkle tree
 
@@ -16,28 +20,563 @@ Returns:
     Vec<u16>: the challenges, indexes of the columns
 */
 pub fn get_challenges(root: &[u8], extended_row_length: usize, num_challenges: usize) -> Vec<u16> {
-    let mut o = vec![];
-    for i in 0..num_challenges {
-        let mut bytes = root.to_vec();
-        bytes.push(i as u8);
-        let hash = hash(&bytes);
-        let challenge =
-            u16::from_le_bytes(hash[0..2].try_into().unwrap()) % extended_row_length as u16;
-        o.push(challenge);
+    get_challenges_with_hasher::<Sha256Hasher>(root, extended_row_length, num_challenges)
+}
+
+/** Get challenges from the root of the Merkle tree, hashing with H
+
+the challenges must be derived with the same hash as the Merkle tree,
+    so a tree built with merkelize::<H> is paired with get_challenges_with_hasher::<H>
+
+Args:
+    root: the root of the Merkle tree
+    extended_row_length: the length of the extended row
+    num_challenges: the number of challenges
+
+Returns:
+    Vec<u16>: the challenges, indexes of the columns
+*/
+pub fn get_challenges_with_hasher<H: MerkleHasher>(
+    root: &[u8],
+    extended_row_length: usize,
+    num_challenges: usize,
+) -> Vec<u16> {
+    // original implementation: biased towards the low indices when extended_row_length does not divide 2^16
+    // for i in 0..num_challenges {
+    //     let mut bytes = root.to_vec();
+    //     bytes.push(i as u8);
+    //     let hash = H::hash(&bytes);
+    //     let challenge =
+    //         u16::from_le_bytes(hash[0..2].try_into().unwrap()) % extended_row_length as u16;
+    //     o.push(challenge);
+    // }
+
+    get_challenges_with_version::<H>(
+        root,
+        extended_row_length,
+        num_challenges,
+        TRANSCRIPT_VERSION,
+    )
+}
+
+/** Get challenges from the root of the Merkle tree, separated from other protocols by a label
+
+the label is prepended to every transcript, label || root || counter,
+    so the same root gives unrelated challenges in different contexts,
+    get_challenges is get_challenges_labeled with an empty label
+
+Args:
+    root: the root of the Merkle tree
+    label: the domain-separation label, e.g. b"binius-fri-query"
+    extended_row_length: the length of the extended row
+    num_challenges: the number of challenges
+
+Returns:
+    Vec<u16>: the challenges, indexes of the columns
+*/
+pub fn get_challenges_labeled(
+    root: &[u8],
+    label: &[u8],
+    extended_row_length: usize,
+    num_challenges: usize,
+) -> Vec<u16> {
+    draw_challenges::<Sha256Hasher>(root, label, extended_row_length, 0.., TRANSCRIPT_VERSION)
+        .take(num_challenges)
+        .collect()
+}
+
+/** How the draw counter is appended to the root in the transcript root || counter
+
+V1: a single byte, so the counter wraps after 256 draws and later draws repeat the earlier ones
+V2: 4 little-endian bytes, every draw has its own transcript
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptVersion {
+    V1,
+    V2,
+}
+
+impl TranscriptVersion {
+    fn push_counter(self, bytes: &mut Vec<u8>, counter: usize) {
+        match self {
+            TranscriptVersion::V1 => bytes.push(counter as u8),
+            TranscriptVersion::V2 => bytes.extend((counter as u32).to_le_bytes()),
+        }
+    }
+
+    // the number of distinct transcripts
+    fn counter_limit(self) -> usize {
+        match self {
+            TranscriptVersion::V1 => 1 << 8,
+            TranscriptVersion::V2 => 1 << 32,
+        }
+    }
+}
+
+// the transcript used by get_challenges and pcs, proofs made with V1 transcripts do not verify under V2
+pub const TRANSCRIPT_VERSION: TranscriptVersion = TranscriptVersion::V2;
+
+/** Get challenges from the root of the Merkle tree with the given transcript version
+
+Args:
+    root: the root of the Merkle tree
+    extended_row_length: the length of the extended row
+    num_challenges: the number of challenges
+    version: the TranscriptVersion, V1 reproduces the challenges of the single-byte counter
+
+Returns:
+    Vec<u16>: the challenges, indexes of the columns
+*/
+pub fn get_challenges_with_version<H: MerkleHasher>(
+    root: &[u8],
+    extended_row_length: usize,
+    num_challenges: usize,
+    version: TranscriptVersion,
+) -> Vec<u16> {
+    draw_challenges::<H>(root, &[], extended_row_length, 0.., version)
+        .take(num_challenges)
+        .collect()
+}
+
+/** The errors get_distinct_challenges reports instead of looping forever */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChallengeError {
+    // there are fewer columns than requested distinct challenges
+    TooManyChallenges {
+        num_challenges: usize,
+        extended_row_length: usize,
+    },
+    // every transcript counter was used before enough distinct challenges were drawn
+    CounterExhausted,
+    // a fixed challenge set has another number of indices than num_challenges
+    FixedCount {
+        expected: usize,
+        actual: usize,
+    },
+    // a fixed challenge is not a column of the extended rows
+    OutOfRange {
+        challenge: u16,
+        extended_row_length: usize,
+    },
+}
+
+impl fmt::Display for ChallengeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChallengeError::TooManyChallenges {
+                num_challenges,
+                extended_row_length,
+            } => write!(
+                f,
+                "cannot draw {} distinct challenges from {} columns",
+                num_challenges, extended_row_length
+            ),
+            ChallengeError::CounterExhausted => {
+                write!(
+                    f,
+                    "ran out of transcript counters before drawing enough distinct challenges"
+                )
+            }
+            ChallengeError::FixedCount { expected, actual } => write!(
+                f,
+                "{} fixed challenges were given, num_challenges is {}",
+                actual, expected
+            ),
+            ChallengeError::OutOfRange {
+                challenge,
+                extended_row_length,
+            } => write!(
+                f,
+                "challenge {} is out of the {} columns",
+                challenge, extended_row_length
+            ),
+        }
     }
-    o
+}
+
+impl std::error::Error for ChallengeError {}
+
+/** Get distinct challenges from the root of the Merkle tree
+
+same draws as get_challenges, but an index already drawn is skipped (the counter still advances),
+    so every queried column is distinct and the proof really opens num_challenges columns
+
+Args:
+    root: the root of the Merkle tree
+    extended_row_length: the length of the extended row
+    num_challenges: the number of challenges, at most extended_row_length
+
+Returns:
+    Vec<u16>: the distinct challenges, indexes of the columns, or the ChallengeError
+*/
+pub fn get_distinct_challenges(
+    root: &[u8],
+    extended_row_length: usize,
+    num_challenges: usize,
+) -> Result<Vec<u16>, ChallengeError> {
+    get_distinct_challenges_with_hasher::<Sha256Hasher>(
+        root,
+        &[],
+        extended_row_length,
+        num_challenges,
+    )
+}
+
+/** get_distinct_challenges with the transcripts label || root || counter hashed by H, see get_challenges_labeled */
+pub fn get_distinct_challenges_with_hasher<H: MerkleHasher>(
+    root: &[u8],
+    label: &[u8],
+    extended_row_length: usize,
+    num_challenges: usize,
+) -> Result<Vec<u16>, ChallengeError> {
+    if num_challenges > extended_row_length {
+        return Err(ChallengeError::TooManyChallenges {
+            num_challenges,
+            extended_row_length,
+        });
+    }
+    let mut seen = HashSet::new();
+    let mut o = Vec::with_capacity(num_challenges);
+    let counters = 0..TRANSCRIPT_VERSION.counter_limit();
+    for challenge in draw_challenges::<H>(
+        root,
+        label,
+        extended_row_length,
+        counters,
+        TRANSCRIPT_VERSION,
+    ) {
+        if o.len() == num_challenges {
+            break;
+        }
+        if seen.insert(challenge) {
+            o.push(challenge);
+        }
+    }
+    if o.len() < num_challenges {
+        return Err(ChallengeError::CounterExhausted);
+    }
+    Ok(o)
+}
+
+/** The challenges as a sorted set of column indices
+
+the columns are gathered in this order, walking the columns once from left to right
+    instead of jumping around them in draw order, a repeated challenge is gathered once
+
+Args:
+    challenges: the challenges in draw order
+
+Returns:
+    Vec<u16>: the distinct challenges in increasing order
+*/
+pub fn challenge_index_set(challenges: &[u16]) -> Vec<u16> {
+    let mut indices = challenges.to_vec();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+/** Draw challenges from the transcripts label || root || counter, for each counter in counters
+
+rejection sampling: draws in the last (2^16 mod extended_row_length) values are skipped (the next counter is used),
+    so every index is hit by the same number of u16 values,
+    for a power-of-two extended_row_length nothing is rejected
+*/
+fn draw_challenges<'a, H: MerkleHasher>(
+    root: &'a [u8],
+    label: &'a [u8],
+    extended_row_length: usize,
+    counters: impl Iterator<Item = usize> + 'a,
+    version: TranscriptVersion,
+) -> impl Iterator<Item = u16> + 'a {
+    assert!(
+        extended_row_length >= 1 && extended_row_length <= 1 << 16,
+        "challenges are sampled from 16 bits, extended_row_length must be in 1..=2^16"
+    );
+    let limit = (1u32 << 16) - (1u32 << 16) % extended_row_length as u32;
+    counters.filter_map(move |counter| {
+        let mut bytes = [label, root].concat();
+        version.push_counter(&mut bytes, counter);
+        let hash = H::hash(&bytes);
+        let sample = u16::from_le_bytes(hash[0..2].try_into().unwrap()) as u32;
+        if sample < limit {
+            Some((sample % extended_row_length as u32) as u16)
+        } else {
+            None
+        }
+    })
+}
+
+/** A Fiat-Shamir transcript: absorb everything the prover sends, then squeeze challenges bound to all of it
+
+the state is a hash chain, absorb sets state = H(state || len(bytes) as u64 LE || bytes),
+    the length prefix keeps absorb(ab) and absorb(a), absorb(b) apart,
+    and the k-th squeeze since the last absorb hashes state || k as u32 LE
+*/
+pub struct Transcript<H: MerkleHasher = Sha256Hasher> {
+    state: Vec<u8>,
+    counter: u32,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: MerkleHasher> Transcript<H> {
+    pub fn new() -> Self {
+        Transcript {
+            state: vec![],
+            counter: 0,
+            _hasher: PhantomData,
+        }
+    }
+
+    pub fn absorb(&mut self, bytes: &[u8]) {
+        self.state =
+            H::hash(&[&self.state, &(bytes.len() as u64).to_le_bytes()[..], bytes].concat());
+        self.counter = 0;
+    }
+
+    /** Squeeze a uniform index in 0..modulus, by rejection sampling 16-bit draws like get_challenges */
+    pub fn challenge_index(&mut self, modulus: usize) -> u16 {
+        assert!(
+            modulus >= 1 && modulus <= 1 << 16,
+            "challenges are sampled from 16 bits, modulus must be in 1..=2^16"
+        );
+        let limit = (1u32 << 16) - (1u32 << 16) % modulus as u32;
+        loop {
+            let hash = H::hash(&[&self.state, &self.counter.to_le_bytes()[..]].concat());
+            self.counter += 1;
+            let sample = u16::from_le_bytes(hash[0..2].try_into().unwrap()) as u32;
+            if sample < limit {
+                return (sample % modulus as u32) as u16;
+            }
+        }
+    }
+
+    /** Squeeze num_challenges distinct indices in 0..modulus, see get_distinct_challenges */
+    pub fn distinct_challenge_indices(
+        &mut self,
+        modulus: usize,
+        num_challenges: usize,
+    ) -> Result<Vec<u16>, ChallengeError> {
+        if num_challenges > modulus {
+            return Err(ChallengeError::TooManyChallenges {
+                num_challenges,
+                extended_row_length: modulus,
+            });
+        }
+        let mut seen = HashSet::new();
+        let mut o = Vec::with_capacity(num_challenges);
+        while o.len() < num_challenges {
+            let challenge = self.challenge_index(modulus);
+            if seen.insert(challenge) {
+                o.push(challenge);
+            }
+        }
+        Ok(o)
+    }
+
+    /** Grind on everything absorbed so far: the smallest nonce such that hash(state || nonce)
+    starts with difficulty zero bits, see grind
+
+    the prover absorbs the nonce afterwards, so the challenges squeezed next depend on it
+    */
+    pub fn grind(&self, difficulty: u32) -> u64 {
+        grind_with_hasher::<H>(&self.state, difficulty)
+    }
+
+    /** Check a nonce found by grind on the same absorbed messages */
+    pub fn check_grind(&self, nonce: u64, difficulty: u32) -> bool {
+        check_grind_with_hasher::<H>(&self.state, nonce, difficulty)
+    }
+}
+
+/** Proof-of-work grinding: find a nonce such that hash(root || nonce) starts with difficulty zero bits
+
+the prover spends about 2^difficulty hashes, the verifier one (check_grind),
+    and an attacker has to redo the search for every root it tries, which adds difficulty bits of soundness
+    for the price of the search instead of more queried columns
+
+Args:
+    root: the root of the Merkle tree
+    difficulty: the number of leading zero bits required
+
+Returns:
+    u64: the smallest nonce satisfying the difficulty
+*/
+pub fn grind(root: &[u8], difficulty: u32) -> u64 {
+    grind_with_hasher::<Sha256Hasher>(root, difficulty)
+}
+
+pub fn grind_with_hasher<H: MerkleHasher>(root: &[u8], difficulty: u32) -> u64 {
+    (0..u64::MAX)
+        .find(|&nonce| check_grind_with_hasher::<H>(root, nonce, difficulty))
+        .unwrap()
+}
+
+/** Check that hash(root || nonce) starts with difficulty zero bits, the nonce is little-endian */
+pub fn check_grind(root: &[u8], nonce: u64, difficulty: u32) -> bool {
+    check_grind_with_hasher::<Sha256Hasher>(root, nonce, difficulty)
+}
+
+pub fn check_grind_with_hasher<H: MerkleHasher>(root: &[u8], nonce: u64, difficulty: u32) -> bool {
+    let hash = H::hash(&[root, &nonce.to_le_bytes()].concat());
+    leading_zero_bits(&hash) >= difficulty
+}
+
+// the leading zero bits of the hash, reading each byte from its most significant bit
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut zeros = 0;
+    for &byte in hash {
+        zeros += byte.leading_zeros();
+        if byte != 0 {
+            break;
+        }
+    }
+    zeros
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_challenge_index_set() {
+        assert_eq!(challenge_index_set(&[9, 2, 7, 2, 0, 9]), vec![0, 2, 7, 9]);
+        assert_eq!(challenge_index_set(&[]), Vec::<u16>::new());
+    }
+
     #[test]
     fn test_get_challenges() {
         let root = vec![1, 2, 3, 4];
         let extended_row_length = 8;
         let num_challenges = 2;
-        let result = get_challenges(&root, extended_row_length, num_challenges);
+        let result = get_challenges_with_version::<Sha256Hasher>(
+            &root,
+            extended_row_length,
+            num_challenges,
+            TranscriptVersion::V1,
+        );
         assert_eq!(result, vec![6, 0]);
+        let result = get_challenges(&root, extended_row_length, num_challenges);
+        assert_eq!(result, vec![2, 6]);
+    }
+
+    #[test]
+    fn test_get_challenges_uniform() {
+        // 2^16 mod 24000 = 17536, so the plain modulo would hit indices below 17536 with probability 3/2^16
+        // instead of 2/2^16, i.e. about 80% of the draws instead of the uniform 73%
+        let extended_row_length = 24000;
+        let mut low = 0;
+        let mut total = 0;
+        for r in 0..4000u32 {
+            for c in get_challenges(&r.to_le_bytes(), extended_row_length, 8) {
+                assert!((c as usize) < extended_row_length);
+                if c < 17536 {
+                    low += 1;
+                }
+                total += 1;
+            }
+        }
+        let fraction = low as f64 / total as f64;
+        assert!(
+            (fraction - 17536.0 / 24000.0).abs() < 0.02,
+            "fraction {}",
+            fraction
+        );
+    }
+
+    #[test]
+    fn test_get_distinct_challenges() {
+        let root = vec![1, 2, 3, 4];
+        let challenges = get_distinct_challenges(&root, 16, 12).unwrap();
+        assert_eq!(challenges.len(), 12);
+        let unique: HashSet<u16> = challenges.iter().copied().collect();
+        assert_eq!(unique.len(), 12);
+        assert!(challenges.iter().all(|&c| c < 16));
+
+        assert_eq!(
+            get_distinct_challenges(&root, 8, 9),
+            Err(ChallengeError::TooManyChallenges {
+                num_challenges: 9,
+                extended_row_length: 8
+            })
+        );
+    }
+
+    #[test]
+    fn test_get_challenges_beyond_256() {
+        let root = vec![1, 2, 3, 4];
+        // with a single-byte counter, draw 256 + i repeats draw i
+        let v1 =
+            get_challenges_with_version::<Sha256Hasher>(&root, 1 << 16, 300, TranscriptVersion::V1);
+        assert_eq!(v1[256..300], v1[0..44]);
+
+        let v2 = get_challenges(&root, 1 << 16, 300);
+        assert_eq!(v2.len(), 300);
+        assert_ne!(v2[256..300], v2[0..44]);
+        assert_eq!(
+            get_distinct_challenges(&root, 1 << 16, 300).unwrap().len(),
+            300
+        );
+    }
+
+    #[test]
+    fn test_get_challenges_labeled() {
+        let root = vec![1, 2, 3, 4];
+        assert_eq!(
+            get_challenges_labeled(&root, b"", 2048, 32),
+            get_challenges(&root, 2048, 32)
+        );
+        assert_ne!(
+            get_challenges_labeled(&root, b"binius-fri-query", 2048, 32),
+            get_challenges_labeled(&root, b"another-protocol", 2048, 32)
+        );
+    }
+
+    #[test]
+    fn test_grind() {
+        let root = vec![1, 2, 3, 4];
+        let nonce = grind(&root, 8);
+        assert!(check_grind(&root, nonce, 8));
+        let hash = Sha256Hasher::hash(&[root.as_slice(), &nonce.to_le_bytes()].concat());
+        assert_eq!(hash[0], 0);
+        // the search returns the first nonce that works
+        assert!((0..nonce).all(|n| !check_grind(&root, n, 8)));
+        assert!(check_grind(&root, 12345, 0));
+    }
+
+    #[test]
+    fn test_transcript_grind() {
+        let transcript_of = |message: &[u8]| {
+            let mut transcript: Transcript = Transcript::new();
+            transcript.absorb(&[1, 2, 3, 4]);
+            transcript.absorb(message);
+            transcript
+        };
+        let transcript = transcript_of(&[5, 6]);
+        let nonce = transcript.grind(8);
+        assert!(transcript.check_grind(nonce, 8));
+        // the nonce is bound to every absorbed message, not only to the first one
+        let other = transcript_of(&[5, 7]);
+        assert_ne!(other.grind(8), nonce);
+    }
+
+    #[test]
+    fn test_transcript() {
+        let squeeze = |point: &[u8]| {
+            let mut transcript: Transcript = Transcript::new();
+            transcript.absorb(&[1, 2, 3, 4]);
+            transcript.absorb(point);
+            transcript.distinct_challenge_indices(2048, 32).unwrap()
+        };
+        assert_eq!(squeeze(&[5, 6]), squeeze(&[5, 6]));
+        assert_ne!(squeeze(&[5, 6]), squeeze(&[5, 7]));
+
+        // the length prefix separates the absorbed messages
+        let mut a: Transcript = Transcript::new();
+        a.absorb(&[1, 2]);
+        let mut b: Transcript = Transcript::new();
+        b.absorb(&[1]);
+        b.absorb(&[2]);
+        assert_ne!(a.challenge_index(1 << 16), b.challenge_index(1 << 16));
     }
 }
@@ -0,0 +1,199 @@
+//! This module defines BiniusError, the one error type of the user-facing entry points.
+//!
+//! Each step keeps its own error (VerifyError, ProveError, NttError, PackError, LayoutError),
+//! they all convert into BiniusError, so a caller chaining commit_checked, prove_checked and verifier
+//! can use ? on every step and match on one enum.
+
+use super::binary_ntt_cache::NttError;
+use super::pcs::{ProveError, VerifyError};
+use super::utils::{LayoutError, PackError};
+use std::fmt;
+
+/** Why a user-facing entry point rejected its input or a proof */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BiniusError {
+    // a length does not match the one the input, the commitment or the params require
+    InvalidLength {
+        expected: usize,
+        actual: usize,
+    },
+    // a length or a factor must be a power of two
+    NotPowerOfTwo(usize),
+    // the extended rows have fewer columns than the distinct challenges to draw
+    TooManyChallenges {
+        num_challenges: usize,
+        extended_row_length: usize,
+    },
+    // the Merkle branch of the index-th queried column does not lead to the root
+    BranchMismatch {
+        index: usize,
+    },
+    // t_prime does not evaluate to the claimed eval
+    EvalMismatch,
+    // a matrix only has the axes 0 and 1
+    UnsupportedAxis(usize),
+    // any other reason the verifier rejected the proof
+    Verify(VerifyError),
+}
+
+impl fmt::Display for BiniusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BiniusError::InvalidLength { expected, actual } => {
+                write!(f, "invalid length {}, expected {}", actual, expected)
+            }
+            BiniusError::NotPowerOfTwo(n) => write!(f, "{} is not a power of two", n),
+            BiniusError::TooManyChallenges {
+                num_challenges,
+                extended_row_length,
+            } => write!(
+                f,
+                "{} distinct challenges cannot be drawn from {} columns",
+                num_challenges, extended_row_length
+            ),
+            BiniusError::BranchMismatch { index } => write!(
+                f,
+                "Merkle branch of queried column {} does not match the root",
+                index
+            ),
+            BiniusError::EvalMismatch => write!(f, "t_prime does not evaluate to the claimed eval"),
+            BiniusError::UnsupportedAxis(axis) => {
+                write!(f, "unsupported axis {}, expected 0 or 1", axis)
+            }
+            BiniusError::Verify(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for BiniusError {}
+
+impl From<VerifyError> for BiniusError {
+    fn from(e: VerifyError) -> Self {
+        match e {
+            VerifyError::BranchMismatch { index } => BiniusError::BranchMismatch { index },
+            VerifyError::EvalMismatch => BiniusError::EvalMismatch,
+            e => BiniusError::Verify(e),
+        }
+    }
+}
+
+impl From<ProveError> for BiniusError {
+    fn from(e: ProveError) -> Self {
+        match e {
+            ProveError::CommittedSize {
+                committed,
+                evaluations,
+            } => BiniusError::InvalidLength {
+                expected: committed,
+                actual: evaluations,
+            },
+            ProveError::PointLength { expected, actual } => {
+                BiniusError::InvalidLength { expected, actual }
+            }
+        }
+    }
+}
+
+impl From<NttError> for BiniusError {
+    fn from(e: NttError) -> Self {
+        match e {
+            NttError::LengthNotPowerOfTwo(n) | NttError::ExpansionFactorNotPowerOfTwo(n) => {
+                BiniusError::NotPowerOfTwo(n)
+            }
+        }
+    }
+}
+
+impl From<PackError> for BiniusError {
+    fn from(e: PackError) -> Self {
+        match e {
+            PackError::PackingFactor(packing_factor) => BiniusError::InvalidLength {
+                expected: 16,
+                actual: packing_factor,
+            },
+            PackError::RowLength {
+                row_length,
+                packing_factor,
+            } => BiniusError::InvalidLength {
+                expected: row_length / packing_factor * packing_factor,
+                actual: row_length,
+            },
+            PackError::TooFewEvaluations { needed, available } => BiniusError::InvalidLength {
+                expected: needed,
+                actual: available,
+            },
+        }
+    }
+}
+
+impl From<LayoutError> for BiniusError {
+    fn from(e: LayoutError) -> Self {
+        match e {
+            // lengths in bits
+            LayoutError::TooFewEvaluations {
+                log_evaluation_count,
+            } => BiniusError::InvalidLength {
+                expected: 1 << super::utils::MIN_LOG_EVALUATION_COUNT,
+                actual: 1 << log_evaluation_count,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_verify_error() {
+        assert_eq!(
+            BiniusError::from(VerifyError::BranchMismatch { index: 4 }),
+            BiniusError::BranchMismatch { index: 4 }
+        );
+        assert_eq!(
+            BiniusError::from(VerifyError::EvalMismatch),
+            BiniusError::EvalMismatch
+        );
+        assert_eq!(
+            BiniusError::from(VerifyError::TPrimeMismatch),
+            BiniusError::Verify(VerifyError::TPrimeMismatch)
+        );
+    }
+
+    #[test]
+    fn test_from_step_errors() {
+        assert_eq!(
+            BiniusError::from(ProveError::PointLength {
+                expected: 23,
+                actual: 22
+            }),
+            BiniusError::InvalidLength {
+                expected: 23,
+                actual: 22
+            }
+        );
+        assert_eq!(
+            BiniusError::from(NttError::ExpansionFactorNotPowerOfTwo(3)),
+            BiniusError::NotPowerOfTwo(3)
+        );
+        assert_eq!(
+            BiniusError::from(PackError::TooFewEvaluations {
+                needed: 32,
+                available: 16
+            }),
+            BiniusError::InvalidLength {
+                expected: 32,
+                actual: 16
+            }
+        );
+        assert_eq!(
+            BiniusError::from(LayoutError::TooFewEvaluations {
+                log_evaluation_count: 3
+            }),
+            BiniusError::InvalidLength {
+                expected: 64,
+                actual: 8
+            }
+        );
+    }
+}
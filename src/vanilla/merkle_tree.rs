@@ -4,15 +4,119 @@
 //! 1. hash: hash a byte array using SHA256
 //! 2. merkelize: build a Merkle tree from the inputs
 //! 3. get_root: return the root of the Merkle tree
-//! 4. get_branch: get the branch of the Merkle tree
+//! 4. get_branch: get the branch of the Merkle tree (try_get_branch returns an error for a position out of range)
 //! 5. verify_branch: verify the Merkle branch
+//! 6. update_leaf: replace one leaf and re-hash only its path to the root
+//! 7. compute_branch_root: the root a Merkle branch leads to, verify_branch compares it with the expected root
+//! 8. get_cap, get_branch_to_cap, verify_branch_to_cap, root_of_cap: commit to the top levels instead of the root, for shorter branches
+//! 9. merkle_root_streaming: the root alone, without keeping the internal nodes
+//! 10. root_to_hex, root_from_hex: print a root as lowercase hex and parse it back
+//! 11. verify_branch_checked: verify_branch that rejects a malformed branch with a BranchError instead of false
+//! 12. combine_roots, verify_root_membership: aggregate the roots of several commitments under one parent root
+//! 13. get_multi_branch, verify_multi_branch: the branches of several leaves, the nodes they share included once
+//!
+//! merkelize and verify_branch are generic over the MerkleHasher used for the nodes,
+//! Sha256Hasher is the default hasher of the PCS, TruncatedSha256Hasher<N> shortens its digests to N bytes, Blake3Hasher is available with the blake3 feature
+//! and Keccak256Hasher with the keccak feature. get_root and get_branch only read the tree, so they work for any hasher.
 
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+// below this many leaves, spawning rayon tasks costs more than hashing the tree
+const PARALLEL_MERKELIZE_THRESHOLD: usize = 64;
+
+/** The hash function used for the leaves and the internal nodes of the Merkle tree */
+pub trait MerkleHasher {
+    fn hash(data: &[u8]) -> Vec<u8>;
+
+    // the length of every digest hash returns, the default hashes the empty input to find it
+    fn digest_len() -> usize {
+        Self::hash(&[]).len()
+    }
+}
+
+/** SHA256, the default Merkle hasher */
+pub struct Sha256Hasher;
+
+impl MerkleHasher for Sha256Hasher {
+    fn hash(data: &[u8]) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        hasher.finalize().to_vec()
+    }
+
+    fn digest_len() -> usize {
+        32
+    }
+}
+
+/** SHA256 truncated to its first N bytes, for smaller proofs
+
+every node of a branch is N bytes instead of 32, so N = 16 halves the branches of a proof,
+    the trade-off is security: a collision of the truncated digest costs about 2^(4N) work,
+    so N = 16 gives 64-bit collision resistance (and 128-bit second preimage resistance),
+    only use it when the prover cannot profit from finding two leaf sets with the same root,
+    the challenges are drawn from the same truncated digests, so N must be at least 2
+*/
+pub struct TruncatedSha256Hasher<const N: usize>;
+
+impl<const N: usize> MerkleHasher for TruncatedSha256Hasher<N> {
+    fn hash(data: &[u8]) -> Vec<u8> {
+        assert!(
+            N >= 2 && N <= 32,
+            "a truncated SHA256 digest has 2 to 32 bytes"
+        );
+        let mut digest = Sha256Hasher::hash(data);
+        digest.truncate(N);
+        digest
+    }
+
+    fn digest_len() -> usize {
+        N
+    }
+}
+
+/** Blake3, faster than SHA256 for large column sets
+
+```
+use binius_rust::pcs::{
+    commit_with_hasher, prove_with_hasher, verifier_with_hasher, Blake3Hasher, PcsParams,
+};
+
+let evaluations = vec![7u8; 1 << 12];
+let evaluation_point: Vec<u128> = (1..16).collect();
+let params = PcsParams::default();
+let commitment = commit_with_hasher::<Blake3Hasher>(&evaluations, &params);
+let proof = prove_with_hasher::<Blake3Hasher>(&commitment, &evaluations, &evaluation_point, &params);
+assert!(verifier_with_hasher::<Blake3Hasher>(&commitment, &proof, &evaluation_point, &params).is_ok());
+```
+*/
+#[cfg(feature = "blake3")]
+pub struct Blake3Hasher;
+
+#[cfg(feature = "blake3")]
+impl MerkleHasher for Blake3Hasher {
+    fn hash(data: &[u8]) -> Vec<u8> {
+        blake3::hash(data).as_bytes().to_vec()
+    }
+}
+
+/** Keccak-256 (the EVM keccak256, not the standardized SHA3-256), for proofs verified on-chain */
+#[cfg(feature = "keccak")]
+pub struct Keccak256Hasher;
+
+#[cfg(feature = "keccak")]
+impl MerkleHasher for Keccak256Hasher {
+    fn hash(data: &[u8]) -> Vec<u8> {
+        <sha3::Keccak256 as sha3::Digest>::digest(data).to_vec()
+    }
+}
 
 pub fn hash(x: &[u8]) -> Vec<u8> {
-    let mut hasher = Sha256::new();
-    hasher.update(x);
-    hasher.finalize().to_vec()
+    Sha256Hasher::hash(x)
 }
 
 /** Build a Merkle tree from the inputs
@@ -25,20 +129,188 @@ Args:
 Returns:
     the Merkle tree
 */
-pub fn merkelize(vals: &Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+pub fn merkelize<H: MerkleHasher>(vals: &Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    if vals.len() < PARALLEL_MERKELIZE_THRESHOLD {
+        merkelize_sequential::<H>(vals)
+    } else {
+        merkelize_parallel::<H>(vals)
+    }
+}
+
+fn merkelize_sequential<H: MerkleHasher>(vals: &Vec<Vec<u8>>) -> Vec<Vec<u8>> {
     assert_eq!(vals.len() & (vals.len() - 1), 0);
     let mut o = vec![vec![]; vals.len() * 2];
     for (i, x) in vals.iter().enumerate() {
-        o[vals.len() + i] = hash(x);
+        o[vals.len() + i] = H::hash(x);
     }
-    for i in (1..vals.len()).rev() {
+    hash_internal_nodes::<H>(&mut o);
+    o
+}
+
+// fill o[1..n] from the leaf hashes in o[n..2n]
+fn hash_internal_nodes<H: MerkleHasher>(o: &mut Vec<Vec<u8>>) {
+    for i in (1..o.len() / 2).rev() {
         let mut combined = o[i * 2].clone();
         combined.extend(o[i * 2 + 1].clone());
-        o[i] = hash(&combined);
+        o[i] = H::hash(&combined);
+    }
+}
+
+/** Build a Merkle tree from leaves produced one at a time
+
+same tree as merkelize, but each leaf is hashed and dropped as soon as it arrives,
+    so only the 2 * leaf_count hashes are held in memory, never all the raw leaves at once
+
+Args:
+    leaves: the original data, e.g. the packed columns generated lazily
+    leaf_count: the number of leaves the iterator yields, a power of two
+
+Returns:
+    the Merkle tree
+*/
+pub fn merkelize_streaming<H: MerkleHasher, I: Iterator<Item = Vec<u8>>>(
+    leaves: I,
+    leaf_count: usize,
+) -> Vec<Vec<u8>> {
+    assert_eq!(leaf_count & (leaf_count - 1), 0);
+    let mut o = vec![vec![]; leaf_count * 2];
+    let mut count = 0;
+    for x in leaves {
+        assert!(count < leaf_count, "more than {} leaves", leaf_count);
+        o[leaf_count + count] = H::hash(&x);
+        count += 1;
     }
+    assert_eq!(count, leaf_count, "expected {} leaves", leaf_count);
+    hash_internal_nodes::<H>(&mut o);
     o
 }
 
+/** Compute the Merkle root of leaves produced one at a time, without building the tree
+
+same root as merkelize, but two siblings are combined as soon as both are known and then dropped,
+    so at most one pending hash per level, log2(leaf_count) + 1 hashes, is held in memory
+
+Args:
+    leaves: the original data, e.g. the packed columns generated lazily
+    leaf_count: the number of leaves the iterator yields, a power of two
+
+Returns:
+    the root of the Merkle tree
+*/
+pub fn merkle_root_streaming<H: MerkleHasher, I: Iterator<Item = Vec<u8>>>(
+    leaves: I,
+    leaf_count: usize,
+) -> Vec<u8> {
+    assert!(leaf_count.is_power_of_two());
+    // pending[k] is the root of the last complete subtree of 2^k leaves still waiting for its sibling
+    let mut pending: Vec<Vec<u8>> = Vec::new();
+    let mut count = 0;
+    for x in leaves {
+        assert!(count < leaf_count, "more than {} leaves", leaf_count);
+        let mut node = H::hash(&x);
+        let mut level = 0;
+        // the k-th bit of count is set when the left sibling at level k is pending
+        while count >> level & 1 == 1 {
+            let left = pending.pop().unwrap();
+            node = H::hash(&[left.as_slice(), node.as_slice()].concat());
+            level += 1;
+        }
+        pending.push(node);
+        count += 1;
+    }
+    assert_eq!(count, leaf_count, "expected {} leaves", leaf_count);
+    pending.pop().unwrap()
+}
+
+/** Replace one leaf of a Merkle tree in place
+
+only the leaf and the nodes on its path to the root are re-hashed, log2(leaf_count) + 1 hashes
+    instead of the 2 * leaf_count of merkelize, the tree ends up equal to merkelize of the modified leaves
+
+Args:
+    tree: the Merkle tree built by merkelize with the same hasher
+    pos: the position of the leaf
+    new_leaf: the new data of the leaf
+
+Returns:
+    the new root of the Merkle tree
+*/
+pub fn update_leaf<H: MerkleHasher>(
+    tree: &mut Vec<Vec<u8>>,
+    pos: usize,
+    new_leaf: &[u8],
+) -> Vec<u8> {
+    let leaf_count = tree.len() / 2;
+    assert!(
+        pos < leaf_count,
+        "leaf {} is outside a tree of {} leaves",
+        pos,
+        leaf_count
+    );
+    let mut i = leaf_count + pos;
+    tree[i] = H::hash(new_leaf);
+    while i > 1 {
+        i /= 2;
+        tree[i] = H::hash(&[tree[i * 2].as_slice(), tree[i * 2 + 1].as_slice()].concat());
+    }
+    get_root(tree)
+}
+
+/** Build the same Merkle tree as merkelize_sequential, hashing each level in parallel
+
+the leaves are hashed in parallel, then each level of internal nodes is hashed in parallel from the level below,
+    o[half..2 * half] only reads o[2 * half..4 * half], so the levels are split with split_at_mut
+*/
+fn merkelize_parallel<H: MerkleHasher>(vals: &Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    assert_eq!(vals.len() & (vals.len() - 1), 0);
+    let mut o = vec![vec![]; vals.len() * 2];
+    o[vals.len()..]
+        .par_iter_mut()
+        .zip(vals.par_iter())
+        .for_each(|(node, x)| *node = H::hash(x));
+
+    let mut half = vals.len() / 2;
+    while half >= 1 {
+        let (upper, lower) = o.split_at_mut(half * 2);
+        let lower = &*lower;
+        upper[half..]
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(k, node)| {
+                let mut combined = lower[k * 2].clone();
+                combined.extend(lower[k * 2 + 1].clone());
+                *node = H::hash(&combined);
+            });
+        half /= 2;
+    }
+    o
+}
+
+/** Build a Merkle tree from any number of inputs
+
+pads vals with empty leaves up to the next power of two, then builds the tree with merkelize,
+    the padding leaves hash like any other (to H of the empty string), so get_branch/verify_branch work unchanged
+    the original leaf count is recorded in o[0], which merkelize leaves empty, see original_leaf_count
+
+Args:
+    vals: the original data, any length
+
+Returns:
+    the Merkle tree over the padded data
+*/
+pub fn merkelize_padded<H: MerkleHasher>(vals: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    let mut padded = vals.to_vec();
+    padded.resize(vals.len().next_power_of_two(), vec![]);
+    let mut o = merkelize::<H>(&padded);
+    o[0] = (vals.len() as u64).to_le_bytes().to_vec();
+    o
+}
+
+/** return the number of leaves before padding of a tree built with merkelize_padded */
+pub fn original_leaf_count(tree: &Vec<Vec<u8>>) -> usize {
+    u64::from_le_bytes(tree[0].as_slice().try_into().unwrap()) as usize
+}
+
 /** return the root of the Merkle tree
 
 Args:
@@ -51,27 +323,401 @@ pub fn get_root(tree: &Vec<Vec<u8>>) -> Vec<u8> {
     tree[1].clone()
 }
 
+/** Why a string is not a hex-encoded root */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RootHexError {
+    // a byte takes two hex digits, the string has an odd number of them
+    OddLength(usize),
+    // the character at this byte offset is not a hex digit
+    InvalidDigit { index: usize, digit: char },
+}
+
+impl fmt::Display for RootHexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RootHexError::OddLength(len) => {
+                write!(f, "hex root has odd length {}", len)
+            }
+            RootHexError::InvalidDigit { index, digit } => {
+                write!(f, "invalid hex digit {:?} at index {}", digit, index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RootHexError {}
+
+/** Encode a root (or any digest) as lowercase hex, two digits per byte
+
+Args:
+    root: the root of the Merkle tree, get_root or Commitment::root
+
+Returns:
+    String: the hex encoding, 64 characters for a SHA256 root
+*/
+pub fn root_to_hex(root: &[u8]) -> String {
+    root.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/** Parse a root written by root_to_hex, upper case digits are accepted too
+
+Args:
+    s: the hex string, without a 0x prefix
+
+Returns:
+    Result<Vec<u8>, RootHexError>: the root bytes, or why s is not valid hex
+*/
+pub fn root_from_hex(s: &str) -> Result<Vec<u8>, RootHexError> {
+    // checking the characters first keeps the byte pairs below on ASCII boundaries
+    if let Some((index, digit)) = s.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(RootHexError::InvalidDigit { index, digit });
+    }
+    if s.len() % 2 != 0 {
+        return Err(RootHexError::OddLength(s.len()));
+    }
+    Ok((0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect())
+}
+
 /** Get the branch of the Merkle tree
 
 the Merkle tree hash path from the leaf to the root, the branch is the sibling of the path
 
 Args:
     tree: the Merkle tree
-    pos: the position of the leaf
+    pos: the position of the leaf, below the number of leaves, tree.len() / 2
 
 Returns:
-    the hash path of the Merkle tree
+    the hash path of the Merkle tree, panics with the BranchError of try_get_branch for a position out of range
  */
 pub fn get_branch(tree: &Vec<Vec<u8>>, pos: usize) -> Vec<Vec<u8>> {
+    try_get_branch(tree, pos).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/** get_branch, with an error instead of a panic for a position out of range
+
+a position past the leaves would read the siblings of another node, or out of the tree,
+    challenge indices drawn with params that do not match the tree end up here
+
+Args:
+    tree: the Merkle tree
+    pos: the position of the leaf
+
+Returns:
+    the hash path of the Merkle tree, or BranchError::Position when pos is not below tree.len() / 2
+*/
+pub fn try_get_branch(tree: &Vec<Vec<u8>>, pos: usize) -> Result<Vec<Vec<u8>>, BranchError> {
+    let leaf_count = tree.len() / 2;
+    if pos >= leaf_count {
+        return Err(BranchError::Position { pos, leaf_count });
+    }
     let offset_pos = pos + tree.len() / 2;
     let branch_length = (tree.len() as f64).log2() as usize - 1;
     let mut branch = vec![];
     for i in 0..branch_length {
         branch.push(tree[(offset_pos >> i) ^ 1].clone());
     }
-    branch
+    Ok(branch)
+}
+
+/** Get the cap of the Merkle tree, the 2^cap_height nodes cap_height levels below the root
+
+committing to the cap instead of the root makes every branch cap_height nodes shorter,
+    a cap of height 0 is the root alone
+
+Args:
+    tree: the Merkle tree
+    cap_height: the level of the cap, at most log2 of the number of leaves
+
+Returns:
+    the nodes of the cap, from left to right
+*/
+pub fn get_cap(tree: &[Vec<u8>], cap_height: usize) -> Vec<Vec<u8>> {
+    assert!(
+        1 << cap_height <= tree.len() / 2,
+        "the cap is below the leaves"
+    );
+    tree[1 << cap_height..2 << cap_height].to_vec()
+}
+
+/** Get the branch of the Merkle tree from the leaf up to the cap
+
+get_branch without its last cap_height siblings, which the cap already holds
+
+Args:
+    tree: the Merkle tree
+    pos: the position of the leaf
+    cap_height: the level of the cap
+
+Returns:
+    the hash path from the leaf to its node of the cap
+*/
+pub fn get_branch_to_cap(tree: &[Vec<u8>], pos: usize, cap_height: usize) -> Vec<Vec<u8>> {
+    let offset_pos = pos + tree.len() / 2;
+    let branch_length = (tree.len() / 2).trailing_zeros() as usize;
+    assert!(cap_height <= branch_length, "the cap is below the leaves");
+    if pos >= tree.len() / 2 {
+        panic!(
+            "{}",
+            BranchError::Position {
+                pos,
+                leaf_count: tree.len() / 2
+            }
+        );
+    }
+    (0..branch_length - cap_height)
+        .map(|i| tree[(offset_pos >> i) ^ 1].clone())
+        .collect()
+}
+
+/** Verify a Merkle branch against the cap, the output of get_branch_to_cap
+
+the steps are those of verify_branch, the path ends at the node pos >> branch.len() of the cap.
+    The shape comes from the verifier, not from the branch: the cap has 2^cap_height nodes,
+    pos must be below leaf_count and the branch must have log2(leaf_count) - cap_height siblings
+
+Args:
+    cap: the cap of the Merkle tree, the output of get_cap
+    pos: the position of the leaf
+    val: the leaf data
+    branch: the sibling hashes from the leaf to the cap
+    leaf_count: the number of leaves of the tree, a power of two
+
+Returns:
+    whether the branch leads to its node of the cap
+*/
+pub fn verify_branch_to_cap<H: MerkleHasher>(
+    cap: &[Vec<u8>],
+    pos: usize,
+    val: &[u8],
+    branch: &[Vec<u8>],
+    leaf_count: usize,
+) -> bool {
+    if !cap.len().is_power_of_two() || !leaf_count.is_power_of_two() || cap.len() > leaf_count {
+        return false;
+    }
+    let cap_height = cap.len().trailing_zeros();
+    if pos >= leaf_count || branch.len() != (leaf_count.trailing_zeros() - cap_height) as usize {
+        return false;
+    }
+    ct_eq(
+        &compute_branch_root::<H>(pos, val, branch),
+        &cap[pos >> branch.len()],
+    )
+}
+
+/** The root above a cap, hashing its nodes pairwise as merkelize does
+
+a verifier given the cap instead of the root recomputes the root with it, 2^cap_height - 1 hashes
+
+Args:
+    cap: the cap of the Merkle tree, the output of get_cap
+
+Returns:
+    the root of the Merkle tree
+*/
+pub fn root_of_cap<H: MerkleHasher>(cap: &[Vec<u8>]) -> Vec<u8> {
+    assert!(
+        cap.len().is_power_of_two(),
+        "a cap has a power-of-two number of nodes"
+    );
+    let mut level = cap.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| H::hash(&[pair[0].as_slice(), pair[1].as_slice()].concat()))
+            .collect();
+    }
+    level.pop().unwrap()
+}
+
+/** Combine the roots of several commitments under one parent root
+
+the child roots are the leaves of a small tree built with merkelize_padded, nothing below them is re-hashed,
+    so commitments of different provers (or different hashers of the same digest length) can be aggregated,
+    a child root is then authenticated with get_branch(&tree, index) and verify_root_membership
+
+Args:
+    roots: the child roots, at least one
+
+Returns:
+    (Vec<u8>, Vec<Vec<u8>>): the parent root and the aggregation tree
+*/
+pub fn combine_roots<H: MerkleHasher>(roots: &[Vec<u8>]) -> (Vec<u8>, Vec<Vec<u8>>) {
+    assert!(!roots.is_empty(), "there are no roots to combine");
+    let tree = merkelize_padded::<H>(roots);
+    (get_root(&tree), tree)
+}
+
+/** Verify that child_root is the index-th root combined under parent
+
+the padding leaves of the aggregation tree are not children, so index must be below num_roots,
+    and the branch must have one sibling per level of the tree padded to num_roots.next_power_of_two() leaves
+
+Args:
+    parent: the parent root of combine_roots
+    index: the position of the child root in the roots given to combine_roots
+    child_root: the root of the child commitment
+    branch: get_branch of the aggregation tree at index
+    num_roots: the number of roots given to combine_roots
+
+Returns:
+    bool: whether child_root is the index-th child of parent
+*/
+pub fn verify_root_membership<H: MerkleHasher>(
+    parent: &[u8],
+    index: usize,
+    child_root: &[u8],
+    branch: &[Vec<u8>],
+    num_roots: usize,
+) -> bool {
+    index < num_roots
+        && verify_branch_checked::<H>(
+            parent,
+            index,
+            child_root,
+            branch,
+            num_roots.next_power_of_two(),
+        ) == Ok(true)
+}
+
+/** The sibling hashes needed to authenticate several leaves at once
+
+nodes are ordered level by level from the leaves up, and inside a level by increasing node index,
+    a sibling that can be computed from the opened leaves (or is itself on an opened path) is never included
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiBranch {
+    pub nodes: Vec<Vec<u8>>,
+}
+
+pub fn get_multi_branch(tree: &[Vec<u8>], positions: &[usize]) -> MultiBranch {
+    try_get_multi_branch(tree, positions).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/** Get the branches of several leaves, sharing the nodes the paths have in common
+
+the paths of nearby leaves merge towards the root, so every node is included at most once,
+    and only if it cannot be recomputed from the opened leaves
+
+Args:
+    tree: the Merkle tree
+    positions: the positions of the leaves, in any order, repeats allowed
+
+Returns:
+    the MultiBranch of the positions, BranchError::NoPositions when there are none
+    and BranchError::Position for a position not below tree.len() / 2
+*/
+pub fn try_get_multi_branch(
+    tree: &[Vec<u8>],
+    positions: &[usize],
+) -> Result<MultiBranch, BranchError> {
+    let leaf_count = tree.len() / 2;
+    if positions.is_empty() {
+        return Err(BranchError::NoPositions);
+    }
+    if let Some(&pos) = positions.iter().find(|&&pos| pos >= leaf_count) {
+        return Err(BranchError::Position { pos, leaf_count });
+    }
+    let mut known: BTreeSet<usize> = positions.iter().map(|&p| p + leaf_count).collect();
+    let mut nodes = vec![];
+    while !known.contains(&1) {
+        let mut parents = BTreeSet::new();
+        for &i in known.iter() {
+            if !known.contains(&(i ^ 1)) {
+                nodes.push(tree[i ^ 1].clone());
+            }
+            parents.insert(i >> 1);
+        }
+        known = parents;
+    }
+    Ok(MultiBranch { nodes })
 }
 
+/** Verify a MultiBranch, requires only the root, not the tree
+
+walks the levels in the same order as get_multi_branch, hashing each pair with the same byte layout as verify_branch
+
+Args:
+    root: the root of the Merkle tree
+    positions: the positions of the leaves
+    leaves: the leaf data, leaves[k] at positions[k]
+    proof: the output of get_multi_branch for the same positions
+    leaf_count: the number of leaves of the tree, a power of two
+
+Returns:
+    whether the leaves and the proof lead to the root, false for no positions,
+    a position out of the leaves or a leaf_count that is not a power of two
+*/
+pub fn verify_multi_branch<H: MerkleHasher>(
+    root: &[u8],
+    positions: &[usize],
+    leaves: &[Vec<u8>],
+    proof: &MultiBranch,
+    leaf_count: usize,
+) -> bool {
+    if positions.len() != leaves.len() || positions.is_empty() || !leaf_count.is_power_of_two() {
+        return false;
+    }
+    let mut known: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+    for (&pos, leaf) in positions.iter().zip(leaves.iter()) {
+        if pos >= leaf_count {
+            return false;
+        }
+        let x = H::hash(leaf);
+        if known.get(&(pos + leaf_count)).map_or(false, |y| *y != x) {
+            return false;
+        }
+        known.insert(pos + leaf_count, x);
+    }
+
+    let mut nodes = proof.nodes.iter();
+    while !known.contains_key(&1) {
+        let mut parents = BTreeMap::new();
+        for (&i, x) in known.iter() {
+            if parents.contains_key(&(i >> 1)) {
+                continue;
+            }
+            let sibling = match known.get(&(i ^ 1)) {
+                Some(b) => b,
+                None => match nodes.next() {
+                    Some(b) => b,
+                    None => return false,
+                },
+            };
+            let combined = if i & 1 == 1 {
+                [sibling.as_slice(), x.as_slice()].concat()
+            } else {
+                [x.as_slice(), sibling.as_slice()].concat()
+            };
+            parents.insert(i >> 1, H::hash(&combined));
+        }
+        known = parents;
+    }
+    nodes.next().is_none() && ct_eq(&known[&1], root)
+}
+
+/** Verify the Merkle branch, requires only the root, not the tree
+
+Byte layout (what an on-chain verifier has to reproduce):
+    the leaf is H(val), val being the packed column bytes as committed
+    each step hashes the 64-byte concatenation of two 32-byte digests, without prefix or length:
+        pos odd: the sibling b is the left child, x = H(b || x)
+        pos even: the sibling b is the right child, x = H(x || b)
+    then pos = pos / 2, and the final x must equal the root
+    with Keccak256Hasher a step is keccak256(abi.encodePacked(left, right)) in Solidity
+
+Args:
+    root: the root of the Merkle tree
+    pos: the position of the leaf
+    val: the leaf data
+    branch: the sibling hashes from the leaf to the root, the output of get_branch
+
+Returns:
+    whether the branch leads to the root
+*/
 // # Verify that Merkle branch (requires only the root, not the tree)
 // def verify_branch(root, pos, val, branch):
 //     x = hash(val)
@@ -82,18 +728,174 @@ pub fn get_branch(tree: &Vec<Vec<u8>>, pos: usize) -> Vec<Vec<u8>> {
 //             x = hash(x + b)
 //         pos //= 2
 //     return x == root
-pub fn verify_branch(root: &[u8], pos: usize, val: &[u8], branch: &Vec<Vec<u8>>) -> bool {
-    let mut x = hash(val);
+pub fn verify_branch<H: MerkleHasher>(
+    root: &[u8],
+    pos: usize,
+    val: &[u8],
+    branch: &Vec<Vec<u8>>,
+) -> bool {
+    ct_eq(&compute_branch_root::<H>(pos, val, branch), root)
+}
+
+/** Why a Merkle branch is malformed, before any hashing */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BranchError {
+    // the tree must have a power-of-two number of leaves
+    LeafCount(usize),
+    // a multi-branch opens at least one leaf
+    NoPositions,
+    // the leaf position is not below the leaf count
+    Position {
+        pos: usize,
+        leaf_count: usize,
+    },
+    // a branch has one sibling per level, log2(leaf_count) of them
+    BranchLength {
+        expected: usize,
+        actual: usize,
+    },
+    // the level-th sibling (from the leaf up) is not a digest of the hasher
+    SiblingLength {
+        level: usize,
+        expected: usize,
+        actual: usize,
+    },
+    // the root is not a digest of the hasher
+    RootLength {
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl fmt::Display for BranchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BranchError::LeafCount(n) => write!(f, "leaf count {} is not a power of two", n),
+            BranchError::NoPositions => write!(f, "no leaf positions to open"),
+            BranchError::Position { pos, leaf_count } => {
+                write!(f, "leaf position {} is out of {} leaves", pos, leaf_count)
+            }
+            BranchError::BranchLength { expected, actual } => {
+                write!(f, "branch has {} siblings, expected {}", actual, expected)
+            }
+            BranchError::SiblingLength {
+                level,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "sibling at level {} has {} bytes, expected a {}-byte digest",
+                level, actual, expected
+            ),
+            BranchError::RootLength { expected, actual } => {
+                write!(
+                    f,
+                    "root has {} bytes, expected a {}-byte digest",
+                    actual, expected
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BranchError {}
+
+/** Verify a Merkle branch, after checking that it is well-formed
+
+verify_branch hashes whatever it is given, so a branch of 3-byte siblings or with a level missing
+    is only rejected because its root happens to differ, here the shape is checked first:
+    the root and every sibling are H::digest_len() bytes and there are log2(leaf_count) siblings
+
+Args:
+    root: the root of the Merkle tree
+    pos: the position of the leaf
+    val: the leaf data
+    branch: the sibling hashes from the leaf up, the output of get_branch
+    leaf_count: the number of leaves of the tree, a power of two
+
+Returns:
+    Result<bool, BranchError>: whether the well-formed branch leads to the root, or why it is malformed
+*/
+pub fn verify_branch_checked<H: MerkleHasher>(
+    root: &[u8],
+    pos: usize,
+    val: &[u8],
+    branch: &[Vec<u8>],
+    leaf_count: usize,
+) -> Result<bool, BranchError> {
+    if !leaf_count.is_power_of_two() {
+        return Err(BranchError::LeafCount(leaf_count));
+    }
+    if pos >= leaf_count {
+        return Err(BranchError::Position { pos, leaf_count });
+    }
+    let depth = leaf_count.trailing_zeros() as usize;
+    if branch.len() != depth {
+        return Err(BranchError::BranchLength {
+            expected: depth,
+            actual: branch.len(),
+        });
+    }
+    let digest_len = H::digest_len();
+    if root.len() != digest_len {
+        return Err(BranchError::RootLength {
+            expected: digest_len,
+            actual: root.len(),
+        });
+    }
+    if let Some((level, sibling)) = branch
+        .iter()
+        .enumerate()
+        .find(|(_, sibling)| sibling.len() != digest_len)
+    {
+        return Err(BranchError::SiblingLength {
+            level,
+            expected: digest_len,
+            actual: sibling.len(),
+        });
+    }
+    Ok(ct_eq(&compute_branch_root::<H>(pos, val, branch), root))
+}
+
+/** Recompute the root a Merkle branch leads to, the steps of verify_branch
+
+when a branch is rejected, the recomputed root shows what the branch actually commits to
+
+Args:
+    pos: the position of the leaf
+    val: the leaf data
+    branch: the sibling hashes from the leaf up, the output of get_branch
+
+Returns:
+    the root of a tree with val at pos and these siblings
+*/
+pub fn compute_branch_root<H: MerkleHasher>(pos: usize, val: &[u8], branch: &[Vec<u8>]) -> Vec<u8> {
+    let mut x = H::hash(val);
     let mut pos = pos;
     for b in branch {
         if pos & 1 == 1 {
-            x = hash(&[b.as_slice(), x.as_slice()].concat());
+            x = H::hash(&[b.as_slice(), x.as_slice()].concat());
         } else {
-            x = hash(&[x.as_slice(), b.as_slice()].concat());
+            x = H::hash(&[x.as_slice(), b.as_slice()].concat());
         }
         pos /= 2;
     }
-    x == root
+    x
+}
+
+/** Compare two hashes in constant time
+
+== on slices stops at the first differing byte, which leaks how much of the hash matched,
+    so every byte is XORed into an accumulator instead; only the lengths (public) short-circuit
+*/
+fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+        == 0
 }
 
 #[cfg(test)]
@@ -117,7 +919,7 @@ mod tests {
     #[test]
     fn test_merkelize() {
         let vals = vec![vec![1, 2], vec![3, 4]];
-        let result = merkelize(&vals);
+        let result = merkelize::<Sha256Hasher>(&vals);
         assert_eq!(result[0], Vec::<u8>::new());
         assert_eq!(
             result[1],
@@ -129,6 +931,226 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_update_leaf() {
+        let mut vals: Vec<Vec<u8>> = (0..16u8).map(|i| vec![i, i.wrapping_mul(5), 9]).collect();
+        let mut tree = merkelize::<Sha256Hasher>(&vals);
+
+        vals[5] = vec![42, 42];
+        let root = update_leaf::<Sha256Hasher>(&mut tree, 5, &vals[5]);
+        let rebuilt = merkelize::<Sha256Hasher>(&vals);
+        assert_eq!(root, get_root(&rebuilt));
+        assert_eq!(tree, rebuilt);
+
+        // the first and the last leaf, their paths share only the root
+        vals[0] = vec![];
+        vals[15] = vec![1, 2, 3, 4];
+        update_leaf::<Sha256Hasher>(&mut tree, 0, &vals[0]);
+        update_leaf::<Sha256Hasher>(&mut tree, 15, &vals[15]);
+        assert_eq!(tree, merkelize::<Sha256Hasher>(&vals));
+        assert!(verify_branch::<Sha256Hasher>(
+            &get_root(&tree),
+            15,
+            &vals[15],
+            &get_branch(&tree, 15)
+        ));
+    }
+
+    #[test]
+    fn test_truncated_sha256_hasher() {
+        let digest = TruncatedSha256Hasher::<16>::hash(b"abc");
+        assert_eq!(digest, Sha256Hasher::hash(b"abc")[..16]);
+
+        let vals: Vec<Vec<u8>> = (0..16u8).map(|i| vec![i; 5]).collect();
+        let tree = merkelize::<TruncatedSha256Hasher<16>>(&vals);
+        assert!(tree[1..].iter().all(|node| node.len() == 16));
+        assert!(verify_branch::<TruncatedSha256Hasher<16>>(
+            &get_root(&tree),
+            7,
+            &vals[7],
+            &get_branch(&tree, 7)
+        ));
+    }
+
+    #[test]
+    fn test_branch_to_cap() {
+        let vals: Vec<Vec<u8>> = (0..16u8).map(|i| vec![i, 7, i]).collect();
+        let tree = merkelize::<Sha256Hasher>(&vals);
+        let root = get_root(&tree);
+        let cap = get_cap(&tree, 2);
+        assert_eq!(cap.len(), 4);
+        assert_eq!(get_cap(&tree, 0), vec![root.clone()]);
+
+        for pos in 0..16 {
+            let full = get_branch(&tree, pos);
+            let branch = get_branch_to_cap(&tree, pos, 2);
+            assert_eq!(branch, full[..full.len() - 2]);
+            assert!(verify_branch::<Sha256Hasher>(&root, pos, &vals[pos], &full));
+            assert!(verify_branch_to_cap::<Sha256Hasher>(
+                &cap, pos, &vals[pos], &branch, 16
+            ));
+            assert!(!verify_branch_to_cap::<Sha256Hasher>(
+                &cap,
+                pos ^ 4,
+                &vals[pos],
+                &branch,
+                16
+            ));
+            // the full branch or one cut short would end at another level of the tree
+            assert!(!verify_branch_to_cap::<Sha256Hasher>(
+                &cap, pos, &vals[pos], &full, 16
+            ));
+            assert!(!verify_branch_to_cap::<Sha256Hasher>(
+                &cap,
+                pos,
+                &vals[pos],
+                &branch[..1],
+                16
+            ));
+        }
+        // a position past the leaves is rejected, even one whose node of the cap exists
+        assert!(!verify_branch_to_cap::<Sha256Hasher>(
+            &cap,
+            16,
+            &vals[0],
+            &get_branch_to_cap(&tree, 0, 2),
+            16
+        ));
+        assert_eq!(root_of_cap::<Sha256Hasher>(&cap), root);
+        assert_eq!(root_of_cap::<Sha256Hasher>(&get_cap(&tree, 4)), root);
+    }
+
+    #[test]
+    fn test_compute_branch_root() {
+        let vals: Vec<Vec<u8>> = (0..32u8).map(|i| vec![i; 4]).collect();
+        let tree = merkelize::<Sha256Hasher>(&vals);
+        for pos in [0, 13, 31] {
+            let branch = get_branch(&tree, pos);
+            assert_eq!(
+                compute_branch_root::<Sha256Hasher>(pos, &vals[pos], &branch),
+                get_root(&tree)
+            );
+            assert_ne!(
+                compute_branch_root::<Sha256Hasher>(pos, &vals[pos ^ 1], &branch),
+                get_root(&tree)
+            );
+        }
+    }
+
+    #[test]
+    fn test_merkle_root_streaming() {
+        for leaf_count in [1, 2, 8, 64] {
+            let vals: Vec<Vec<u8>> = (0..leaf_count).map(|i| vec![i as u8; 3]).collect();
+            assert_eq!(
+                merkle_root_streaming::<Sha256Hasher, _>(vals.iter().cloned(), leaf_count),
+                get_root(&merkelize::<Sha256Hasher>(&vals))
+            );
+        }
+    }
+
+    #[test]
+    fn test_merkelize_parallel() {
+        let vals: Vec<Vec<u8>> = (0..64u8).map(|i| vec![i, i.wrapping_mul(7), 3]).collect();
+        let expected = merkelize_sequential::<Sha256Hasher>(&vals);
+        assert_eq!(merkelize_parallel::<Sha256Hasher>(&vals), expected);
+        assert_eq!(merkelize::<Sha256Hasher>(&vals), expected);
+    }
+
+    #[test]
+    fn test_merkelize_padded() {
+        for n in [3usize, 5] {
+            let vals: Vec<Vec<u8>> = (0..n as u8).map(|i| vec![i, i + 1]).collect();
+            let tree = merkelize_padded::<Sha256Hasher>(&vals);
+            assert_eq!(tree.len(), 2 * n.next_power_of_two());
+            assert_eq!(original_leaf_count(&tree), n);
+            for pos in 0..n {
+                let branch = get_branch(&tree, pos);
+                assert!(verify_branch::<Sha256Hasher>(
+                    &tree[1], pos, &vals[pos], &branch
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_branch() {
+        let vals: Vec<Vec<u8>> = (0..2048u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let tree = merkelize::<Sha256Hasher>(&vals);
+        let positions = vec![3, 100, 101, 512, 777, 1500, 2000, 2047];
+        let leaves: Vec<Vec<u8>> = positions.iter().map(|&p| vals[p].clone()).collect();
+
+        let proof = get_multi_branch(&tree, &positions);
+        assert!(verify_multi_branch::<Sha256Hasher>(
+            &tree[1],
+            &positions,
+            &leaves,
+            &proof,
+            vals.len()
+        ));
+        let independent: usize = positions.iter().map(|&p| get_branch(&tree, p).len()).sum();
+        assert!(proof.nodes.len() < independent);
+
+        let mut tampered = leaves.clone();
+        tampered[4][0] ^= 1;
+        assert!(!verify_multi_branch::<Sha256Hasher>(
+            &tree[1],
+            &positions,
+            &tampered,
+            &proof,
+            vals.len()
+        ));
+
+        // no positions and positions past the leaves are errors instead of a walk that never ends
+        assert_eq!(
+            try_get_multi_branch(&tree, &[]),
+            Err(BranchError::NoPositions)
+        );
+        assert_eq!(
+            try_get_multi_branch(&tree, &[5, 2048]),
+            Err(BranchError::Position {
+                pos: 2048,
+                leaf_count: 2048
+            })
+        );
+        let empty = MultiBranch { nodes: vec![] };
+        assert!(!verify_multi_branch::<Sha256Hasher>(
+            &tree[1],
+            &[],
+            &[],
+            &empty,
+            vals.len()
+        ));
+        assert!(!verify_multi_branch::<Sha256Hasher>(
+            &tree[1],
+            &[2048],
+            &[vals[0].clone()],
+            &proof,
+            vals.len()
+        ));
+    }
+
+    #[test]
+    fn test_verify_branch_flipped_byte() {
+        let vals: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i]).collect();
+        let tree = merkelize::<Sha256Hasher>(&vals);
+        let mut branch = get_branch(&tree, 5);
+        assert!(verify_branch::<Sha256Hasher>(
+            &tree[1], 5, &vals[5], &branch
+        ));
+
+        branch[1][31] ^= 1;
+        assert!(!verify_branch::<Sha256Hasher>(
+            &tree[1], 5, &vals[5], &branch
+        ));
+    }
+
+    #[test]
+    fn test_merkelize_streaming() {
+        let vals: Vec<Vec<u8>> = (0..16u8).map(|i| vec![i; 3]).collect();
+        let streamed = merkelize_streaming::<Sha256Hasher, _>((0..16u8).map(|i| vec![i; 3]), 16);
+        assert_eq!(streamed, merkelize::<Sha256Hasher>(&vals));
+    }
+
     #[test]
     fn test_get_root() {
         let tree = vec![
@@ -150,13 +1172,236 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_root_hex_round_trip() {
+        let tree = merkelize::<Sha256Hasher>(&(0..8u8).map(|i| vec![i; 4]).collect());
+        let root = get_root(&tree);
+        let hex = root_to_hex(&root);
+        assert_eq!(hex.len(), 64);
+        assert_eq!(root_from_hex(&hex), Ok(root.clone()));
+        assert_eq!(root_from_hex(&hex.to_uppercase()), Ok(root));
+        assert_eq!(root_to_hex(&[0x00, 0x0f, 0xa0]), "000fa0");
+        assert_eq!(root_from_hex(""), Ok(vec![]));
+        assert_eq!(root_from_hex("abc"), Err(RootHexError::OddLength(3)));
+        assert_eq!(
+            root_from_hex("0é"),
+            Err(RootHexError::InvalidDigit {
+                index: 1,
+                digit: 'é'
+            })
+        );
+        assert_eq!(
+            root_from_hex("00g0"),
+            Err(RootHexError::InvalidDigit {
+                index: 2,
+                digit: 'g'
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_branch_checked() {
+        let vals: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 5]).collect();
+        let tree = merkelize::<Sha256Hasher>(&vals);
+        let root = get_root(&tree);
+        let branch = get_branch(&tree, 5);
+        assert_eq!(
+            verify_branch_checked::<Sha256Hasher>(&root, 5, &vals[5], &branch, 8),
+            Ok(true)
+        );
+        assert_eq!(
+            verify_branch_checked::<Sha256Hasher>(&root, 5, &vals[4], &branch, 8),
+            Ok(false)
+        );
+
+        // a truncated sibling is malformed, not merely a mismatch
+        let mut truncated = branch.clone();
+        truncated[1].truncate(3);
+        assert_eq!(
+            verify_branch_checked::<Sha256Hasher>(&root, 5, &vals[5], &truncated, 8),
+            Err(BranchError::SiblingLength {
+                level: 1,
+                expected: 32,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            verify_branch_checked::<Sha256Hasher>(&root, 5, &vals[5], &branch[..2], 8),
+            Err(BranchError::BranchLength {
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            verify_branch_checked::<Sha256Hasher>(&root[..16], 5, &vals[5], &branch, 8),
+            Err(BranchError::RootLength {
+                expected: 32,
+                actual: 16
+            })
+        );
+        assert_eq!(
+            verify_branch_checked::<Sha256Hasher>(&root, 8, &vals[5], &branch, 8),
+            Err(BranchError::Position {
+                pos: 8,
+                leaf_count: 8
+            })
+        );
+        assert_eq!(
+            verify_branch_checked::<Sha256Hasher>(&root, 5, &vals[5], &branch, 6),
+            Err(BranchError::LeafCount(6))
+        );
+
+        // the digest length follows the hasher
+        let tree = merkelize::<TruncatedSha256Hasher<16>>(&vals);
+        let branch = get_branch(&tree, 2);
+        assert_eq!(
+            verify_branch_checked::<TruncatedSha256Hasher<16>>(&tree[1], 2, &vals[2], &branch, 8),
+            Ok(true)
+        );
+        assert!(verify_branch_checked::<Sha256Hasher>(&tree[1], 2, &vals[2], &branch, 8).is_err());
+    }
+
+    #[test]
+    fn test_combine_roots() {
+        let roots: Vec<Vec<u8>> = (0..4u8)
+            .map(|i| {
+                let vals: Vec<Vec<u8>> = (0..8u8).map(|j| vec![i, j]).collect();
+                get_root(&merkelize::<Sha256Hasher>(&vals))
+            })
+            .collect();
+        let (parent, tree) = combine_roots::<Sha256Hasher>(&roots);
+        assert_eq!(parent, get_root(&merkelize::<Sha256Hasher>(&roots)));
+        for (i, root) in roots.iter().enumerate() {
+            let branch = get_branch(&tree, i);
+            assert_eq!(branch.len(), 2);
+            assert!(verify_root_membership::<Sha256Hasher>(
+                &parent, i, root, &branch, 4
+            ));
+            // the branch only authenticates the root at its own index
+            assert!(!verify_root_membership::<Sha256Hasher>(
+                &parent,
+                (i + 1) % 4,
+                root,
+                &branch,
+                4
+            ));
+            assert!(!verify_root_membership::<Sha256Hasher>(
+                &parent,
+                i,
+                &roots[(i + 1) % 4],
+                &branch,
+                4
+            ));
+            // a branch cut short or lengthened is rejected before hashing
+            assert!(!verify_root_membership::<Sha256Hasher>(
+                &parent,
+                i,
+                root,
+                &branch[..1],
+                4
+            ));
+            let mut longer = branch.clone();
+            longer.push(parent.clone());
+            assert!(!verify_root_membership::<Sha256Hasher>(
+                &parent, i, root, &longer, 4
+            ));
+        }
+        assert!(!verify_root_membership::<Sha256Hasher>(
+            &parent,
+            4,
+            &roots[0],
+            &get_branch(&tree, 0),
+            4
+        ));
+
+        // any number of roots, the tree is padded
+        let (parent, tree) = combine_roots::<Sha256Hasher>(&roots[..3]);
+        assert_eq!(original_leaf_count(&tree), 3);
+        assert!(verify_root_membership::<Sha256Hasher>(
+            &parent,
+            2,
+            &roots[2],
+            &get_branch(&tree, 2),
+            3
+        ));
+        // the padding leaf has a valid branch but is not a child
+        let padding_branch = get_branch(&tree, 3);
+        assert!(verify_branch::<Sha256Hasher>(
+            &parent,
+            3,
+            &[],
+            &padding_branch
+        ));
+        assert!(!verify_root_membership::<Sha256Hasher>(
+            &parent,
+            3,
+            &[],
+            &padding_branch,
+            3
+        ));
+    }
+
+    #[test]
+    fn test_try_get_branch() {
+        let vals: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 2]).collect();
+        let tree = merkelize::<Sha256Hasher>(&vals);
+        assert_eq!(try_get_branch(&tree, 7), Ok(get_branch(&tree, 7)));
+        assert_eq!(
+            try_get_branch(&tree, 8),
+            Err(BranchError::Position {
+                pos: 8,
+                leaf_count: 8
+            })
+        );
+        assert!(try_get_branch(&tree, usize::MAX).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "leaf position 8 is out of 8 leaves")]
+    fn test_get_branch_out_of_range() {
+        let vals: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 2]).collect();
+        get_branch(&merkelize::<Sha256Hasher>(&vals), 8);
+    }
+
     #[test]
     fn test_verify_branch() {
         let vals = vec![vec![1, 2], vec![3, 4]];
-        let tree = merkelize(&vals);
+        let tree = merkelize::<Sha256Hasher>(&vals);
         let pos = 1;
         let branch = get_branch(&tree, pos);
-        let result = verify_branch(&tree[1], pos, &vals[1], &branch);
+        let result = verify_branch::<Sha256Hasher>(&tree[1], pos, &vals[1], &branch);
         assert_eq!(result, true);
     }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn test_keccak256_hasher() {
+        // keccak256("abc"), as in the Ethereum yellow paper and any web3 library
+        assert_eq!(
+            Keccak256Hasher::hash(b"abc"),
+            vec![
+                0x4e, 0x03, 0x65, 0x7a, 0xea, 0x45, 0xa9, 0x4f, 0xc7, 0xd4, 0x7b, 0xa8, 0x26, 0xc8,
+                0xd6, 0x67, 0xc0, 0xd1, 0xe6, 0xe3, 0x3a, 0x64, 0xa0, 0x36, 0xec, 0x44, 0xf5, 0x8f,
+                0xa1, 0x2d, 0x6c, 0x45
+            ]
+        );
+
+        // root = keccak256(abi.encodePacked(keccak256(hex"0102"), keccak256(hex"0304")))
+        let vals = vec![vec![1, 2], vec![3, 4]];
+        let tree = merkelize::<Keccak256Hasher>(&vals);
+        assert_eq!(
+            tree[1],
+            vec![
+                0x36, 0xf7, 0x10, 0x15, 0xfb, 0x11, 0xd7, 0x60, 0xfc, 0x09, 0xe3, 0x27, 0x0f, 0xf0,
+                0xab, 0x4e, 0x1d, 0x07, 0x8f, 0x92, 0xac, 0xfb, 0x44, 0xdb, 0xc0, 0x1b, 0xed, 0x9c,
+                0xc6, 0x82, 0x95, 0x3d
+            ]
+        );
+        for pos in 0..2 {
+            let branch = get_branch(&tree, pos);
+            assert!(verify_branch::<Keccak256Hasher>(
+                &tree[1], pos, &vals[pos], &branch
+            ));
+        }
+    }
 }
@@ -1,6 +1,13 @@
-mod binary_field16;
+pub mod binary_field16;
+pub mod binary_field32;
 pub mod binary_ntt_cache;
-mod challenger;
-mod merkle_tree;
+#[cfg(feature = "std")]
+pub mod challenger;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "std")]
+pub mod merkle_tree;
+#[cfg(feature = "std")]
 pub mod pcs;
-mod utils;
+#[cfg(feature = "std")]
+pub mod utils;
@@ -1,50 +1,715 @@
+// the defaults of PcsParams
 const EXPANSION_FACTOR: usize = 8;
 const NUM_CHALLENGES: usize = 32;
 const PACKING_FACTOR: usize = 16;
+// proof-of-work bits the prover grinds before the queries are drawn
+const GRINDING_BITS: u32 = 16;
+// the rows commit_reader reads, packs and extends at a time
+const READ_CHUNK_ROWS: usize = 64;
+// domain separation of the column queries from any other use of the same root
+const CHALLENGE_LABEL: &[u8] = b"binius-fri-query";
+// the random bit-rows commit_hiding appends, one per bit of the 128-bit mask of t_prime
+#[cfg(feature = "rand")]
+const HIDING_MASK_ROWS: usize = 128;
+// domain separation of the mask coefficient of a hiding proof from the column queries
+#[cfg(feature = "rand")]
+const MASK_LABEL: &[u8] = b"binius-hiding-mask";
 
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
 use std::str;
+use std::time::{Duration, Instant};
 
 use super::merkle_tree::get_branch;
-use p3_util::log2_strict_usize;
+use p3_util::{log2_ceil_usize, log2_strict_usize};
 
-use super::binary_field16::{big_mul, uint16_to_bit, uint16s_to_bits, BinaryFieldElement16};
-use super::challenger::get_challenges;
-use super::merkle_tree::{get_root, merkelize, verify_branch};
+#[cfg(feature = "rand")]
+use super::binary_field16::int_to_bigbin;
+use super::binary_field16::{
+    big_mul, bigbin_to_int, uint16_to_bit, uint16s_to_bits, BinaryFieldElement16, BIGBIN_LIMBS,
+};
+use super::binary_ntt_cache::warmup_caches;
+use super::challenger::{challenge_index_set, ChallengeError, Transcript};
+use super::error::BiniusError;
+use super::merkle_tree::{
+    get_branch_to_cap, get_cap, get_multi_branch, get_root, merkelize, merkelize_streaming,
+    merkle_root_streaming, root_of_cap, verify_branch_checked, verify_branch_to_cap,
+    verify_multi_branch, BranchError, MultiBranch,
+};
+// the hashers the *_with_hasher entry points are generic over
+#[cfg(feature = "blake3")]
+pub use super::merkle_tree::Blake3Hasher;
+#[cfg(feature = "keccak")]
+pub use super::merkle_tree::Keccak256Hasher;
+pub use super::merkle_tree::{MerkleHasher, Sha256Hasher, TruncatedSha256Hasher};
 use super::utils::{
-    choose_row_length_and_count, computed_tprimes, evaluation_tensor_product, extend_rows,
-    multisubset, pack_row, pack_rows, transpose, transpose_3d, transpose_bits, xor_along_axis,
+    choose_row_length_and_count, computed_tprimes_parallel, evaluation_tensor_product_parallel,
+    extend_rows, extend_rows_parallel, multisubset, pack_row, pack_rows, transpose, transpose_3d,
+    transpose_bits, transpose_parallel, try_choose_row_length_and_count, xor_along_axis_parallel,
+    MIN_LOG_EVALUATION_COUNT,
 };
 
+/** The parameters of the PCS, prover and verifier must use the same ones
+
+expansion_factor: the Reed-Solomon rate is 1 / expansion_factor, a power of two
+    num_challenges: the number of queried columns, more columns give more soundness and bigger proofs
+    packing_factor: the number of bits packed into one field element, 16 for BinaryFieldElement16
+    grinding_bits: the proof-of-work bits the prover grinds before the queries are drawn, 0 disables grinding
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PcsParams {
+    pub expansion_factor: usize,
+    pub num_challenges: usize,
+    pub packing_factor: usize,
+    pub grinding_bits: u32,
+}
+
+impl Default for PcsParams {
+    fn default() -> Self {
+        PcsParams {
+            expansion_factor: EXPANSION_FACTOR,
+            num_challenges: NUM_CHALLENGES,
+            packing_factor: PACKING_FACTOR,
+            grinding_bits: GRINDING_BITS,
+        }
+    }
+}
+
+/** Estimate the bits of soundness of a parameter choice
+
+the estimate is the union bound of two errors, bits = -log2(query_error + field_error):
+    query_error = (1 - delta)^num_challenges / 2^grinding_bits, a committed matrix that is
+        delta-far from the code passes each queried column with probability at most 1 - delta,
+        delta = (1 - 1 / expansion_factor) / 2 is the unique decoding radius of the Reed-Solomon code,
+        and the proof of work multiplies the cost of retrying with a new commitment or t_prime by 2^grinding_bits
+    field_error = 2^-field_bits, the chance that a wrong t_prime agrees with the columns at the random point,
+        the Schwartz-Zippel degree factor (at most log2 of the row count) is left out
+    it assumes the Merkle hash and the challenger are random oracles and the challenges are distinct,
+    a conjectured list decoding regime would give more bits for the same params
+
+Args:
+    params: the PCS parameters
+    field_bits: the bit size of the field the evaluation point is drawn from, 128 for the u128 coordinates
+
+Returns:
+    f64: the estimated bits of soundness
+*/
+pub fn security_bits(params: &PcsParams, field_bits: usize) -> f64 {
+    let delta = (1.0 - 1.0 / params.expansion_factor as f64) / 2.0;
+    let query_bits =
+        -(params.num_challenges as f64) * (1.0 - delta).log2() + params.grinding_bits as f64;
+    let query_error = (-query_bits).exp2();
+    let field_error = (-(field_bits as f64)).exp2();
+    -(query_error + field_error).log2()
+}
+
+/** The dimensions commit builds for a number of evaluation bits */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PcsLayout {
+    pub log_row_length: usize,
+    pub log_row_count: usize,
+    // the bits of a row, before packing
+    pub row_length: usize,
+    pub row_count: usize,
+    // the elements of a row after packing, row_length / packing_factor
+    pub packed_row_length: usize,
+    // the elements of a row after the Reed-Solomon extension, the number of columns
+    pub extended_row_length: usize,
+    // one leaf per column
+    pub leaf_count: usize,
+}
+
+/** The layout commit and prove derive from the size of the evaluations
+
+lets callers size buffers and estimate proofs before committing
+
+Args:
+    evaluation_bits: the number of evaluation bits, evaluations.len() * 8, a power of two
+    params: the PCS parameters
+
+Returns:
+    PcsLayout: the row, column and leaf counts of the commitment
+*/
+pub fn layout_for(evaluation_bits: usize, params: &PcsParams) -> PcsLayout {
+    let (log_row_length, log_row_count, row_length, row_count) =
+        choose_row_length_and_count(log2_strict_usize(evaluation_bits));
+    let packed_row_length = row_length / params.packing_factor;
+    let extended_row_length = packed_row_length * params.expansion_factor;
+    PcsLayout {
+        log_row_length,
+        log_row_count,
+        row_length,
+        row_count,
+        packed_row_length,
+        extended_row_length,
+        leaf_count: extended_row_length,
+    }
+}
+
+/** The PCS configured once: the params and the hasher H of the Merkle tree and the challenges
+
+a Binius commits, proves and verifies with the same params and H,
+    Binius::default() uses the default PcsParams and Sha256Hasher
+*/
+pub struct Binius<H: MerkleHasher = Sha256Hasher> {
+    pub params: PcsParams,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: MerkleHasher> Binius<H> {
+    pub fn new(params: PcsParams) -> Self {
+        Binius {
+            params,
+            _hasher: PhantomData,
+        }
+    }
+
+    pub fn commit(&self, evaluations: &[u8]) -> Commitment {
+        commit_with_hasher::<H>(evaluations, &self.params)
+    }
+
+    pub fn prove(
+        &self,
+        commitment: &Commitment,
+        evaluations: &[u8],
+        evaluation_point: &Vec<u128>,
+    ) -> Proof {
+        prove_with_hasher::<H>(commitment, evaluations, evaluation_point, &self.params)
+    }
+
+    pub fn verify(
+        &self,
+        commitment: &Commitment,
+        proof: &Proof,
+        evaluation_point: &Vec<u128>,
+    ) -> Result<(), VerifyError> {
+        verifier_with_hasher::<H>(commitment, proof, evaluation_point, &self.params)
+    }
+}
+
+impl Default for Binius {
+    fn default() -> Self {
+        Binius::new(PcsParams::default())
+    }
+}
+
+/** The prover's commitment
+
+root and packed_columns are all the verifier reads,
+    merkle_tree, rows and columns are only needed to prove, prune the commitment once the proofs are generated
+*/
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Commitment {
+    // the Merkle root, the actual commitment, needed by the verifier
     pub root: Vec<u8>,
+    // the Merkle leaves, the verifier reads their count, the opened columns are hashed into them
     pub packed_columns: Vec<Vec<u8>>,
+    // prove only: the branches are read from the full tree
     pub merkle_tree: Vec<Vec<u8>>,
+    // prove only: t_prime is a combination of the rows before extension
     pub rows: Vec<Vec<BinaryFieldElement16>>,
+    // prove only: the opened columns of the extended rows
+    pub columns: Vec<Vec<BinaryFieldElement16>>,
+    // the number of evaluation bytes before commit_padded padded them
+    pub original_len: usize,
+}
+
+/** A Commitment without the prover-only data, what the verifier needs */
+#[derive(Serialize, Deserialize)]
+pub struct PrunedCommitment {
+    pub root: Vec<u8>,
+    pub packed_columns: Vec<Vec<u8>>,
+}
+
+impl Commitment {
+    pub fn root(&self) -> &[u8] {
+        &self.root
+    }
+
+    /** The cap of the Merkle tree, see merkle_tree::get_cap
+
+    sent in place of the root, the branches can stop cap_height levels early, see prove_to_cap
+        and verify_to_cap. The commitment keeps the root, the challenges are still drawn from it,
+        a verifier holding the cap recomputes it with merkle_tree::root_of_cap
+    */
+    pub fn cap(&self, cap_height: usize) -> Vec<Vec<u8>> {
+        get_cap(&self.merkle_tree, cap_height)
+    }
+
+    /** The committed bytes of the index-th column, the Merkle leaf a branch of that column opens
+
+    Panics:
+        if index is not below the number of columns
+    */
+    pub fn column_bytes(&self, index: usize) -> Vec<u8> {
+        Self::check_column(index, self.packed_columns.len());
+        self.packed_columns[index].clone()
+    }
+
+    /** The elements of the index-th column of the extended rows, what a proof opening it carries
+
+    Panics:
+        if index is not below the number of columns
+    */
+    pub fn column_elements(&self, index: usize) -> &[BinaryFieldElement16] {
+        Self::check_column(index, self.columns.len());
+        &self.columns[index]
+    }
+
+    fn check_column(index: usize, column_count: usize) {
+        assert!(
+            index < column_count,
+            "column {} is out of the {} columns of the commitment",
+            index,
+            column_count
+        );
+    }
+
+    /** Keep only what prove_view reads, dropping rows and packed_columns */
+    pub fn into_prover_view(self) -> ProverView {
+        ProverView {
+            root: self.root,
+            merkle_tree: self.merkle_tree,
+            columns: self.columns,
+        }
+    }
+
+    /** Drop the prover-only fields (merkle_tree, rows, columns) once the proofs are generated */
+    pub fn prune(self) -> PrunedCommitment {
+        PrunedCommitment {
+            root: self.root,
+            packed_columns: self.packed_columns,
+        }
+    }
+}
+
+/** What prove reads from a Commitment, the lean commitment kept by the prover
+
+the rows are packed again from the evaluations passed to prove, and the packed columns
+    are only the verifier's, so a ProverView holds the extended columns once plus the tree
+*/
+#[derive(Serialize, Deserialize)]
+pub struct ProverView {
+    pub root: Vec<u8>,
+    pub merkle_tree: Vec<Vec<u8>>,
     pub columns: Vec<Vec<BinaryFieldElement16>>,
 }
 
+// what open reads from a Commitment or from a ProverView and its packed rows
+struct ProverInputs<'a> {
+    root: &'a Vec<u8>,
+    merkle_tree: &'a Vec<Vec<u8>>,
+    columns: &'a Vec<Vec<BinaryFieldElement16>>,
+    rows: &'a Vec<Vec<BinaryFieldElement16>>,
+}
+
+impl<'a> From<&'a Commitment> for ProverInputs<'a> {
+    fn from(commitment: &'a Commitment) -> Self {
+        ProverInputs {
+            root: &commitment.root,
+            merkle_tree: &commitment.merkle_tree,
+            columns: &commitment.columns,
+            rows: &commitment.rows,
+        }
+    }
+}
+
+// the prover's messages the challenges are drawn after
+struct Opening {
+    t_prime: Vec<Vec<u16>>,
+    eval: Vec<u16>,
+    grinding_nonce: Option<u64>,
+}
+
+impl PrunedCommitment {
+    pub fn root(&self) -> &[u8] {
+        &self.root
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Proof {
     pub evaluation_point: Vec<u128>,
     pub eval: Vec<u16>,
     pub t_prime: Vec<Vec<u16>>,
     pub columns: Vec<Vec<BinaryFieldElement16>>,
     pub branches: Vec<Vec<Vec<u8>>>,
+    // the proof-of-work nonce, None when grinding_bits = 0
+    pub grinding_nonce: Option<u64>,
 }
 
-pub fn commit(evaluations: &[u8]) -> Commitment {
-    let log_evaluation_count = log2_strict_usize(evaluations.len() * 8);
-    let (log_row_length, log_row_count, row_length, row_count) =
-        choose_row_length_and_count(log_evaluation_count);
+// Debug prints the dimensions of the matrices and a prefix of the root instead of megabytes of elements
+impl fmt::Debug for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Commitment")
+            .field("root", &format_args!("{}", hex_prefix(&self.root)))
+            .field(
+                "packed_columns",
+                &format_args!("{}", dims(&self.packed_columns)),
+            )
+            .field(
+                "merkle_tree",
+                &format_args!("{} nodes", self.merkle_tree.len()),
+            )
+            .field("rows", &format_args!("{}", dims(&self.rows)))
+            .field("columns", &format_args!("{}", dims(&self.columns)))
+            .field("original_len", &self.original_len)
+            .finish()
+    }
+}
 
-    // row packing, convert each rows into a list of BinaryFieldElement16s
-    let rows = pack_rows(evaluations, row_count, row_length, PACKING_FACTOR);
+impl fmt::Debug for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Proof")
+            .field(
+                "evaluation_point",
+                &format_args!("{} coordinates", self.evaluation_point.len()),
+            )
+            .field("eval", &self.eval)
+            .field("t_prime", &format_args!("{}", dims(&self.t_prime)))
+            .field("columns", &format_args!("{}", dims(&self.columns)))
+            .field("branches", &format_args!("{}", dims(&self.branches)))
+            .field("grinding_nonce", &self.grinding_nonce)
+            .finish()
+    }
+}
 
-    // Fast-Fourier extend the rows
-    let extended_rows = extend_rows(&rows, EXPANSION_FACTOR);
-    let extended_row_length = row_length * EXPANSION_FACTOR / PACKING_FACTOR;
+// the first 6 bytes in hex, followed by ... when there are more
+fn hex_prefix(bytes: &[u8]) -> String {
+    let prefix: String = bytes.iter().take(6).map(|b| format!("{:02x}", b)).collect();
+    if bytes.len() > 6 {
+        prefix + "..."
+    } else {
+        prefix
+    }
+}
+
+// rows x columns of a matrix, the column count is the length of the first row
+fn dims<T>(matrix: &[Vec<T>]) -> String {
+    format!(
+        "{}x{}",
+        matrix.len(),
+        matrix.first().map_or(0, |row| row.len())
+    )
+}
+
+/** Why Proof::from_bytes rejected its input */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofDecodeError {
+    // the input ends before the section being read
+    Truncated,
+    // the grinding nonce tag is neither 0 (None) nor 1 (Some)
+    InvalidNonceTag(u8),
+    // bytes are left over after the last section
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for ProofDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofDecodeError::Truncated => write!(f, "the proof bytes are truncated"),
+            ProofDecodeError::InvalidNonceTag(tag) => {
+                write!(f, "invalid grinding nonce tag {}", tag)
+            }
+            ProofDecodeError::TrailingBytes(n) => {
+                write!(f, "{} trailing bytes after the proof", n)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProofDecodeError {}
+
+impl Proof {
+    /** Encode the proof in a compact little-endian layout
+
+    every vector is prefixed by its length as a u32, the sections come in field order:
+        evaluation_point: u32 n, n * u128
+        eval: u32 n, n * u16
+        t_prime: u32 rows, per row u32 n, n * u16
+        columns: u32 count, per column u32 n, n * u16
+        branches: u32 count, per branch u32 nodes, per node u32 n, n * u8
+        grinding_nonce: u8 tag (0 = None, 1 = Some), then the u64 if Some
+
+    Returns:
+        the encoded proof, about the raw size of the field elements and hashes
+    */
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        put_len(&mut out, self.evaluation_point.len());
+        for x in &self.evaluation_point {
+            out.extend_from_slice(&x.to_le_bytes());
+        }
+        put_u16s(&mut out, &self.eval);
+        put_len(&mut out, self.t_prime.len());
+        for row in &self.t_prime {
+            put_u16s(&mut out, row);
+        }
+        put_len(&mut out, self.columns.len());
+        for column in &self.columns {
+            put_len(&mut out, column.len());
+            for x in column {
+                out.extend_from_slice(&x.value.to_le_bytes());
+            }
+        }
+        put_len(&mut out, self.branches.len());
+        for branch in &self.branches {
+            put_len(&mut out, branch.len());
+            for node in branch {
+                put_len(&mut out, node.len());
+                out.extend_from_slice(node);
+            }
+        }
+        match self.grinding_nonce {
+            Some(nonce) => {
+                out.push(1);
+                out.extend_from_slice(&nonce.to_le_bytes());
+            }
+            None => out.push(0),
+        }
+        out
+    }
+
+    /** Decode a proof encoded by to_bytes
+
+    Args:
+        bytes: the whole encoded proof, trailing bytes are rejected
+
+    Returns:
+        the proof, or why the bytes are not a proof
+    */
+    pub fn from_bytes(bytes: &[u8]) -> Result<Proof, ProofDecodeError> {
+        let mut reader = ByteReader { bytes };
+
+        let n = reader.len(16)?;
+        let mut evaluation_point = Vec::with_capacity(n);
+        for _ in 0..n {
+            evaluation_point.push(u128::from_le_bytes(reader.array()?));
+        }
+        let eval = reader.u16s()?;
+        let n = reader.len(4)?;
+        let mut t_prime = Vec::with_capacity(n);
+        for _ in 0..n {
+            t_prime.push(reader.u16s()?);
+        }
+        let n = reader.len(4)?;
+        let mut columns = Vec::with_capacity(n);
+        for _ in 0..n {
+            columns.push(
+                reader
+                    .u16s()?
+                    .into_iter()
+                    .map(BinaryFieldElement16::new)
+                    .collect(),
+            );
+        }
+        let n = reader.len(4)?;
+        let mut branches = Vec::with_capacity(n);
+        for _ in 0..n {
+            let nodes = reader.len(4)?;
+            let mut branch = Vec::with_capacity(nodes);
+            for _ in 0..nodes {
+                let len = reader.len(1)?;
+                branch.push(reader.take(len)?.to_vec());
+            }
+            branches.push(branch);
+        }
+        let grinding_nonce = match reader.take(1)?[0] {
+            0 => None,
+            1 => Some(u64::from_le_bytes(reader.array()?)),
+            tag => return Err(ProofDecodeError::InvalidNonceTag(tag)),
+        };
+
+        if !reader.bytes.is_empty() {
+            return Err(ProofDecodeError::TrailingBytes(reader.bytes.len()));
+        }
+        Ok(Proof {
+            evaluation_point,
+            eval,
+            t_prime,
+            columns,
+            branches,
+            grinding_nonce,
+        })
+    }
+}
+
+/** The bytes each section of a proof takes in the Proof::to_bytes layout, length prefixes included */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofSizeBreakdown {
+    pub evaluation_point: usize,
+    pub eval: usize,
+    pub t_prime: usize,
+    pub columns: usize,
+    pub branches: usize,
+    // the tag byte and the u64 nonce if there is one
+    pub grinding_nonce: usize,
+}
+
+impl ProofSizeBreakdown {
+    /** the length of Proof::to_bytes */
+    pub fn total(&self) -> usize {
+        self.evaluation_point
+            + self.eval
+            + self.t_prime
+            + self.columns
+            + self.branches
+            + self.grinding_nonce
+    }
+}
+
+/** Count the encoded size of a proof by section, without encoding it
+
+Args:
+    proof: the proof
+
+Returns:
+    ProofSizeBreakdown: the bytes of each section of Proof::to_bytes, they sum to its length
+*/
+pub fn proof_size_bytes(proof: &Proof) -> ProofSizeBreakdown {
+    // every vector is prefixed by its u32 length
+    let vector = |len: usize, element_size: usize| 4 + len * element_size;
+    ProofSizeBreakdown {
+        evaluation_point: vector(proof.evaluation_point.len(), 16),
+        eval: vector(proof.eval.len(), 2),
+        t_prime: 4 + proof
+            .t_prime
+            .iter()
+            .map(|row| vector(row.len(), 2))
+            .sum::<usize>(),
+        columns: 4 + proof
+            .columns
+            .iter()
+            .map(|column| vector(column.len(), 2))
+            .sum::<usize>(),
+        branches: 4 + proof
+            .branches
+            .iter()
+            .map(|branch| {
+                4 + branch
+                    .iter()
+                    .map(|node| vector(node.len(), 1))
+                    .sum::<usize>()
+            })
+            .sum::<usize>(),
+        grinding_nonce: if proof.grinding_nonce.is_some() { 9 } else { 1 },
+    }
+}
+
+/** The eval of a proof as one 128-bit integer, the encoding of the simd Proof.eval
+
+the 8 uint16 limbs are joined least significant first, only the encoding is shared with simd:
+    its eval is a big_mul product, not a tower-field element, so the same input gives different values
+
+Args:
+    proof: the proof
+
+Returns:
+    u128: bigbin_to_int of the eval
+*/
+pub fn eval_as_u128(proof: &Proof) -> u128 {
+    bigbin_to_int(&proof.eval)
+}
+
+fn put_len(out: &mut Vec<u8>, len: usize) {
+    out.extend_from_slice(&(len as u32).to_le_bytes());
+}
+
+fn put_u16s(out: &mut Vec<u8>, values: &[u16]) {
+    put_len(out, values.len());
+    for x in values {
+        out.extend_from_slice(&x.to_le_bytes());
+    }
+}
+
+// reads Proof::to_bytes sections from the front of the input, any short read is Truncated
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ProofDecodeError> {
+        if self.bytes.len() < n {
+            return Err(ProofDecodeError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ProofDecodeError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    // a length prefix of items of at least min_item_size bytes, checked against the remaining input
+    // so a corrupted prefix cannot trigger a huge allocation
+    fn len(&mut self, min_item_size: usize) -> Result<usize, ProofDecodeError> {
+        let len = u32::from_le_bytes(self.array()?) as usize;
+        if len.saturating_mul(min_item_size) > self.bytes.len() {
+            return Err(ProofDecodeError::Truncated);
+        }
+        Ok(len)
+    }
+
+    fn u16s(&mut self) -> Result<Vec<u16>, ProofDecodeError> {
+        let n = self.len(2)?;
+        Ok(self
+            .take(n * 2)?
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect())
+    }
+}
+
+/** The number of coordinates of the evaluation point to prove evaluations with
+
+the evaluations are a multilinear polynomial over len * 8 bits, one coordinate per bit of the
+    evaluation index, so prove and verifier take a point of log2(len * 8) coordinates,
+    e.g. 23 for 1 << 20 bytes
+
+Args:
+    evaluations: the committed evaluations, a power-of-two number of bits
 
-    // Pack columns into a Merkle tree
-    let columns = transpose(&extended_rows);
+Returns:
+    usize: the length of the evaluation point
+*/
+pub fn required_point_len(evaluations: &[u8]) -> usize {
+    log2_strict_usize(evaluations.len() * 8)
+}
+
+pub fn commit(evaluations: &[u8], params: &PcsParams) -> Commitment {
+    commit_with_hasher::<Sha256Hasher>(evaluations, params)
+}
+
+/** commit with the Merkle tree hashed by H
+
+a commitment made with H must be proven and verified with the same H
+    (prove_with_hasher::<H>, verifier_with_hasher::<H>), since the challenges are derived from the root with H
+    evaluations the commitment could not be proven for (not a power-of-two number of bits, or fewer bytes
+    than min_evaluation_len) panic with the BiniusError of commit_checked, commit_padded takes any length
+*/
+pub fn commit_with_hasher<H: MerkleHasher>(evaluations: &[u8], params: &PcsParams) -> Commitment {
+    if let Err(e) = check_evaluation_len(evaluations.len(), params) {
+        panic!(
+            "cannot commit to {} bytes: {}, commit_padded pads any length",
+            evaluations.len(),
+            e
+        );
+    }
+    // row packing and Fast-Fourier extension, then pack columns into a Merkle tree
+    let (rows, columns) = encode_rows(evaluations, params);
+    commit_encoded::<H>(rows, columns, evaluations.len())
+}
+
+// the Merkle tree of the columns of the extended rows
+fn commit_encoded<H: MerkleHasher>(
+    rows: Vec<Vec<BinaryFieldElement16>>,
+    columns: Vec<Vec<BinaryFieldElement16>>,
+    original_len: usize,
+) -> Commitment {
     // packed_columns = [col.tobytes('C') for col in columns]
     // let packed_columns = columns
     //     .iter()
@@ -54,7 +719,7 @@ pub fn commit(evaluations: &[u8]) -> Commitment {
         .iter()
         .map(|col| col.iter().copied().collect())
         .collect();
-    let merkle_tree = merkelize(&packed_columns);
+    let merkle_tree = merkelize::<H>(&packed_columns);
     let root = get_root(&merkle_tree);
 
     Commitment {
@@ -63,191 +728,4281 @@ pub fn commit(evaluations: &[u8]) -> Commitment {
         merkle_tree,
         rows,
         columns,
+        original_len,
     }
 }
 
-pub fn prove(commitment: &Commitment, evaluations: &[u8], evaluation_point: &Vec<u128>) -> Proof {
-    let log_evaluation_count = log2_strict_usize(evaluations.len() * 8);
-    let (log_row_length, log_row_count, row_length, row_count) =
-        choose_row_length_and_count(log_evaluation_count);
-    let extended_row_length = row_length * EXPANSION_FACTOR / PACKING_FACTOR;
+pub fn commit_checked(evaluations: &[u8], params: &PcsParams) -> Result<Commitment, BiniusError> {
+    commit_checked_with_hasher::<Sha256Hasher>(evaluations, params)
+}
 
-    // Compute t_prime: linear combination of rows before extension
-    let row_combination = evaluation_tensor_product(&evaluation_point[log_row_length..].to_vec());
-    assert_eq!(row_combination.len(), commitment.rows.len());
-    let rows_as_bits_transpose = transpose_bits(
-        commitment
-            .rows
-            .iter()
-            .map(|row| uint16s_to_bits(row))
-            .collect(),
-    );
-    let t_prime = computed_tprimes(&rows_as_bits_transpose, &row_combination);
+/** commit, but check the evaluations and the params first instead of panicking inside
 
-    // Get challenges
-    let challenges = get_challenges(&commitment.root, extended_row_length, NUM_CHALLENGES);
+Args:
+    evaluations: the evaluations, a power-of-two number of bits, at least 2^MIN_LOG_EVALUATION_COUNT
+    params: the PcsParams, checked with check_params
 
-    // Compute evaluation
-    let col_combination = evaluation_tensor_product(&evaluation_point[..log_row_length].to_vec());
-    // for each row in t_prime and each row in col_combination, use big_mul to multiply them
-    let multi_result = t_prime
-        .iter()
-        .zip(col_combination.iter())
-        .map(|(t_prime_row, col_combination_row)| big_mul(t_prime_row, col_combination_row))
-        .collect::<Vec<Vec<u16>>>();
-    let computed_eval = xor_along_axis(&multi_result, 0);
+Returns:
+    the commitment, or the BiniusError describing the invalid input
+*/
+pub fn commit_checked_with_hasher<H: MerkleHasher>(
+    evaluations: &[u8],
+    params: &PcsParams,
+) -> Result<Commitment, BiniusError> {
+    check_evaluation_len(evaluations.len(), params)?;
+    Ok(commit_with_hasher::<H>(evaluations, params))
+}
 
-    Proof {
-        evaluation_point: evaluation_point.clone(),
-        eval: computed_eval,
-        t_prime,
-        columns: challenges
-            .iter()
-            .map(|&c| commitment.columns[c as usize].clone())
-            .collect(),
-        branches: challenges
-            .iter()
-            .map(|c| get_branch(&commitment.merkle_tree, (*c).into()))
-            .collect(),
+// the checks of commit_checked, on the byte length of the evaluations
+fn check_evaluation_len(len: usize, params: &PcsParams) -> Result<(), BiniusError> {
+    let evaluation_count = len * 8;
+    if !evaluation_count.is_power_of_two() {
+        return Err(BiniusError::NotPowerOfTwo(evaluation_count));
     }
+    try_choose_row_length_and_count(log2_strict_usize(evaluation_count))?;
+    check_params(evaluation_count, params)
 }
 
-pub fn verifier(commitment: &Commitment, proof: &Proof, evaluation_point: &Vec<u128>) -> bool {
-    let columns = &commitment.packed_columns;
-    let evaluation_point = &proof.evaluation_point;
-    let value = &proof.eval;
-    let t_prime = &proof.t_prime;
-    let root = &commitment.root;
-    let branches = &proof.branches;
+/** The smallest number of evaluation bytes commit accepts with params
 
-    // Compute the row length and row count of the grid. Should output same numbers as what prover gave
-    let (log_row_length, log_row_count, row_length, row_count) =
-        choose_row_length_and_count(evaluation_point.len());
-    let extended_row_length = row_length * EXPANSION_FACTOR / PACKING_FACTOR;
-
-    // Compute challenges. Should output the same as what prover computed
-    let challenges = get_challenges(&root, extended_row_length, NUM_CHALLENGES);
-
-    // Verify Merkle branches
-    for i in 0..NUM_CHALLENGES {
-        let challenge = challenges[i];
-        let packed_column: Vec<u8> = columns[challenge as usize].clone().into_iter().collect();
-        let branch = branches[i].clone();
-        assert!(verify_branch(
-            &root,
-            challenge as usize,
-            &packed_column,
-            &branch
-        ));
-    }
+below it the rows are shorter than a packed element, or the extended rows have fewer columns
+    than num_challenges, so the challenges could not be drawn. 128 bytes with the default params
+    (rows of 64 bits, 32 columns after extension), shorter evaluations go through commit_padded
 
-    // Use the same Reed-Solomon code that the prover used to extend the rows,
-    // but to extend t_prime. We do this separately for each bit of t_prime
-    // each row in t_prime is a list of uint16s, use uint16s_to_bits to convert it to a list of bits
-    let t_prime_bits: Vec<Vec<u8>> = t_prime.iter().map(|row| uint16s_to_bits(row)).collect();
+Args:
+    params: the PcsParams, with a power-of-two expansion_factor and a packing_factor of 16
 
-    // transpose the bits
-    let t_prime_bits_transpose = transpose_bits(t_prime_bits);
-    // pack the each row of t_prime_bits_transpose into a list of BinaryFieldElement16s
-    let t_prime_columns: Vec<Vec<BinaryFieldElement16>> = t_prime_bits_transpose
-        .iter()
-        .map(|row| pack_row(row, t_prime_bits_transpose[0].len() * 8, PACKING_FACTOR))
-        .collect();
-    // extend the rows
-    let extended_t_prime_columns = extend_rows(&t_prime_columns, EXPANSION_FACTOR);
+Returns:
+    usize: the minimum length in bytes, a power of two
+*/
+pub fn min_evaluation_len(params: &PcsParams) -> usize {
+    (1 << padded_log_evaluation_count(0, params)) / 8
+}
 
-    // Here, we take advantage of the linearity of the code. A linear combination of the Reed-Solomon extension gives the same result as an extension of the linear combination.
-    let row_combination = evaluation_tensor_product(&evaluation_point[log_row_length..].to_vec());
-    // Use Challenge to select columns from columns
-    let selected_columns: Vec<Vec<BinaryFieldElement16>> = proof.columns.clone();
-    // Each column is a vector of row_count uint16's. Convert each uint16 into bits
-    let column_bits: Vec<Vec<Vec<u8>>> = selected_columns
-        .iter()
-        .map(|col| col.iter().map(|uint16| uint16_to_bit(uint16)).collect())
-        .collect();
-    // Take the same linear combination the prover used to compute t_prime, and apply it to the columns of bits.
-    let transposed_column_bits = transpose_3d(&column_bits, (0, 2, 1));
-    let computed_tprimes = multisubset(&row_combination, &transposed_column_bits);
-    // Turn the computed tprimes into bits using uint16s_to_bits
-    let computed_tprime_bits: Vec<Vec<Vec<u8>>> = computed_tprimes
-        .iter()
-        .map(|row| row.iter().map(|uint16| uint16s_to_bits(uint16)).collect())
-        .collect();
+/** Check that params can commit to evaluation_count bits and later draw the challenges
 
-    // Convert our FFT-extended t_prime rows into bits
-    // step 1: use challenge to select columns, and convert to bits
-    let extended_t_prime_columns_slices: Vec<Vec<Vec<BinaryFieldElement16>>> =
-        extended_t_prime_columns
-            .iter()
-            .map(|row| challenges.iter().map(|&c| vec![row[c as usize]]).collect())
-            .collect();
-    let extended_t_prime_bits: Vec<Vec<Vec<u8>>> = extended_t_prime_columns_slices
-        .iter()
-        .map(|row| row.iter().map(|uint16| uint16s_to_bits(uint16)).collect())
-        .collect();
-    // step 2: transpose the bits
-    let extended_t_prime_bits_transpose = transpose_3d(&extended_t_prime_bits, (1, 2, 0));
+Returns:
+    Ok, or NotPowerOfTwo for the expansion factor, InvalidLength for a packing factor other than 16
+    or rows shorter than one element, TooManyChallenges when there are fewer columns than challenges
+*/
+pub fn check_params(evaluation_count: usize, params: &PcsParams) -> Result<(), BiniusError> {
+    if !params.expansion_factor.is_power_of_two() {
+        return Err(BiniusError::NotPowerOfTwo(params.expansion_factor));
+    }
+    if params.packing_factor != 16 {
+        return Err(BiniusError::InvalidLength {
+            expected: 16,
+            actual: params.packing_factor,
+        });
+    }
+    let (_, _, row_length, _) =
+        try_choose_row_length_and_count(log2_strict_usize(evaluation_count))?;
+    if row_length < params.packing_factor {
+        return Err(BiniusError::InvalidLength {
+            expected: params.packing_factor,
+            actual: row_length,
+        });
+    }
+    let extended_row_length = row_length * params.expansion_factor / params.packing_factor;
+    if extended_row_length < params.num_challenges {
+        return Err(BiniusError::TooManyChallenges {
+            num_challenges: params.num_challenges,
+            extended_row_length,
+        });
+    }
+    Ok(())
+}
 
-    // The bits of the t_prime extension should equal the bits of the row linear combination of the column bits
-    assert_eq!(computed_tprime_bits, extended_t_prime_bits_transpose);
+pub fn commit_elements(elements: &[BinaryFieldElement16], params: &PcsParams) -> Commitment {
+    commit_elements_with_hasher::<Sha256Hasher>(elements, params)
+}
 
-    // Compute the evaluation
-    let col_combination = evaluation_tensor_product(&evaluation_point[..log_row_length].to_vec());
-    let computed_eval = xor_along_axis(
-        &t_prime
-            .iter()
-            .zip(col_combination.iter())
-            .map(|(t_prime_row, col_combination_row)| big_mul(t_prime_row, col_combination_row))
-            .collect::<Vec<Vec<u16>>>(),
-        0,
+/** commit to field elements that are already packed
+
+the elements are the packed rows themselves, row after row, so the bit extraction of pack_rows is skipped,
+    the commitment is the one commit builds from the little-endian bytes of the elements,
+    which also gives the evaluations to use for the evaluation point and original_len
+
+Args:
+    elements: the packed evaluations, a power-of-two number of them
+    params: the PcsParams, packing_factor must be 16, the bits of a BinaryFieldElement16
+
+Returns:
+    the commitment of the elements
+*/
+pub fn commit_elements_with_hasher<H: MerkleHasher>(
+    elements: &[BinaryFieldElement16],
+    params: &PcsParams,
+) -> Commitment {
+    assert_eq!(
+        params.packing_factor, 16,
+        "a BinaryFieldElement16 packs 16 evaluations"
     );
-    assert_eq!(computed_eval, *value);
-    true
+    let log_evaluation_count = log2_strict_usize(elements.len() * params.packing_factor);
+    let (_, _, row_length, _) = choose_row_length_and_count(log_evaluation_count);
+    let rows: Vec<Vec<BinaryFieldElement16>> = elements
+        .chunks(row_length / params.packing_factor)
+        .map(|row| row.to_vec())
+        .collect();
+    let columns = transpose_parallel(&extend_rows(&rows, params.expansion_factor));
+    commit_encoded::<H>(rows, columns, elements.len() * 2)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub fn commit_reader<R: io::Read>(
+    reader: R,
+    total_bytes: usize,
+    params: &PcsParams,
+) -> io::Result<Commitment> {
+    commit_reader_with_hasher::<Sha256Hasher, R>(reader, total_bytes, params)
+}
 
-    #[test]
-    fn test_commit() {
-        let evaluations = vec![1; 1 << 20];
-        let result = commit(&evaluations);
+/** commit to evaluations read from reader, without holding them all in memory
 
-        assert_eq!(
-            result.root,
-            vec![
-                14, 137, 1, 182, 32, 73, 136, 127, 237, 218, 39, 11, 5, 243, 134, 95, 106, 158,
-                189, 161, 93, 114, 169, 113, 24, 23, 215, 128, 16, 106, 56, 90
-            ]
-        );
+the rows are read READ_CHUNK_ROWS at a time, packed, extended, and appended to the columns,
+    so neither the evaluation bytes nor the extended rows are ever held whole,
+    only the rows and the columns the Commitment keeps anyway,
+    the commitment is the one commit builds from the same bytes
+
+Args:
+    reader: the evaluations, at least total_bytes of them
+    total_bytes: the number of evaluation bytes, total_bytes * 8 is a power of two
+    params: the PcsParams
+
+Returns:
+    the commitment, or the io::Error of the reader, UnexpectedEof when it ends before total_bytes
+*/
+pub fn commit_reader_with_hasher<H: MerkleHasher, R: io::Read>(
+    mut reader: R,
+    total_bytes: usize,
+    params: &PcsParams,
+) -> io::Result<Commitment> {
+    if !(total_bytes * 8).is_power_of_two() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} evaluation bytes are not a power-of-two number of bits",
+                total_bytes
+            ),
+        ));
+    }
+    let layout = layout_for(total_bytes * 8, params);
+    let row_bytes = layout.row_length / 8;
+
+    let mut rows = Vec::with_capacity(layout.row_count);
+    let mut columns = vec![Vec::with_capacity(layout.row_count); layout.extended_row_length];
+    let mut buffer = vec![0u8; READ_CHUNK_ROWS * row_bytes];
+    while rows.len() < layout.row_count {
+        let chunk_rows = READ_CHUNK_ROWS.min(layout.row_count - rows.len());
+        let chunk = &mut buffer[..chunk_rows * row_bytes];
+        reader.read_exact(chunk)?;
+
+        let packed = pack_rows(chunk, chunk_rows, layout.row_length, params.packing_factor);
+        for extended_row in extend_rows_parallel(&packed, params.expansion_factor) {
+            for (column, element) in columns.iter_mut().zip(extended_row) {
+                column.push(element);
+            }
+        }
+        rows.extend(packed);
+    }
+    Ok(commit_encoded::<H>(rows, columns, total_bytes))
+}
+
+pub fn commit_root_only(evaluations: &[u8], params: &PcsParams) -> Vec<u8> {
+    commit_root_only_with_hasher::<Sha256Hasher>(evaluations, params)
+}
+
+/** The root commit would produce, without keeping the Merkle tree or the packed columns
+
+the columns are packed one at a time and merged into the root as they come, see merkle_root_streaming
+
+Args:
+    evaluations: the evaluations
+    params: the PcsParams
+
+Returns:
+    the Merkle root, equal to commit(evaluations, params).root
+*/
+pub fn commit_root_only_with_hasher<H: MerkleHasher>(
+    evaluations: &[u8],
+    params: &PcsParams,
+) -> Vec<u8> {
+    let (_, columns) = encode_rows(evaluations, params);
+    merkle_root_streaming::<H, _>(
+        columns.iter().map(|col| col.iter().copied().collect()),
+        columns.len(),
+    )
+}
+
+pub fn commit_padded(evaluations: &[u8], params: &PcsParams) -> Commitment {
+    commit_padded_with_hasher::<Sha256Hasher>(evaluations, params)
+}
+
+/** commit to evaluations of any length
+
+the evaluations are zero-padded up to a power-of-two bit length, and further up to the smallest
+    size whose extended rows can serve num_challenges distinct queries.
+    prove takes the unpadded evaluations and an evaluation point of as few coordinates as the
+    unpadded evaluations need, the missing coordinates are 0 so the padding does not change the eval
+
+Args:
+    evaluations: the evaluations, original_len of the commitment is their length
+    params: the PcsParams
+
+Returns:
+    the commitment of the padded evaluations
+*/
+pub fn commit_padded_with_hasher<H: MerkleHasher>(
+    evaluations: &[u8],
+    params: &PcsParams,
+) -> Commitment {
+    let log_evaluation_count = padded_log_evaluation_count(evaluations.len(), params);
+    let mut commitment = commit_with_hasher::<H>(
+        &zero_pad(evaluations, (1 << log_evaluation_count) / 8),
+        params,
+    );
+    commitment.original_len = evaluations.len();
+    commitment
+}
+
+// the log2 of the number of bits commit_padded commits to
+fn padded_log_evaluation_count(len: usize, params: &PcsParams) -> usize {
+    let mut log_evaluation_count = log2_ceil_usize(len * 8).max(MIN_LOG_EVALUATION_COUNT);
+    loop {
+        let (_, _, row_length, _) = choose_row_length_and_count(log_evaluation_count);
+        if row_length >= params.packing_factor
+            && row_length * params.expansion_factor / params.packing_factor >= params.num_challenges
+        {
+            return log_evaluation_count;
+        }
+        log_evaluation_count += 1;
+    }
+}
+
+fn zero_pad(evaluations: &[u8], len: usize) -> Vec<u8> {
+    assert!(evaluations.len() <= len);
+    let mut padded = evaluations.to_vec();
+    padded.resize(len, 0);
+    padded
+}
+
+// extend the point with 0 coordinates up to the committed number of variables
+fn pad_evaluation_point(evaluation_point: &Vec<u128>, log_evaluation_count: usize) -> Vec<u128> {
+    assert!(evaluation_point.len() <= log_evaluation_count);
+    let mut padded = evaluation_point.clone();
+    padded.resize(log_evaluation_count, 0);
+    padded
+}
+
+pub fn commit_lean(evaluations: &[u8], params: &PcsParams) -> ProverView {
+    commit_lean_with_hasher::<Sha256Hasher>(evaluations, params)
+}
+
+/** commit without keeping the rows and the packed columns
+
+same root as commit_with_hasher, the rows are dropped right after the extension and each
+    packed column is hashed and dropped while the tree is built
+*/
+pub fn commit_lean_with_hasher<H: MerkleHasher>(
+    evaluations: &[u8],
+    params: &PcsParams,
+) -> ProverView {
+    let (_, columns) = encode_rows(evaluations, params);
+    let merkle_tree = merkelize_streaming::<H, _>(
+        columns.iter().map(|col| col.iter().copied().collect()),
+        columns.len(),
+    );
+    let root = get_root(&merkle_tree);
+
+    ProverView {
+        root,
+        merkle_tree,
+        columns,
+    }
+}
+
+/** Pack the evaluations into rows and Reed-Solomon extend them
+
+Returns:
+    (rows, columns): the rows before extension and the columns of the extended rows
+*/
+fn encode_rows(
+    evaluations: &[u8],
+    params: &PcsParams,
+) -> (
+    Vec<Vec<BinaryFieldElement16>>,
+    Vec<Vec<BinaryFieldElement16>>,
+) {
+    let log_evaluation_count = log2_strict_usize(evaluations.len() * 8);
+    let (log_row_length, log_row_count, row_length, row_count) =
+        choose_row_length_and_count(log_evaluation_count);
+
+    // row packing, convert each rows into a list of BinaryFieldElement16s
+    let rows = pack_rows(evaluations, row_count, row_length, params.packing_factor);
+
+    // Fast-Fourier extend the rows
+    let extended_rows = extend_rows(&rows, params.expansion_factor);
+
+    let columns = transpose_parallel(&extended_rows);
+    (rows, columns)
+}
+
+pub fn prove(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Proof {
+    prove_with_hasher::<Sha256Hasher>(commitment, evaluations, evaluation_point, params)
+}
+
+/** Open the commitment at evaluation_point
+
+the committed rows, padding included, are opened, so evaluations is only compared with the size
+    the commitment was built from, commit_padded ones are passed before padding
+
+Panics:
+    if evaluations is not as long as the committed evaluations, prove_checked returns the error instead
+*/
+pub fn prove_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Proof {
+    check_committed_len(commitment, evaluations).unwrap_or_else(|e| panic!("{}", e));
+    open::<H>(
+        &ProverInputs::from(commitment),
+        evaluation_point,
+        params,
+        &FiatShamirChallenger::<H>::new(),
+        true,
+    )
+}
+
+pub fn prove_with_challenger(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+    challenger: &dyn Challenger,
+) -> Proof {
+    prove_with_challenger_with_hasher::<Sha256Hasher>(
+        commitment,
+        evaluations,
+        evaluation_point,
+        params,
+        challenger,
+    )
+}
+
+/** prove with the queried columns drawn by challenger instead of the Fiat-Shamir transcript
+
+the verifier must use the same challenger, see verifier_with_challenger,
+    prove is this with FiatShamirChallenger::<H>
+*/
+pub fn prove_with_challenger_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+    challenger: &dyn Challenger,
+) -> Proof {
+    check_committed_len(commitment, evaluations).unwrap_or_else(|e| panic!("{}", e));
+    open::<H>(
+        &ProverInputs::from(commitment),
+        evaluation_point,
+        params,
+        challenger,
+        true,
+    )
+}
+
+/** Why prove_checked refused to open a commitment */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProveError {
+    // the evaluations are not as many bytes as the ones the commitment was built from
+    CommittedSize {
+        committed: usize,
+        evaluations: usize,
+    },
+    // the point does not have one coordinate per bit of the evaluation index
+    PointLength {
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl fmt::Display for ProveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProveError::CommittedSize {
+                committed,
+                evaluations,
+            } => write!(
+                f,
+                "the commitment holds {} evaluation bytes but {} were given",
+                committed, evaluations
+            ),
+            ProveError::PointLength { expected, actual } => write!(
+                f,
+                "the evaluation point has {} coordinates, expected log2_ceil(evaluations.len() * 8) = {}",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProveError {}
+
+/** Prove, but check the inputs first instead of panicking deep inside open
+
+Args:
+    commitment: the commitment to the evaluations
+    evaluations: the evaluations the commitment was built from
+    evaluation_point: one coordinate per bit of the evaluation index, log2(evaluations.len() * 8) of them,
+        rounded up for a commit_padded commitment
+    params: the PCS parameters of the commitment
+
+Returns:
+    the proof, or the first input that does not fit
+*/
+pub fn prove_checked(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Result<Proof, ProveError> {
+    prove_checked_with_hasher::<Sha256Hasher>(commitment, evaluations, evaluation_point, params)
+}
+
+pub fn prove_checked_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Result<Proof, ProveError> {
+    check_committed_len(commitment, evaluations)?;
+    let expected = log2_ceil_usize(evaluations.len() * 8);
+    if evaluation_point.len() != expected {
+        return Err(ProveError::PointLength {
+            expected,
+            actual: evaluation_point.len(),
+        });
+    }
+    Ok(prove_with_hasher::<H>(
+        commitment,
+        evaluations,
+        evaluation_point,
+        params,
+    ))
+}
+
+// the evaluations given to prove must be as many bytes as the ones the commitment was built from
+fn check_committed_len(commitment: &Commitment, evaluations: &[u8]) -> Result<(), ProveError> {
+    if evaluations.len() != commitment.original_len {
+        return Err(ProveError::CommittedSize {
+            committed: commitment.original_len,
+            evaluations: evaluations.len(),
+        });
+    }
+    Ok(())
+}
+
+pub fn prove_elements(
+    commitment: &Commitment,
+    elements: &[BinaryFieldElement16],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Proof {
+    prove_elements_with_hasher::<Sha256Hasher>(commitment, elements, evaluation_point, params)
+}
+
+// the proof opens the committed rows, the elements only fix the number of variables of the point
+pub fn prove_elements_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    elements: &[BinaryFieldElement16],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Proof {
+    assert_eq!(
+        evaluation_point.len(),
+        log2_strict_usize(elements.len() * params.packing_factor)
+    );
+    open::<H>(
+        &ProverInputs::from(commitment),
+        evaluation_point,
+        params,
+        &FiatShamirChallenger::<H>::new(),
+        true,
+    )
+}
+
+pub fn prove_view(
+    view: &ProverView,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Proof {
+    prove_view_with_hasher::<Sha256Hasher>(view, evaluations, evaluation_point, params)
+}
+
+/** prove_with_hasher from a ProverView, the proof is the same as from the full Commitment */
+pub fn prove_view_with_hasher<H: MerkleHasher>(
+    view: &ProverView,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Proof {
+    // the rows are not kept by the view, pack them again at the committed size,
+    // zero-padding the evaluations of a commit_padded commitment
+    let row_count = view.columns[0].len();
+    let row_length = view.columns.len() * params.packing_factor / params.expansion_factor;
+    let rows = pack_rows(
+        &zero_pad(evaluations, row_count * row_length / 8),
+        row_count,
+        row_length,
+        params.packing_factor,
+    );
+    open::<H>(
+        &ProverInputs {
+            root: &view.root,
+            merkle_tree: &view.merkle_tree,
+            columns: &view.columns,
+            rows: &rows,
+        },
+        evaluation_point,
+        params,
+        &FiatShamirChallenger::<H>::new(),
+        true,
+    )
+}
+
+/** Wall-clock time of each stage of prove_profiled
+
+the stages run one after the other on the calling thread (extend_rows, not extend_rows_parallel),
+    so the stages add up to nearly the total, only the layout and the point padding between them are left out,
+    the numbers are those of a sequential prover, commit and prove overlap some of these stages
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProveProfile {
+    // pack_rows
+    pub pack: Duration,
+    // extend_rows and the transpose into columns
+    pub extend: Duration,
+    // packing the columns and merkelize
+    pub merkle: Duration,
+    // the row combination of the evaluation point
+    pub t_prime: Duration,
+    // the column combination of t_prime
+    pub eval: Duration,
+    // the proof of work, grinding_bits of the params
+    pub grind: Duration,
+    // the challenges and gathering the queried columns with their branches
+    pub branches: Duration,
+    // from the call to the returned proof
+    pub total: Duration,
+}
+
+impl ProveProfile {
+    /** the sum of the stages, without total */
+    pub fn stages(&self) -> Duration {
+        self.pack
+            + self.extend
+            + self.merkle
+            + self.t_prime
+            + self.eval
+            + self.grind
+            + self.branches
+    }
+}
+
+// run f and store its wall-clock time in stage
+fn timed<T>(stage: &mut Duration, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    *stage = start.elapsed();
+    result
+}
+
+pub fn prove_profiled(
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> (Commitment, Proof, ProveProfile) {
+    prove_profiled_with_hasher::<Sha256Hasher>(evaluations, evaluation_point, params)
+}
+
+/** Commit and prove on one thread, timing each stage
+
+Args:
+    evaluations: the evaluations, a power-of-two number of bits
+    evaluation_point: the evaluation point
+    params: the PcsParams
+
+Returns:
+    the commitment and the proof commit and prove return, and the ProveProfile of the run
+*/
+pub fn prove_profiled_with_hasher<H: MerkleHasher>(
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> (Commitment, Proof, ProveProfile) {
+    let start = Instant::now();
+    let mut profile = ProveProfile::default();
+    let log_evaluation_count = log2_strict_usize(evaluations.len() * 8);
+    let (log_row_length, _, row_length, row_count) =
+        choose_row_length_and_count(log_evaluation_count);
+
+    let rows = timed(&mut profile.pack, || {
+        pack_rows(evaluations, row_count, row_length, params.packing_factor)
+    });
+    let columns = timed(&mut profile.extend, || {
+        transpose(&extend_rows(&rows, params.expansion_factor))
+    });
+    let commitment = timed(&mut profile.merkle, || {
+        commit_encoded::<H>(rows, columns, evaluations.len())
+    });
+
+    let evaluation_point = &pad_evaluation_point(evaluation_point, log_evaluation_count);
+    let t_prime = timed(&mut profile.t_prime, || {
+        compute_t_prime(&commitment.rows, evaluation_point, log_row_length)
+    });
+    let computed_eval = timed(&mut profile.eval, || {
+        evaluate_t_prime(&t_prime, evaluation_point, log_row_length)
+    });
+    let grinding_nonce = timed(&mut profile.grind, || {
+        grind_nonce(
+            &query_transcript::<H>(
+                &commitment.root,
+                evaluation_point,
+                &computed_eval,
+                &t_prime,
+                None,
+            ),
+            params,
+        )
+    });
+    let proof = timed(&mut profile.branches, || {
+        open_queried(
+            &ProverInputs::from(&commitment),
+            evaluation_point,
+            Opening {
+                t_prime,
+                eval: computed_eval,
+                grinding_nonce,
+            },
+            0,
+            params,
+            &FiatShamirChallenger::<H>::new(),
+            false,
+        )
+    });
+    profile.total = start.elapsed();
+    (commitment, proof, profile)
+}
+
+/** The prover shared by Commitment and ProverView
+
+t_prime and the eval come first, the grinding runs on the transcript of the statement and t_prime,
+    then the queried columns and branches are gathered per challenge.
+    With parallel, the gathering runs on rayon workers, the proof is the same either way
+
+Args:
+    challenger: draws the queried columns, FiatShamirChallenger::<H> outside of tests
+    parallel: false runs every step on the calling thread
+*/
+fn open<H: MerkleHasher>(
+    inputs: &ProverInputs,
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+    challenger: &dyn Challenger,
+    parallel: bool,
+) -> Proof {
+    // the committed size, padding included, comes from the rows rather than from the evaluations
+    let rows = inputs.rows;
+    let log_evaluation_count =
+        log2_strict_usize(rows.len() * rows[0].len() * params.packing_factor);
+    let (log_row_length, _, _, _) = choose_row_length_and_count(log_evaluation_count);
+    let evaluation_point = &pad_evaluation_point(evaluation_point, log_evaluation_count);
+
+    // Compute t_prime: linear combination of rows before extension
+    let t_prime = compute_t_prime(rows, evaluation_point, log_row_length);
+    // Compute evaluation
+    let computed_eval = evaluate_t_prime(&t_prime, evaluation_point, log_row_length);
+    // Grind on the statement, the challenges are drawn after the nonce
+    let grinding_nonce = grind_nonce(
+        &query_transcript::<H>(
+            inputs.root,
+            evaluation_point,
+            &computed_eval,
+            &t_prime,
+            None,
+        ),
+        params,
+    );
+    open_queried(
+        inputs,
+        evaluation_point,
+        Opening {
+            t_prime,
+            eval: computed_eval,
+            grinding_nonce,
+        },
+        0,
+        params,
+        challenger,
+        parallel,
+    )
+}
+
+// draw the challenges after t_prime, the eval and the nonce, then gather the queried columns and branches,
+// the first skipped columns are never queried
+fn open_queried(
+    inputs: &ProverInputs,
+    evaluation_point: &Vec<u128>,
+    opening: Opening,
+    skipped: usize,
+    params: &PcsParams,
+    challenger: &dyn Challenger,
+    parallel: bool,
+) -> Proof {
+    let Opening {
+        t_prime,
+        eval,
+        grinding_nonce,
+    } = opening;
+    let (merkle_tree, columns) = (inputs.merkle_tree, inputs.columns);
+    // Get challenges from the whole statement
+    let statement = QueryStatement {
+        root: inputs.root,
+        evaluation_point,
+        eval: &eval,
+        t_prime: &t_prime,
+        grinding_nonce,
+    };
+    let challenges = challenger
+        .challenges(&statement, columns.len() - skipped, params.num_challenges)
+        .unwrap_or_else(|e| panic!("failed to draw the challenges: {}", e))
+        .iter()
+        .map(|&c| c + skipped as u16)
+        .collect::<Vec<u16>>();
+
+    // gather each distinct column once, in increasing index order, then put them back in draw order
+    let index_set = challenge_index_set(&challenges);
+    let gather = |&c: &u16| {
+        (
+            columns[c as usize].clone(),
+            get_branch(merkle_tree, c.into()),
+        )
+    };
+    let gathered: Vec<_> = if parallel {
+        index_set.par_iter().map(gather).collect()
+    } else {
+        index_set.iter().map(gather).collect()
+    };
+    let (opened_columns, branches) = challenges
+        .iter()
+        .map(|c| gathered[index_set.binary_search(c).unwrap()].clone())
+        .unzip();
+
+    Proof {
+        evaluation_point: evaluation_point.clone(),
+        eval,
+        t_prime,
+        columns: opened_columns,
+        branches,
+        grinding_nonce,
+    }
+}
+
+/** Why the verifier rejected a proof */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    // the proof does not open num_challenges columns
+    MalformedProof,
+    // the grinding nonce is missing or does not meet grinding_bits
+    GrindingMismatch,
+    // the proof is for another evaluation point than the verified one, padding included
+    PointMismatch,
+    // the challenges cannot be drawn with these params
+    Challenges(ChallengeError),
+    // the Merkle branch of the index-th queried column does not lead to the root
+    BranchMismatch {
+        index: usize,
+    },
+    // the Merkle branch of the index-th queried column has the wrong shape for the hasher or the tree
+    MalformedBranch {
+        index: usize,
+        error: BranchError,
+    },
+    // the index-th challenge, supplied or drawn from the proof's point, is not a column of the extended rows
+    ChallengeOutOfRange {
+        index: usize,
+        challenge: u16,
+        extended_row_length: usize,
+    },
+    // the index-th opened column is not the committed column, see verify_with_context
+    ColumnMismatch {
+        index: usize,
+    },
+    // the multi-branch of the queried columns does not lead to the root, see verify_compact
+    MultiBranchMismatch,
+    // the packed columns of the commitment do not lead to its root
+    RootMismatch,
+    // the extension of t_prime disagrees with the queried columns
+    TPrimeMismatch,
+    // t_prime does not evaluate to the claimed eval
+    EvalMismatch,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::MalformedProof => {
+                write!(f, "the proof does not open num_challenges columns")
+            }
+            VerifyError::GrindingMismatch => write!(f, "invalid proof-of-work nonce"),
+            VerifyError::PointMismatch => {
+                write!(f, "the proof is for another evaluation point")
+            }
+            VerifyError::Challenges(e) => write!(f, "{}", e),
+            VerifyError::BranchMismatch { index } => {
+                write!(
+                    f,
+                    "Merkle branch of queried column {} does not match the root",
+                    index
+                )
+            }
+            VerifyError::MalformedBranch { index, error } => {
+                write!(
+                    f,
+                    "malformed Merkle branch of queried column {}: {}",
+                    index, error
+                )
+            }
+            VerifyError::ChallengeOutOfRange {
+                index,
+                challenge,
+                extended_row_length,
+            } => write!(
+                f,
+                "challenge {} (the {}-th) is out of the {} columns",
+                challenge, index, extended_row_length
+            ),
+            VerifyError::ColumnMismatch { index } => {
+                write!(f, "queried column {} is not the committed column", index)
+            }
+            VerifyError::MultiBranchMismatch => {
+                write!(
+                    f,
+                    "the multi-branch of the queried columns does not match the root"
+                )
+            }
+            VerifyError::RootMismatch => {
+                write!(f, "the packed columns do not match the root")
+            }
+            VerifyError::TPrimeMismatch => {
+                write!(f, "t_prime is inconsistent with the queried columns")
+            }
+            VerifyError::EvalMismatch => write!(f, "t_prime does not evaluate to the claimed eval"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+pub fn verifier(
+    commitment: &Commitment,
+    proof: &Proof,
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verifier_with_hasher::<Sha256Hasher>(commitment, proof, evaluation_point, params)
+}
+
+pub fn verifier_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    proof: &Proof,
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verify::<H>(
+        &commitment.root,
+        &commitment.packed_columns,
+        proof,
+        evaluation_point,
+        params,
+    )
+}
+
+pub fn verifier_with_challenger(
+    commitment: &Commitment,
+    proof: &Proof,
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+    challenger: &dyn Challenger,
+) -> Result<(), VerifyError> {
+    verifier_with_challenger_with_hasher::<Sha256Hasher>(
+        commitment,
+        proof,
+        evaluation_point,
+        params,
+        challenger,
+    )
+}
+
+/** verifier with the queried columns drawn by challenger, the one the proof was made with
+
+unlike verifier_with_challenges, the grinding nonce is still checked,
+    verifier is this with FiatShamirChallenger::<H>
+*/
+pub fn verifier_with_challenger_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    proof: &Proof,
+    evaluation_point: &[u128],
+    params: &PcsParams,
+    challenger: &dyn Challenger,
+) -> Result<(), VerifyError> {
+    let (challenges, log_row_length) = verifier_challenges::<H>(
+        &commitment.root,
+        proof,
+        evaluation_point,
+        params,
+        challenger,
+        false,
+    )?;
+    check_openings::<H>(
+        &commitment.root,
+        &commitment.packed_columns,
+        proof,
+        &challenges,
+        log_row_length,
+        params,
+    )
+}
+
+pub fn verifier_with_challenges(
+    commitment: &Commitment,
+    proof: &Proof,
+    evaluation_point: &Vec<u128>,
+    challenges: &[u16],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verifier_with_challenges_with_hasher::<Sha256Hasher>(
+        commitment,
+        proof,
+        evaluation_point,
+        challenges,
+        params,
+    )
+}
+
+/** Verify a proof whose queried columns were chosen outside of the PCS
+
+for a verifier inside a larger protocol that derives the column queries from its own transcript:
+    the Fiat-Shamir challenges and the grinding nonce are skipped, the proof must open exactly
+    the supplied columns, in the same order. The soundness of the queries is then up to the caller,
+    who must draw them after the prover is bound to t_prime and the eval.
+    verifier is this check with the challenges the transcript of the root draws
+
+Args:
+    commitment: the commitment, the root and the packed columns are read
+    proof: the proof, its columns and branches are those of the challenges
+    evaluation_point: the evaluation point, compared with the point the proof carries after the zero padding of commit_padded
+    challenges: the indices of the queried columns of the extended rows
+    params: the PCS parameters, num_challenges is ignored
+
+Returns:
+    Ok if the proof is valid for these challenges, the VerifyError otherwise
+*/
+pub fn verifier_with_challenges_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    proof: &Proof,
+    evaluation_point: &[u128],
+    challenges: &[u16],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    padded_point(evaluation_point, &proof.evaluation_point)?;
+    let (log_row_length, _, row_length, _) =
+        try_choose_row_length_and_count(proof.evaluation_point.len())
+            .map_err(|_| VerifyError::MalformedProof)?;
+    let extended_row_length = row_length * params.expansion_factor / params.packing_factor;
+    if challenges.is_empty()
+        || proof.columns.len() != challenges.len()
+        || proof.branches.len() != challenges.len()
+        || proof.t_prime.len() != row_length
+    {
+        return Err(VerifyError::MalformedProof);
+    }
+    if let Some((index, &challenge)) = challenges
+        .iter()
+        .enumerate()
+        .find(|(_, &c)| c as usize >= extended_row_length)
+    {
+        return Err(VerifyError::ChallengeOutOfRange {
+            index,
+            challenge,
+            extended_row_length,
+        });
+    }
+    check_openings::<H>(
+        &commitment.root,
+        &commitment.packed_columns,
+        proof,
+        challenges,
+        log_row_length,
+        params,
+    )
+}
+
+pub fn verifier_pruned(
+    commitment: &PrunedCommitment,
+    proof: &Proof,
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verifier_pruned_with_hasher::<Sha256Hasher>(commitment, proof, evaluation_point, params)
+}
+
+pub fn verifier_pruned_with_hasher<H: MerkleHasher>(
+    commitment: &PrunedCommitment,
+    proof: &Proof,
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verify::<H>(
+        &commitment.root,
+        &commitment.packed_columns,
+        proof,
+        evaluation_point,
+        params,
+    )
+}
+
+/** commit, with each row blinded so the opened columns and t_prime reveal nothing about the evaluations
+
+each packed row is followed by as many random field elements before the Reed-Solomon extension,
+    so the code is the one of rows twice as long. The queries skip the positions where the extension
+    still holds the rows themselves, and any num_challenges of the other positions are uniformly random
+    as long as num_challenges <= row_length / packing_factor.
+    HIDING_MASK_ROWS fully random rows follow the blinded rows, their bits make one random 128-bit
+    mask per position of t_prime, which prove_hiding adds to t_prime so t_prime is uniformly random
+    instead of the row combination of the evaluations.
+    A hiding commitment is proven with prove_hiding and verified with verify_hiding
+
+Args:
+    evaluations: the evaluations, of a power-of-two bit length
+    params: the PcsParams
+
+Returns:
+    a Commitment whose rows are the blinded rows followed by the mask rows
+*/
+#[cfg(feature = "rand")]
+pub fn commit_hiding(evaluations: &[u8], params: &PcsParams) -> Commitment {
+    commit_hiding_with_hasher::<Sha256Hasher>(evaluations, params)
+}
+
+#[cfg(feature = "rand")]
+pub fn commit_hiding_with_hasher<H: MerkleHasher>(
+    evaluations: &[u8],
+    params: &PcsParams,
+) -> Commitment {
+    let log_evaluation_count = log2_strict_usize(evaluations.len() * 8);
+    let (log_row_length, log_row_count, row_length, row_count) =
+        choose_row_length_and_count(log_evaluation_count);
+    assert!(
+        params.num_challenges <= row_length / params.packing_factor,
+        "the blinding hides at most row_length / packing_factor queried columns"
+    );
+
+    let random_elements = |count: usize| -> Vec<BinaryFieldElement16> {
+        (0..count)
+            .map(|_| BinaryFieldElement16::new(rand::random::<u16>()))
+            .collect()
+    };
+    let mut rows: Vec<Vec<BinaryFieldElement16>> =
+        pack_rows(evaluations, row_count, row_length, params.packing_factor)
+            .into_iter()
+            .map(|mut row| {
+                row.extend(random_elements(row.len()));
+                row
+            })
+            .collect();
+    let blinded_row_length = rows[0].len();
+    rows.extend((0..HIDING_MASK_ROWS).map(|_| random_elements(blinded_row_length)));
+    let extended_rows = extend_rows(&rows, params.expansion_factor);
+    let columns = transpose_parallel(&extended_rows);
+    let packed_columns: Vec<Vec<u8>> = columns
+        .iter()
+        .map(|col| col.iter().copied().collect())
+        .collect();
+    let merkle_tree = merkelize::<H>(&packed_columns);
+    let root = get_root(&merkle_tree);
+
+    Commitment {
+        root,
+        packed_columns,
+        merkle_tree,
+        rows,
+        columns,
+        original_len: evaluations.len(),
+    }
+}
+
+/** A proof of a commit_hiding commitment
+
+the t_prime of proof is masked, t_prime + rho * mask, where mask is the combination of the mask rows
+    and rho is drawn after eval and mask_eval, so the verifier checks the masked t_prime against
+    eval + rho * mask_eval
+*/
+#[cfg(feature = "rand")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HidingProof {
+    pub proof: Proof,
+    // the evaluation of the mask, the column combination of the evaluation point applied to it
+    pub mask_eval: Vec<u16>,
+}
+
+#[cfg(feature = "rand")]
+pub fn prove_hiding(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> HidingProof {
+    prove_hiding_with_hasher::<Sha256Hasher>(commitment, evaluations, evaluation_point, params)
+}
+
+#[cfg(feature = "rand")]
+pub fn prove_hiding_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> HidingProof {
+    check_committed_len(commitment, evaluations).unwrap_or_else(|e| panic!("{}", e));
+    // the blinded rows are twice as long as the rows of the evaluations
+    let rows = &commitment.rows;
+    let row_count = rows.len() - HIDING_MASK_ROWS;
+    let log_evaluation_count =
+        log2_strict_usize(row_count * rows[0].len() * params.packing_factor / 2);
+    let (log_row_length, _, row_length, _) = choose_row_length_and_count(log_evaluation_count);
+    let evaluation_point = &pad_evaluation_point(evaluation_point, log_evaluation_count);
+    let row_combination =
+        evaluation_tensor_product_parallel(&evaluation_point[log_row_length..].to_vec());
+
+    // the eval and the mask eval are fixed before rho is drawn
+    let eval = evaluate_t_prime(
+        &combine_rows(&rows[..row_count], &row_combination),
+        evaluation_point,
+        log_row_length,
+    );
+    let mask_eval = evaluate_t_prime(
+        &combine_rows(&rows[row_count..], &mask_basis()),
+        evaluation_point,
+        log_row_length,
+    );
+    let rho = mask_coefficient::<H>(&commitment.root, evaluation_point, &eval, &mask_eval);
+    let t_prime = combine_rows(rows, &masked_combination(row_combination, &rho));
+    let grinding_nonce = grind_nonce(
+        &query_transcript::<H>(&commitment.root, evaluation_point, &eval, &t_prime, None),
+        params,
+    );
+
+    // a hiding commitment is never queried where the extension still holds the rows themselves
+    let proof = open_queried(
+        &ProverInputs::from(commitment),
+        evaluation_point,
+        Opening {
+            t_prime,
+            eval,
+            grinding_nonce,
+        },
+        row_length / params.packing_factor,
+        params,
+        &FiatShamirChallenger::<H>::new(),
+        true,
+    );
+    HidingProof { proof, mask_eval }
+}
+
+#[cfg(feature = "rand")]
+pub fn verify_hiding(
+    commitment: &Commitment,
+    proof: &HidingProof,
+    evaluation_point: &[u128],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verify_hiding_with_hasher::<Sha256Hasher>(commitment, proof, evaluation_point, params)
+}
+
+/** Verify a proof of a commit_hiding commitment
+
+the branches are those of verifier, the masked t_prime is checked against the columns with the row
+    combination extended by rho times the bits of the mask, and its evaluation against eval + rho * mask_eval
+*/
+#[cfg(feature = "rand")]
+pub fn verify_hiding_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    proof: &HidingProof,
+    evaluation_point: &[u128],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    let HidingProof { proof, mask_eval } = proof;
+    let root = &commitment.root;
+    let (challenges, log_row_length) = verifier_challenges::<H>(
+        root,
+        proof,
+        evaluation_point,
+        params,
+        &FiatShamirChallenger::<H>::new(),
+        true,
+    )?;
+    check_merkle_openings::<H>(
+        root,
+        commitment.packed_columns.len(),
+        &proof.columns,
+        &proof.branches,
+        &challenges,
+    )?;
+
+    let row_combination =
+        evaluation_tensor_product_parallel(&proof.evaluation_point[log_row_length..].to_vec());
+    if proof.eval.len() != BIGBIN_LIMBS
+        || mask_eval.len() != BIGBIN_LIMBS
+        || proof
+            .columns
+            .iter()
+            .any(|col| col.len() != row_combination.len() + HIDING_MASK_ROWS)
+    {
+        return Err(VerifyError::MalformedProof);
+    }
+    let rho = mask_coefficient::<H>(root, &proof.evaluation_point, &proof.eval, mask_eval);
+    check_t_prime_combination(
+        &proof.t_prime,
+        &proof.columns,
+        &challenges,
+        &masked_combination(row_combination, &rho),
+        params,
+    )?;
+
+    let masked_eval: Vec<u16> = proof
+        .eval
+        .iter()
+        .zip(big_mul(&rho, mask_eval))
+        .map(|(a, b)| a ^ b)
+        .collect();
+    if evaluate_t_prime(&proof.t_prime, &proof.evaluation_point, log_row_length) != masked_eval {
+        return Err(VerifyError::EvalMismatch);
+    }
+    Ok(())
+}
+
+// the combination of the mask rows into the mask, row b is bit b of the 128-bit mask
+#[cfg(feature = "rand")]
+fn mask_basis() -> Vec<Vec<u16>> {
+    (0..HIDING_MASK_ROWS)
+        .map(|b| int_to_bigbin(1 << b))
+        .collect()
+}
+
+// the row combination of the blinded rows followed by rho times the mask basis
+#[cfg(feature = "rand")]
+fn masked_combination(mut row_combination: Vec<Vec<u16>>, rho: &Vec<u16>) -> Vec<Vec<u16>> {
+    row_combination.extend(mask_basis().iter().map(|bit| big_mul(rho, bit)));
+    row_combination
+}
+
+// rho, a field element drawn from the statement with eval and mask_eval,
+// squeezed as BIGBIN_LIMBS uniform 16-bit limbs
+#[cfg(feature = "rand")]
+fn mask_coefficient<H: MerkleHasher>(
+    root: &[u8],
+    evaluation_point: &[u128],
+    eval: &Vec<u16>,
+    mask_eval: &Vec<u16>,
+) -> Vec<u16> {
+    let mut transcript: Transcript<H> = Transcript::new();
+    transcript.absorb(MASK_LABEL);
+    transcript.absorb(root);
+    transcript.absorb(
+        &evaluation_point
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect::<Vec<u8>>(),
+    );
+    for limbs in [eval, mask_eval] {
+        transcript.absorb(
+            &limbs
+                .iter()
+                .flat_map(|x| x.to_le_bytes())
+                .collect::<Vec<u8>>(),
+        );
+    }
+    (0..BIGBIN_LIMBS)
+        .map(|_| transcript.challenge_index(1 << 16))
+        .collect()
+}
+
+// the verifier only reads the root and the packed columns, so Commitment and PrunedCommitment share it
+fn verify<H: MerkleHasher>(
+    root: &Vec<u8>,
+    columns: &Vec<Vec<u8>>,
+    proof: &Proof,
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    let (challenges, log_row_length) = verifier_challenges::<H>(
+        root,
+        proof,
+        evaluation_point,
+        params,
+        &FiatShamirChallenger::<H>::new(),
+        false,
+    )?;
+    check_openings::<H>(root, columns, proof, &challenges, log_row_length, params)
+}
+
+// the checks once the challenges are known, the Merkle openings first since they are cheaper
+fn check_openings<H: MerkleHasher>(
+    root: &Vec<u8>,
+    columns: &Vec<Vec<u8>>,
+    proof: &Proof,
+    challenges: &[u16],
+    log_row_length: usize,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    check_merkle_openings::<H>(
+        root,
+        columns.len(),
+        &proof.columns,
+        &proof.branches,
+        challenges,
+    )?;
+    check_algebraic(proof, challenges, log_row_length, params)
+}
+
+/** Verify only that the columns the proof queries are authenticated against the root
+
+the cheap half of verifier: the grinding nonce, the challenges and the Merkle branches of the opened columns
+    of the proof, without re-extending t_prime, so tampered columns are rejected before the expensive checks
+
+Args:
+    commitment: the commitment, the root and the packed columns are read
+    proof: the proof
+    params: the PCS parameters
+
+Returns:
+    Ok if every queried column is authenticated, the VerifyError otherwise
+*/
+pub fn verify_merkle_openings(
+    commitment: &Commitment,
+    proof: &Proof,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verify_merkle_openings_with_hasher::<Sha256Hasher>(commitment, proof, params)
+}
+
+pub fn verify_merkle_openings_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    proof: &Proof,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    // the point is bound by verify_algebraic, the branches are the same for any point
+    let (challenges, _) = verifier_challenges::<H>(
+        &commitment.root,
+        proof,
+        &proof.evaluation_point,
+        params,
+        &FiatShamirChallenger::<H>::new(),
+        false,
+    )?;
+    check_merkle_openings::<H>(
+        &commitment.root,
+        commitment.packed_columns.len(),
+        &proof.columns,
+        &proof.branches,
+        &challenges,
+    )
+}
+
+/** Verify only the algebra of the proof: t_prime against the opened columns and the claimed eval
+
+the other half of verifier, the Merkle branches are not checked,
+    so a proof only passes verifier if it passes both verify_merkle_openings and verify_algebraic
+
+Args:
+    commitment: the commitment, only the root is read, to draw the challenges
+    proof: the proof
+    evaluation_point: the evaluation point, compared with the point the proof carries after the zero padding of commit_padded
+    params: the PCS parameters
+
+Returns:
+    Ok if t_prime is consistent with the columns and evaluates to the eval, the VerifyError otherwise
+*/
+pub fn verify_algebraic(
+    commitment: &Commitment,
+    proof: &Proof,
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verify_algebraic_with_hasher::<Sha256Hasher>(commitment, proof, evaluation_point, params)
+}
+
+pub fn verify_algebraic_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    proof: &Proof,
+    evaluation_point: &[u128],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    let (challenges, log_row_length) = verifier_challenges::<H>(
+        &commitment.root,
+        proof,
+        evaluation_point,
+        params,
+        &FiatShamirChallenger::<H>::new(),
+        false,
+    )?;
+    check_algebraic(proof, &challenges, log_row_length, params)
+}
+
+/** Check only that t_prime evaluates to the claimed eval at point, for proofs from a trusted prover
+
+WARNING: this is NOT a verifier. Nothing authenticates the proof: the Merkle branches, the opened
+    columns and t_prime against them are not checked, and no challenge is drawn, so any t_prime
+    with a matching eval passes. Never use it on a proof that did not come from a trusted prover,
+    use verifier for those. It skips the t_prime extension and the branch hashing, leaving
+    the column combination and one big_mul per row of t_prime
+
+Args:
+    proof: the proof, known to be well-formed, of a non-hiding commitment
+    point: the point t_prime was computed at, proof.evaluation_point for a proof of commit_padded
+    _params: the PCS parameters, the row length only depends on the point
+
+Returns:
+    Ok if t_prime evaluates to proof.eval, EvalMismatch if not, MalformedProof if t_prime does not fit the point
+*/
+pub fn verify_eval_only(
+    proof: &Proof,
+    point: &[u128],
+    _params: &PcsParams,
+) -> Result<(), VerifyError> {
+    let (log_row_length, _, row_length, _) =
+        try_choose_row_length_and_count(point.len()).map_err(|_| VerifyError::MalformedProof)?;
+    if proof.t_prime.len() != row_length {
+        return Err(VerifyError::MalformedProof);
+    }
+    if evaluate_t_prime(&proof.t_prime, &point.to_vec(), log_row_length) != proof.eval {
+        return Err(VerifyError::EvalMismatch);
+    }
+    Ok(())
+}
+
+/** Verify a proof with only the root of the commitment
+
+the leaf of a queried column is its packed bytes, so the opened columns of the proof
+    are authenticated against the root alone, the leaf count comes from the point instead of
+    the packed columns of a Commitment, and the t_prime checks then run on authenticated columns
+
+Args:
+    root: the Merkle root of the commitment
+    proof: the proof
+    evaluation_point: the evaluation point, compared with the point the proof carries after the zero padding of commit_padded
+    params: the PCS parameters
+
+Returns:
+    Ok if the proof is valid for the root, the VerifyError otherwise
+*/
+pub fn verify_standalone(
+    root: &[u8],
+    proof: &Proof,
+    evaluation_point: &[u128],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verify_standalone_with_hasher::<Sha256Hasher>(root, proof, evaluation_point, params)
+}
+
+pub fn verify_standalone_with_hasher<H: MerkleHasher>(
+    root: &[u8],
+    proof: &Proof,
+    evaluation_point: &[u128],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    let root = root.to_vec();
+    let (challenges, log_row_length) = verifier_challenges::<H>(
+        &root,
+        proof,
+        evaluation_point,
+        params,
+        &FiatShamirChallenger::<H>::new(),
+        false,
+    )?;
+    check_merkle_openings::<H>(
+        &root,
+        (1 << log_row_length) * params.expansion_factor / params.packing_factor,
+        &proof.columns,
+        &proof.branches,
+        &challenges,
+    )?;
+    check_algebraic(proof, &challenges, log_row_length, params)
+}
+
+/** The proof-independent work of verifier, done once per commitment
+
+for a verifier checking many proofs of one commitment: the params are checked, the lazily built NTT tables
+    are loaded (warmup_caches) and the packed columns are authenticated against the root once, by rebuilding
+    the Merkle tree. A proof is then checked by comparing its opened columns with the authenticated ones,
+    without hashing its branches, the challenges and the t_prime checks are those of verifier
+*/
+pub struct VerifierContext<'a, H: MerkleHasher = Sha256Hasher> {
+    pub root: &'a Vec<u8>,
+    // authenticated against the root by new
+    pub packed_columns: &'a Vec<Vec<u8>>,
+    pub params: PcsParams,
+    // the log2 of the committed bits, the length of the evaluation point of every proof
+    pub log_evaluation_count: usize,
+    _hasher: PhantomData<H>,
+}
+
+impl<'a, H: MerkleHasher> VerifierContext<'a, H> {
+    /** Set up the verification of the proofs of commitment
+
+    Args:
+        commitment: the commitment, the root and the packed columns are read
+        params: the PCS parameters of every proof
+
+    Returns:
+        the context, or InvalidLength / NotPowerOfTwo / TooManyChallenges for params that cannot verify
+        this commitment, Verify(RootMismatch) when the packed columns are not the ones of the root
+    */
+    pub fn new(commitment: &'a Commitment, params: &PcsParams) -> Result<Self, BiniusError> {
+        let packed_columns = &commitment.packed_columns;
+        if packed_columns.is_empty() || !packed_columns.len().is_power_of_two() {
+            return Err(BiniusError::NotPowerOfTwo(packed_columns.len()));
+        }
+        // a packed column holds row_count elements of 2 bytes, there are packed_row_length * expansion_factor columns
+        let evaluation_count = packed_columns[0].len() / 2 * packed_columns.len()
+            / params.expansion_factor
+            * params.packing_factor;
+        if !evaluation_count.is_power_of_two() {
+            return Err(BiniusError::NotPowerOfTwo(evaluation_count));
+        }
+        check_params(evaluation_count, params)?;
+        warmup_caches();
+        if get_root(&merkelize::<H>(packed_columns)) != commitment.root {
+            return Err(BiniusError::Verify(VerifyError::RootMismatch));
+        }
+        Ok(VerifierContext {
+            root: &commitment.root,
+            packed_columns,
+            params: *params,
+            log_evaluation_count: log2_strict_usize(evaluation_count),
+            _hasher: PhantomData,
+        })
+    }
+}
+
+/** Verify a proof of the commitment of context
+
+Args:
+    context: the VerifierContext of the commitment
+    proof: the proof, its branches are not read
+    evaluation_point: the evaluation point, compared with the point the proof carries after the zero padding of commit_padded
+
+Returns:
+    Ok if the proof is valid, the VerifyError of verifier otherwise, ColumnMismatch instead of
+    BranchMismatch for an opened column that is not the committed one
+*/
+pub fn verify_with_context<H: MerkleHasher>(
+    context: &VerifierContext<H>,
+    proof: &Proof,
+    evaluation_point: &[u128],
+) -> Result<(), VerifyError> {
+    if proof.evaluation_point.len() != context.log_evaluation_count {
+        return Err(VerifyError::MalformedProof);
+    }
+    let (challenges, log_row_length) = verifier_challenges::<H>(
+        context.root,
+        proof,
+        evaluation_point,
+        &context.params,
+        &FiatShamirChallenger::<H>::new(),
+        false,
+    )?;
+    for (i, &challenge) in challenges.iter().enumerate() {
+        let packed_column: Vec<u8> = proof.columns[i].iter().copied().collect();
+        if packed_column != context.packed_columns[challenge as usize] {
+            return Err(VerifyError::ColumnMismatch { index: i });
+        }
+    }
+    check_algebraic(proof, &challenges, log_row_length, &context.params)
+}
+
+// check the point, the shape of the proof and the proof of work, then draw the challenges,
+// returns the challenges and log_row_length
+fn verifier_challenges<H: MerkleHasher>(
+    root: &Vec<u8>,
+    proof: &Proof,
+    evaluation_point: &[u128],
+    params: &PcsParams,
+    challenger: &dyn Challenger,
+    hiding: bool,
+) -> Result<(Vec<u16>, usize), VerifyError> {
+    // the transcript absorbs the verifier's point, which the proof must carry
+    let evaluation_point = &padded_point(evaluation_point, &proof.evaluation_point)?;
+    let value = &proof.eval;
+    let t_prime = &proof.t_prime;
+
+    // Compute the row length and row count of the grid. Should output same numbers as what prover gave
+    let (log_row_length, log_row_count, row_length, row_count) =
+        try_choose_row_length_and_count(evaluation_point.len())
+            .map_err(|_| VerifyError::MalformedProof)?;
+    // a hiding commitment has rows twice as long, the blinding half included
+    let message_length = if hiding { 2 * row_length } else { row_length };
+    let extended_row_length = message_length * params.expansion_factor / params.packing_factor;
+    let skipped = if hiding {
+        row_length / params.packing_factor
+    } else {
+        0
+    };
+
+    // the branches are counted where they are read, a CompactProof has none
+    if proof.columns.len() != params.num_challenges || t_prime.len() != message_length {
+        return Err(VerifyError::MalformedProof);
+    }
+
+    // Check the proof of work, then compute challenges. Should output the same as what prover computed
+    check_grind_nonce(
+        &query_transcript::<H>(root, evaluation_point, value, t_prime, None),
+        proof.grinding_nonce,
+        params,
+    )?;
+    let statement = QueryStatement {
+        root,
+        evaluation_point,
+        eval: value,
+        t_prime,
+        grinding_nonce: proof.grinding_nonce,
+    };
+    let challenges = challenger
+        .challenges(
+            &statement,
+            extended_row_length - skipped,
+            params.num_challenges,
+        )
+        .map_err(VerifyError::Challenges)?
+        .iter()
+        .map(|&c| c + skipped as u16)
+        .collect::<Vec<u16>>();
+    Ok((challenges, log_row_length))
+}
+
+// the verifier's point padded with 0 coordinates like open pads the prover's,
+// PointMismatch unless it is the point the proof was made at
+fn padded_point(
+    evaluation_point: &[u128],
+    proof_point: &Vec<u128>,
+) -> Result<Vec<u128>, VerifyError> {
+    if evaluation_point.len() > proof_point.len() {
+        return Err(VerifyError::PointMismatch);
+    }
+    let padded = pad_evaluation_point(&evaluation_point.to_vec(), proof_point.len());
+    if padded != *proof_point {
+        return Err(VerifyError::PointMismatch);
+    }
+    Ok(padded)
+}
+
+// Verify Merkle branches, the leaf of each challenge is the opened column of the proof,
+// leaf_count is the number of columns of the committed tree
+fn check_merkle_openings<H: MerkleHasher>(
+    root: &[u8],
+    leaf_count: usize,
+    columns: &[Vec<BinaryFieldElement16>],
+    branches: &[Vec<Vec<u8>>],
+    challenges: &[u16],
+) -> Result<(), VerifyError> {
+    if columns.len() != challenges.len() || branches.len() != challenges.len() {
+        return Err(VerifyError::MalformedProof);
+    }
+    // the layout of the challenges comes from the proof's point, the leaf count from the commitment
+    if let Some((index, &challenge)) = challenges
+        .iter()
+        .enumerate()
+        .find(|(_, &c)| c as usize >= leaf_count)
+    {
+        return Err(VerifyError::ChallengeOutOfRange {
+            index,
+            challenge,
+            extended_row_length: leaf_count,
+        });
+    }
+    for (i, &challenge) in challenges.iter().enumerate() {
+        let packed_column: Vec<u8> = columns[i].iter().copied().collect();
+        check_branch::<H>(
+            root,
+            i,
+            challenge as usize,
+            &packed_column,
+            &branches[i],
+            leaf_count,
+        )?;
+    }
+    Ok(())
+}
+
+// one Merkle opening, a malformed branch is reported as such instead of as a mismatch
+fn check_branch<H: MerkleHasher>(
+    root: &[u8],
+    index: usize,
+    pos: usize,
+    packed_column: &[u8],
+    branch: &[Vec<u8>],
+    leaf_count: usize,
+) -> Result<(), VerifyError> {
+    match verify_branch_checked::<H>(root, pos, packed_column, branch, leaf_count) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(VerifyError::BranchMismatch { index }),
+        Err(error) => Err(VerifyError::MalformedBranch { index, error }),
+    }
+}
+
+// t_prime against the opened columns, then the evaluation of t_prime against the claimed eval
+fn check_algebraic(
+    proof: &Proof,
+    challenges: &[u16],
+    log_row_length: usize,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    check_t_prime(
+        &proof.t_prime,
+        &proof.columns,
+        challenges,
+        &proof.evaluation_point,
+        log_row_length,
+        params,
+    )?;
+
+    // Compute the evaluation
+    if evaluate_t_prime(&proof.t_prime, &proof.evaluation_point, log_row_length) != proof.eval {
+        return Err(VerifyError::EvalMismatch);
+    }
+    Ok(())
+}
+
+/** A commitment to several polynomials of the same size under one Merkle tree
+
+leaf j is the j-th extended column of every polynomial, concatenated in polynomial order,
+    so the polynomials share the tree and the column queries
+*/
+#[derive(Serialize, Deserialize)]
+pub struct BatchCommitment {
+    pub root: Vec<u8>,
+    pub packed_columns: Vec<Vec<u8>>,
+    // prove only
+    pub merkle_tree: Vec<Vec<u8>>,
+    // prove only: the rows before extension, per polynomial
+    pub rows: Vec<Vec<Vec<BinaryFieldElement16>>>,
+    // prove only: the interleaved columns, the leaves before packing
+    pub columns: Vec<Vec<BinaryFieldElement16>>,
+}
+
+/** The opening of every polynomial of a BatchCommitment at one evaluation point
+
+t_prime is the concatenation of the t_primes of the polynomials, row_length rows each,
+    and each column is the concatenation of the polynomials' columns, row_count elements each
+*/
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchProof {
+    pub evaluation_point: Vec<u128>,
+    pub evals: Vec<Vec<u16>>,
+    pub t_prime: Vec<Vec<u16>>,
+    pub columns: Vec<Vec<BinaryFieldElement16>>,
+    pub branches: Vec<Vec<Vec<u8>>>,
+    pub grinding_nonce: Option<u64>,
+}
+
+pub fn commit_batch(polys: &[&[u8]], params: &PcsParams) -> BatchCommitment {
+    commit_batch_with_hasher::<Sha256Hasher>(polys, params)
+}
+
+/** commit to several polynomials under one root
+
+Args:
+    polys: the evaluations of each polynomial, all of the same power-of-two bit length
+    params: the PcsParams shared by all the polynomials
+
+Returns:
+    BatchCommitment: one Merkle tree over the interleaved columns
+*/
+pub fn commit_batch_with_hasher<H: MerkleHasher>(
+    polys: &[&[u8]],
+    params: &PcsParams,
+) -> BatchCommitment {
+    assert!(
+        !polys.is_empty(),
+        "commit_batch needs at least one polynomial"
+    );
+    assert!(
+        polys.iter().all(|poly| poly.len() == polys[0].len()),
+        "batched polynomials must have the same number of evaluations"
+    );
+
+    let (rows, poly_columns): (Vec<_>, Vec<_>) =
+        polys.iter().map(|poly| encode_rows(poly, params)).unzip();
+    // interleave: column j is the j-th column of every polynomial
+    let columns: Vec<Vec<BinaryFieldElement16>> = (0..poly_columns[0].len())
+        .map(|j| {
+            poly_columns
+                .iter()
+                .flat_map(|cols| cols[j].iter().copied())
+                .collect()
+        })
+        .collect();
+    let packed_columns: Vec<Vec<u8>> = columns
+        .iter()
+        .map(|col| col.iter().copied().collect())
+        .collect();
+    let merkle_tree = merkelize::<H>(&packed_columns);
+    let root = get_root(&merkle_tree);
+
+    BatchCommitment {
+        root,
+        packed_columns,
+        merkle_tree,
+        rows,
+        columns,
+    }
+}
+
+pub fn prove_batch(
+    commitment: &BatchCommitment,
+    polys: &[&[u8]],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> BatchProof {
+    prove_batch_with_hasher::<Sha256Hasher>(commitment, polys, evaluation_point, params)
+}
+
+pub fn prove_batch_with_hasher<H: MerkleHasher>(
+    commitment: &BatchCommitment,
+    polys: &[&[u8]],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> BatchProof {
+    assert_eq!(polys.len(), commitment.rows.len());
+    let log_evaluation_count = log2_strict_usize(polys[0].len() * 8);
+    let (log_row_length, log_row_count, row_length, row_count) =
+        choose_row_length_and_count(log_evaluation_count);
+    let extended_row_length = row_length * params.expansion_factor / params.packing_factor;
+
+    // t_prime and evaluation of each polynomial, the t_primes are concatenated
+    let t_primes: Vec<Vec<Vec<u16>>> = commitment
+        .rows
+        .iter()
+        .map(|rows| compute_t_prime(rows, evaluation_point, log_row_length))
+        .collect();
+    let evals: Vec<Vec<u16>> = t_primes
+        .iter()
+        .map(|t_prime| evaluate_t_prime(t_prime, evaluation_point, log_row_length))
+        .collect();
+    let t_prime: Vec<Vec<u16>> = t_primes.into_iter().flatten().collect();
+
+    // one set of challenges for all the polynomials
+    let grinding_nonce = grind_nonce(
+        &query_transcript::<H>(
+            &commitment.root,
+            evaluation_point,
+            &evals.concat(),
+            &t_prime,
+            None,
+        ),
+        params,
+    );
+    let challenges = query_transcript::<H>(
+        &commitment.root,
+        evaluation_point,
+        &evals.concat(),
+        &t_prime,
+        grinding_nonce,
+    )
+    .distinct_challenge_indices(extended_row_length, params.num_challenges)
+    .expect("failed to draw num_challenges distinct challenges");
+
+    BatchProof {
+        evaluation_point: evaluation_point.clone(),
+        evals,
+        t_prime,
+        columns: challenges
+            .iter()
+            .map(|&c| commitment.columns[c as usize].clone())
+            .collect(),
+        branches: challenges
+            .iter()
+            .map(|c| get_branch(&commitment.merkle_tree, (*c).into()))
+            .collect(),
+        grinding_nonce,
+    }
+}
+
+pub fn verify_batch(
+    commitment: &BatchCommitment,
+    proof: &BatchProof,
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verify_batch_with_hasher::<Sha256Hasher>(commitment, proof, evaluation_point, params)
+}
+
+/** Verify the openings of every polynomial of a BatchCommitment
+
+the branches are checked once for the interleaved columns, then t_prime and the eval
+    are checked per polynomial on its slice of t_prime and of the columns
+*/
+pub fn verify_batch_with_hasher<H: MerkleHasher>(
+    commitment: &BatchCommitment,
+    proof: &BatchProof,
+    evaluation_point: &[u128],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    let root = &commitment.root;
+    let evaluation_point = &padded_point(evaluation_point, &proof.evaluation_point)?;
+    let num_polys = proof.evals.len();
+
+    let (log_row_length, log_row_count, row_length, row_count) =
+        try_choose_row_length_and_count(evaluation_point.len())
+            .map_err(|_| VerifyError::MalformedProof)?;
+    let extended_row_length = row_length * params.expansion_factor / params.packing_factor;
+
+    if num_polys == 0
+        || proof.t_prime.len() != num_polys * row_length
+        || proof.columns.len() != params.num_challenges
+        || proof.branches.len() != params.num_challenges
+        || proof
+            .columns
+            .iter()
+            .any(|col| col.len() != num_polys * row_count)
+    {
+        return Err(VerifyError::MalformedProof);
+    }
+
+    check_grind_nonce(
+        &query_transcript::<H>(
+            root,
+            evaluation_point,
+            &proof.evals.concat(),
+            &proof.t_prime,
+            None,
+        ),
+        proof.grinding_nonce,
+        params,
+    )?;
+    let challenges = query_transcript::<H>(
+        root,
+        evaluation_point,
+        &proof.evals.concat(),
+        &proof.t_prime,
+        proof.grinding_nonce,
+    )
+    .distinct_challenge_indices(extended_row_length, params.num_challenges)
+    .map_err(VerifyError::Challenges)?;
+
+    check_merkle_openings::<H>(
+        root,
+        commitment.packed_columns.len(),
+        &proof.columns,
+        &proof.branches,
+        &challenges,
+    )?;
+
+    for (k, eval) in proof.evals.iter().enumerate() {
+        let t_prime = proof.t_prime[k * row_length..(k + 1) * row_length].to_vec();
+        let selected_columns: Vec<Vec<BinaryFieldElement16>> = proof
+            .columns
+            .iter()
+            .map(|col| col[k * row_count..(k + 1) * row_count].to_vec())
+            .collect();
+        check_t_prime(
+            &t_prime,
+            &selected_columns,
+            &challenges,
+            evaluation_point,
+            log_row_length,
+            params,
+        )?;
+        if evaluate_t_prime(&t_prime, evaluation_point, log_row_length) != *eval {
+            return Err(VerifyError::EvalMismatch);
+        }
+    }
+    Ok(())
+}
+
+/** The openings of one committed polynomial at several evaluation points
+
+the challenges are drawn once for all the points, so the columns and branches are shared,
+    evals[i] and t_primes[i] belong to evaluation_points[i]
+*/
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiProof {
+    pub evaluation_points: Vec<Vec<u128>>,
+    pub evals: Vec<Vec<u16>>,
+    pub t_primes: Vec<Vec<Vec<u16>>>,
+    pub columns: Vec<Vec<BinaryFieldElement16>>,
+    pub branches: Vec<Vec<Vec<u8>>>,
+    pub grinding_nonce: Option<u64>,
+}
+
+pub fn prove_multi(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    points: &[Vec<u128>],
+    params: &PcsParams,
+) -> MultiProof {
+    prove_multi_with_hasher::<Sha256Hasher>(commitment, evaluations, points, params)
+}
+
+/** Open the committed polynomial at several points without committing again
+
+Args:
+    commitment: the commitment of evaluations
+    evaluations: the committed evaluations
+    points: the evaluation points, all of log2(evaluations.len() * 8) coordinates
+    params: the PcsParams of the commitment
+
+Returns:
+    MultiProof: a t_prime and an eval per point, one set of opened columns
+*/
+pub fn prove_multi_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    points: &[Vec<u128>],
+    params: &PcsParams,
+) -> MultiProof {
+    let log_evaluation_count = log2_strict_usize(evaluations.len() * 8);
+    let (log_row_length, log_row_count, row_length, row_count) =
+        choose_row_length_and_count(log_evaluation_count);
+    let extended_row_length = row_length * params.expansion_factor / params.packing_factor;
+    assert!(
+        points
+            .iter()
+            .all(|point| point.len() == log_evaluation_count),
+        "every evaluation point needs log2(evaluations.len() * 8) coordinates"
+    );
+
+    let t_primes: Vec<Vec<Vec<u16>>> = points
+        .iter()
+        .map(|point| compute_t_prime(&commitment.rows, point, log_row_length))
+        .collect();
+    let evals: Vec<Vec<u16>> = points
+        .iter()
+        .zip(t_primes.iter())
+        .map(|(point, t_prime)| evaluate_t_prime(t_prime, point, log_row_length))
+        .collect();
+
+    let grinding_nonce = grind_nonce(
+        &multi_query_transcript::<H>(&commitment.root, points, &evals, &t_primes, None),
+        params,
+    );
+    let challenges =
+        multi_query_transcript::<H>(&commitment.root, points, &evals, &t_primes, grinding_nonce)
+            .distinct_challenge_indices(extended_row_length, params.num_challenges)
+            .expect("failed to draw num_challenges distinct challenges");
+
+    MultiProof {
+        evaluation_points: points.to_vec(),
+        evals,
+        t_primes,
+        columns: challenges
+            .iter()
+            .map(|&c| commitment.columns[c as usize].clone())
+            .collect(),
+        branches: challenges
+            .iter()
+            .map(|c| get_branch(&commitment.merkle_tree, (*c).into()))
+            .collect(),
+        grinding_nonce,
+    }
+}
+
+pub fn verify_multi(
+    commitment: &Commitment,
+    proof: &MultiProof,
+    points: &[Vec<u128>],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verify_multi_with_hasher::<Sha256Hasher>(commitment, proof, points, params)
+}
+
+/** Verify the openings of a MultiProof
+
+the branches are checked once, then t_prime and the eval are checked per point
+
+Args:
+    commitment: the commitment, the root and the packed columns are read
+    proof: the MultiProof
+    points: the evaluation points, in order, each compared with the point the proof carries
+        after the zero padding of commit_padded
+    params: the PCS parameters
+
+Returns:
+    Ok if the proof opens the commitment at exactly these points, the VerifyError otherwise
+*/
+pub fn verify_multi_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    proof: &MultiProof,
+    points: &[Vec<u128>],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    let root = &commitment.root;
+    if points.len() != proof.evaluation_points.len() {
+        return Err(VerifyError::PointMismatch);
+    }
+    let points = &points
+        .iter()
+        .zip(&proof.evaluation_points)
+        .map(|(point, proof_point)| padded_point(point, proof_point))
+        .collect::<Result<Vec<Vec<u128>>, VerifyError>>()?;
+    if points.is_empty()
+        || proof.evals.len() != points.len()
+        || proof.t_primes.len() != points.len()
+        || points.iter().any(|point| point.len() != points[0].len())
+        || proof.columns.len() != params.num_challenges
+        || proof.branches.len() != params.num_challenges
+    {
+        return Err(VerifyError::MalformedProof);
+    }
+
+    let (log_row_length, log_row_count, row_length, row_count) =
+        try_choose_row_length_and_count(points[0].len())
+            .map_err(|_| VerifyError::MalformedProof)?;
+    let extended_row_length = row_length * params.expansion_factor / params.packing_factor;
+
+    check_grind_nonce(
+        &multi_query_transcript::<H>(root, points, &proof.evals, &proof.t_primes, None),
+        proof.grinding_nonce,
+        params,
+    )?;
+    let challenges = multi_query_transcript::<H>(
+        root,
+        points,
+        &proof.evals,
+        &proof.t_primes,
+        proof.grinding_nonce,
+    )
+    .distinct_challenge_indices(extended_row_length, params.num_challenges)
+    .map_err(VerifyError::Challenges)?;
+
+    check_merkle_openings::<H>(
+        root,
+        commitment.packed_columns.len(),
+        &proof.columns,
+        &proof.branches,
+        &challenges,
+    )?;
+
+    for ((point, eval), t_prime) in points.iter().zip(&proof.evals).zip(&proof.t_primes) {
+        check_t_prime(
+            t_prime,
+            &proof.columns,
+            &challenges,
+            point,
+            log_row_length,
+            params,
+        )?;
+        if evaluate_t_prime(t_prime, point, log_row_length) != *eval {
+            return Err(VerifyError::EvalMismatch);
+        }
+    }
+    Ok(())
+}
+
+/** Evaluate the multilinear extension of the bits of evaluations at evaluation_point directly
+
+the reference for the eval of a proof, independent of the rows, the extension and the Merkle tree:
+    bit i of the polynomial is bit i % 8 of evaluations[i / 8] (LSB-first, as commit packs it),
+    and its coefficient is entry i of the tensor product of the whole point,
+    so the value is the XOR of the tensor entries of the set bits
+
+Args:
+    evaluations: the evaluations of the polynomial, 8 bits per byte
+    evaluation_point: the point, one coordinate per bit of the bit index
+
+Returns:
+    Vec<u16>: the value of the multilinear extension at the point, in the same form as Proof::eval
+*/
+pub fn evaluate_mle(evaluations: &[u8], evaluation_point: &[u128]) -> Vec<u16> {
+    let log_evaluation_count = log2_strict_usize(evaluations.len() * 8);
+    assert_eq!(
+        evaluation_point.len(),
+        log_evaluation_count,
+        "the point must have one coordinate per bit of the bit index"
+    );
+    let tensor = evaluation_tensor_product_parallel(&evaluation_point.to_vec());
+
+    let mut result = vec![0u16; BIGBIN_LIMBS];
+    for (i, coefficient) in tensor.iter().enumerate() {
+        if (evaluations[i / 8] >> (i % 8)) & 1 == 1 {
+            for (r, c) in result.iter_mut().zip(coefficient.iter()) {
+                *r ^= c;
+            }
+        }
+    }
+    result
+}
+
+/** t_prime: the row combination of the evaluation point applied to the rows before extension
+
+Args:
+    rows: the packed rows of one polynomial
+    evaluation_point: the point the polynomial is opened at
+    log_row_length: the log2 of the row length, the first log_row_length coordinates select the column
+
+Returns:
+    one row of uint16s per bit of the field element
+*/
+fn compute_t_prime(
+    rows: &Vec<Vec<BinaryFieldElement16>>,
+    evaluation_point: &Vec<u128>,
+    log_row_length: usize,
+) -> Vec<Vec<u16>> {
+    let row_combination =
+        evaluation_tensor_product_parallel(&evaluation_point[log_row_length..].to_vec());
+    combine_rows(rows, &row_combination)
+}
+
+// t_prime of any row combination, one field element per row
+fn combine_rows(
+    rows: &[Vec<BinaryFieldElement16>],
+    row_combination: &Vec<Vec<u16>>,
+) -> Vec<Vec<u16>> {
+    assert_eq!(row_combination.len(), rows.len());
+    let rows_as_bits_transpose =
+        transpose_bits(rows.iter().map(|row| uint16s_to_bits(row)).collect());
+    computed_tprimes_parallel(&rows_as_bits_transpose, row_combination)
+}
+
+// the evaluation of the polynomial, the column combination of the evaluation point applied to t_prime
+fn evaluate_t_prime(
+    t_prime: &Vec<Vec<u16>>,
+    evaluation_point: &Vec<u128>,
+    log_row_length: usize,
+) -> Vec<u16> {
+    let col_combination =
+        evaluation_tensor_product_parallel(&evaluation_point[..log_row_length].to_vec());
+    // for each row in t_prime and each row in col_combination, use big_mul to multiply them
+    let multi_result = t_prime
+        .iter()
+        .zip(col_combination.iter())
+        .map(|(t_prime_row, col_combination_row)| big_mul(t_prime_row, col_combination_row))
+        .collect::<Vec<Vec<u16>>>();
+    xor_along_axis_parallel(&multi_result, 0)
+}
+
+/** Check t_prime against the queried columns
+
+the extension of t_prime at the challenges must equal the row combination of the queried columns
+
+Args:
+    t_prime: the t_prime claimed by the prover
+    selected_columns: the columns at the challenges, in challenge order
+    challenges: the queried column indices
+    evaluation_point: the point the polynomial is opened at
+    log_row_length: the log2 of the row length
+    params: the PcsParams of the commitment
+
+Returns:
+    Err(TPrimeMismatch) if they disagree
+*/
+fn check_t_prime(
+    t_prime: &Vec<Vec<u16>>,
+    selected_columns: &Vec<Vec<BinaryFieldElement16>>,
+    challenges: &[u16],
+    evaluation_point: &Vec<u128>,
+    log_row_length: usize,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    let row_combination =
+        evaluation_tensor_product_parallel(&evaluation_point[log_row_length..].to_vec());
+    check_t_prime_combination(
+        t_prime,
+        selected_columns,
+        challenges,
+        &row_combination,
+        params,
+    )
+}
+
+// check_t_prime with the row combination given, one field element per element of a column
+fn check_t_prime_combination(
+    t_prime: &[Vec<u16>],
+    selected_columns: &[Vec<BinaryFieldElement16>],
+    challenges: &[u16],
+    row_combination: &[Vec<u16>],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    // Use the same Reed-Solomon code that the prover used to extend the rows,
+    // but to extend t_prime. We do this separately for each bit of t_prime
+    // each row in t_prime is a list of uint16s, use uint16s_to_bits to convert it to a list of bits
+    let t_prime_bits: Vec<Vec<u8>> = t_prime.iter().map(|row| uint16s_to_bits(row)).collect();
+
+    // transpose the bits
+    let t_prime_bits_transpose = transpose_bits(t_prime_bits);
+    // pack the each row of t_prime_bits_transpose into a list of BinaryFieldElement16s
+    let t_prime_columns: Vec<Vec<BinaryFieldElement16>> = t_prime_bits_transpose
+        .iter()
+        .map(|row| {
+            pack_row(
+                row,
+                t_prime_bits_transpose[0].len() * 8,
+                params.packing_factor,
+            )
+        })
+        .collect();
+    // extend the rows
+    let extended_t_prime_columns = extend_rows(&t_prime_columns, params.expansion_factor);
+
+    // Here, we take advantage of the linearity of the code. A linear combination of the Reed-Solomon extension gives the same result as an extension of the linear combination.
+    // Each column is a vector of row_count uint16's. Convert each uint16 into bits
+    let column_bits: Vec<Vec<Vec<u8>>> = selected_columns
+        .iter()
+        .map(|col| col.iter().map(|uint16| uint16_to_bit(uint16)).collect())
+        .collect();
+    // Take the same linear combination the prover used to compute t_prime, and apply it to the columns of bits.
+    let transposed_column_bits = transpose_3d(&column_bits, (0, 2, 1));
+    let computed_tprimes = multisubset(row_combination, &transposed_column_bits);
+    // Turn the computed tprimes into bits using uint16s_to_bits
+    let computed_tprime_bits: Vec<Vec<Vec<u8>>> = computed_tprimes
+        .iter()
+        .map(|row| row.iter().map(|uint16| uint16s_to_bits(uint16)).collect())
+        .collect();
+
+    // Convert our FFT-extended t_prime rows into bits
+    // step 1: use challenge to select columns, and convert to bits
+    let extended_t_prime_columns_slices: Vec<Vec<Vec<BinaryFieldElement16>>> =
+        extended_t_prime_columns
+            .iter()
+            .map(|row| challenges.iter().map(|&c| vec![row[c as usize]]).collect())
+            .collect();
+    let extended_t_prime_bits: Vec<Vec<Vec<u8>>> = extended_t_prime_columns_slices
+        .iter()
+        .map(|row| row.iter().map(|uint16| uint16s_to_bits(uint16)).collect())
+        .collect();
+    // step 2: transpose the bits
+    let extended_t_prime_bits_transpose = transpose_3d(&extended_t_prime_bits, (1, 2, 0));
+
+    // The bits of the t_prime extension should equal the bits of the row linear combination of the column bits
+    if computed_tprime_bits != extended_t_prime_bits_transpose {
+        return Err(VerifyError::TPrimeMismatch);
+    }
+    Ok(())
+}
+
+// the proof-of-work nonce of the transcript of the statement, None when grinding is disabled
+fn grind_nonce<H: MerkleHasher>(statement: &Transcript<H>, params: &PcsParams) -> Option<u64> {
+    if params.grinding_bits > 0 {
+        Some(statement.grind(params.grinding_bits))
+    } else {
+        None
+    }
+}
+
+// the verifier's side of grind_nonce
+fn check_grind_nonce<H: MerkleHasher>(
+    statement: &Transcript<H>,
+    grinding_nonce: Option<u64>,
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    if params.grinding_bits > 0 {
+        match grinding_nonce {
+            Some(nonce) if statement.check_grind(nonce, params.grinding_bits) => {}
+            _ => return Err(VerifyError::GrindingMismatch),
+        }
+    }
+    Ok(())
+}
+
+/** What the column queries of a proof are drawn from, the statement and the prover's messages */
+pub struct QueryStatement<'a> {
+    pub root: &'a [u8],
+    pub evaluation_point: &'a Vec<u128>,
+    pub eval: &'a Vec<u16>,
+    pub t_prime: &'a Vec<Vec<u16>>,
+    pub grinding_nonce: Option<u64>,
+}
+
+/** Draws the columns a proof opens, the prover and the verifier must use the same one
+
+FiatShamirChallenger is the challenger of prove and verifier, FixedChallenger queries chosen columns,
+    to test edge cases (the first and the last column) without searching for a root that draws them
+*/
+pub trait Challenger {
+    /** the num_challenges column indices, each below extended_row_length */
+    fn challenges(
+        &self,
+        statement: &QueryStatement,
+        extended_row_length: usize,
+        num_challenges: usize,
+    ) -> Result<Vec<u16>, ChallengeError>;
+}
+
+/** Distinct challenges from the transcript of the whole statement, hashed with H, see query_transcript */
+pub struct FiatShamirChallenger<H: MerkleHasher = Sha256Hasher> {
+    _hasher: PhantomData<H>,
+}
+
+impl<H: MerkleHasher> FiatShamirChallenger<H> {
+    pub fn new() -> Self {
+        FiatShamirChallenger {
+            _hasher: PhantomData,
+        }
+    }
+}
+
+impl<H: MerkleHasher> Default for FiatShamirChallenger<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: MerkleHasher> Challenger for FiatShamirChallenger<H> {
+    fn challenges(
+        &self,
+        statement: &QueryStatement,
+        extended_row_length: usize,
+        num_challenges: usize,
+    ) -> Result<Vec<u16>, ChallengeError> {
+        query_transcript::<H>(
+            statement.root,
+            statement.evaluation_point,
+            statement.eval,
+            statement.t_prime,
+            statement.grinding_nonce,
+        )
+        .distinct_challenge_indices(extended_row_length, num_challenges)
+    }
+}
+
+/** The same chosen indices for every statement, for tests only: a prover who knows the queries can cheat */
+pub struct FixedChallenger {
+    pub indices: Vec<u16>,
+}
+
+impl Challenger for FixedChallenger {
+    fn challenges(
+        &self,
+        _statement: &QueryStatement,
+        extended_row_length: usize,
+        num_challenges: usize,
+    ) -> Result<Vec<u16>, ChallengeError> {
+        if self.indices.len() != num_challenges {
+            return Err(ChallengeError::FixedCount {
+                expected: num_challenges,
+                actual: self.indices.len(),
+            });
+        }
+        match self
+            .indices
+            .iter()
+            .find(|&&c| c as usize >= extended_row_length)
+        {
+            Some(&challenge) => Err(ChallengeError::OutOfRange {
+                challenge,
+                extended_row_length,
+            }),
+            None => Ok(self.indices.clone()),
+        }
+    }
+}
+
+/** The Fiat-Shamir transcript the column queries are drawn from
+
+binds the queries to the whole statement and the prover's messages: the label, the root,
+    the evaluation point, the claimed eval, t_prime and the grinding nonce
+    (every u128/u16 absorbed as little-endian bytes).
+    Without a nonce it is the transcript the nonce is ground on
+*/
+fn query_transcript<H: MerkleHasher>(
+    root: &[u8],
+    evaluation_point: &Vec<u128>,
+    eval: &Vec<u16>,
+    t_prime: &Vec<Vec<u16>>,
+    grinding_nonce: Option<u64>,
+) -> Transcript<H> {
+    let mut transcript = Transcript::new();
+    transcript.absorb(CHALLENGE_LABEL);
+    transcript.absorb(root);
+    transcript.absorb(
+        &evaluation_point
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect::<Vec<u8>>(),
+    );
+    transcript.absorb(
+        &eval
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect::<Vec<u8>>(),
+    );
+    transcript.absorb(
+        &t_prime
+            .iter()
+            .flatten()
+            .flat_map(|x| x.to_le_bytes())
+            .collect::<Vec<u8>>(),
+    );
+    if let Some(nonce) = grinding_nonce {
+        transcript.absorb(&nonce.to_le_bytes());
+    }
+    transcript
+}
+
+/** A Proof whose Merkle branches are merged into one MultiBranch
+
+the num_challenges branches of a proof repeat the nodes near the root, one MultiBranch includes
+    each node once, so the proof is smaller by about num_challenges * log2(num_challenges) digests
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactProof {
+    // the proof, without its branches
+    pub proof: Proof,
+    // the branches of the queried columns, at the challenges
+    pub multi_branch: MultiBranch,
+}
+
+pub fn prove_compact(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> CompactProof {
+    prove_compact_with_hasher::<Sha256Hasher>(commitment, evaluations, evaluation_point, params)
+}
+
+/** prove, with the branches of the queried columns merged into one MultiBranch
+
+Args:
+    commitment: the commitment
+    evaluations: the evaluations
+    evaluation_point: the evaluation point
+    params: the PcsParams
+
+Returns:
+    the CompactProof, verified with verify_compact
+*/
+pub fn prove_compact_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    params: &PcsParams,
+) -> CompactProof {
+    let mut proof = prove_with_hasher::<H>(commitment, evaluations, evaluation_point, params);
+    let challenges = query_transcript::<H>(
+        &commitment.root,
+        &proof.evaluation_point,
+        &proof.eval,
+        &proof.t_prime,
+        proof.grinding_nonce,
+    )
+    .distinct_challenge_indices(commitment.columns.len(), params.num_challenges)
+    .expect("failed to draw num_challenges distinct challenges");
+    let positions: Vec<usize> = challenges.iter().map(|&c| c as usize).collect();
+    proof.branches.clear();
+    CompactProof {
+        proof,
+        multi_branch: get_multi_branch(&commitment.merkle_tree, &positions),
+    }
+}
+
+pub fn verify_compact(
+    root: &[u8],
+    proof: &CompactProof,
+    evaluation_point: &[u128],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verify_compact_with_hasher::<Sha256Hasher>(root, proof, evaluation_point, params)
+}
+
+/** Verify a CompactProof with only the root of the commitment
+
+the opened columns of the proof are authenticated against the root all at once by the MultiBranch,
+    then checked against t_prime and the eval as in verifier
+
+Args:
+    root: the Merkle root of the commitment
+    proof: the CompactProof
+    evaluation_point: the evaluation point, compared with the point the proof carries after the zero padding of commit_padded
+    params: the PCS parameters
+
+Returns:
+    Ok if the proof is valid for the root, the VerifyError otherwise
+*/
+pub fn verify_compact_with_hasher<H: MerkleHasher>(
+    root: &[u8],
+    proof: &CompactProof,
+    evaluation_point: &[u128],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    let CompactProof {
+        proof,
+        multi_branch,
+    } = proof;
+    let root = root.to_vec();
+    if !proof.branches.is_empty() {
+        return Err(VerifyError::MalformedProof);
+    }
+    let (challenges, log_row_length) = verifier_challenges::<H>(
+        &root,
+        proof,
+        evaluation_point,
+        params,
+        &FiatShamirChallenger::<H>::new(),
+        false,
+    )?;
+    let positions: Vec<usize> = challenges.iter().map(|&c| c as usize).collect();
+    let leaves: Vec<Vec<u8>> = proof
+        .columns
+        .iter()
+        .map(|col| col.iter().copied().collect())
+        .collect();
+    let leaf_count = (1 << log_row_length) * params.expansion_factor / params.packing_factor;
+    if !verify_multi_branch::<H>(&root, &positions, &leaves, multi_branch, leaf_count) {
+        return Err(VerifyError::MultiBranchMismatch);
+    }
+    check_algebraic(proof, &challenges, log_row_length, params)
+}
+
+pub fn prove_to_cap(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    cap_height: usize,
+    params: &PcsParams,
+) -> Proof {
+    prove_to_cap_with_hasher::<Sha256Hasher>(
+        commitment,
+        evaluations,
+        evaluation_point,
+        cap_height,
+        params,
+    )
+}
+
+/** prove, with the branches of the queried columns stopping at the cap of commitment.cap(cap_height)
+
+Args:
+    commitment: the commitment
+    evaluations: the evaluations
+    evaluation_point: the evaluation point
+    cap_height: the level of the cap, at most log2 of the number of columns
+    params: the PcsParams
+
+Returns:
+    the proof, its branches cap_height siblings shorter, verified with verify_to_cap
+*/
+pub fn prove_to_cap_with_hasher<H: MerkleHasher>(
+    commitment: &Commitment,
+    evaluations: &[u8],
+    evaluation_point: &Vec<u128>,
+    cap_height: usize,
+    params: &PcsParams,
+) -> Proof {
+    let mut proof = prove_with_hasher::<H>(commitment, evaluations, evaluation_point, params);
+    let challenges = query_transcript::<H>(
+        &commitment.root,
+        &proof.evaluation_point,
+        &proof.eval,
+        &proof.t_prime,
+        proof.grinding_nonce,
+    )
+    .distinct_challenge_indices(commitment.columns.len(), params.num_challenges)
+    .expect("failed to draw num_challenges distinct challenges");
+    proof.branches = challenges
+        .iter()
+        .map(|&c| get_branch_to_cap(&commitment.merkle_tree, c as usize, cap_height))
+        .collect();
+    proof
+}
+
+pub fn verify_to_cap(
+    cap: &[Vec<u8>],
+    proof: &Proof,
+    evaluation_point: &[u128],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    verify_to_cap_with_hasher::<Sha256Hasher>(cap, proof, evaluation_point, params)
+}
+
+/** Verify a proof of prove_to_cap with only the cap of the commitment
+
+the opened columns are authenticated against their nodes of the cap instead of the packed columns,
+    and the challenges are drawn from the root above the cap
+
+Args:
+    cap: the cap of the commitment, commitment.cap(cap_height)
+    proof: the proof, its branches end at the cap
+    evaluation_point: the evaluation point, compared with the point the proof carries after the zero padding of commit_padded
+    params: the PCS parameters
+
+Returns:
+    Ok if the proof is valid for the cap, the VerifyError otherwise
+*/
+pub fn verify_to_cap_with_hasher<H: MerkleHasher>(
+    cap: &[Vec<u8>],
+    proof: &Proof,
+    evaluation_point: &[u128],
+    params: &PcsParams,
+) -> Result<(), VerifyError> {
+    if cap.is_empty() || !cap.len().is_power_of_two() {
+        return Err(VerifyError::MalformedProof);
+    }
+    let root = root_of_cap::<H>(cap);
+    let (challenges, log_row_length) = verifier_challenges::<H>(
+        &root,
+        proof,
+        evaluation_point,
+        params,
+        &FiatShamirChallenger::<H>::new(),
+        false,
+    )?;
+    if proof.branches.len() != challenges.len() {
+        return Err(VerifyError::MalformedProof);
+    }
+    let leaf_count = (1 << log_row_length) * params.expansion_factor / params.packing_factor;
+    for (i, &challenge) in challenges.iter().enumerate() {
+        let packed_column: Vec<u8> = proof.columns[i].iter().copied().collect();
+        if !verify_branch_to_cap::<H>(
+            cap,
+            challenge as usize,
+            &packed_column,
+            &proof.branches[i],
+            leaf_count,
+        ) {
+            return Err(VerifyError::BranchMismatch { index: i });
+        }
+    }
+    check_algebraic(proof, &challenges, log_row_length, params)
+}
+
+// query_transcript over all the points of a MultiProof, the points have the same length so
+// concatenating them (and the evals, the t_primes) is unambiguous
+fn multi_query_transcript<H: MerkleHasher>(
+    root: &[u8],
+    points: &[Vec<u128>],
+    evals: &Vec<Vec<u16>>,
+    t_primes: &Vec<Vec<Vec<u16>>>,
+    grinding_nonce: Option<u64>,
+) -> Transcript<H> {
+    query_transcript::<H>(
+        root,
+        &points.concat(),
+        &evals.concat(),
+        &t_primes.concat(),
+        grinding_nonce,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::binary_field16::int_to_bigbin;
+    use super::super::challenger::check_grind_with_hasher;
+    use super::super::utils::assert_matrix_eq;
+    use super::*;
+
+    #[test]
+    fn test_commit() {
+        let evaluations = vec![1; 1 << 20];
+        let result = commit(&evaluations, &PcsParams::default());
+
+        assert_eq!(
+            result.root,
+            vec![
+                14, 137, 1, 182, 32, 73, 136, 127, 237, 218, 39, 11, 5, 243, 134, 95, 106, 158,
+                189, 161, 93, 114, 169, 113, 24, 23, 215, 128, 16, 106, 56, 90
+            ]
+        );
+    }
+
+    #[test]
+    fn test_commit_reader() {
+        let evaluations = vec![1; 1 << 20];
+        let params = PcsParams::default();
+        let result =
+            commit_reader(io::Cursor::new(&evaluations), evaluations.len(), &params).unwrap();
+
+        assert_eq!(
+            result.root,
+            vec![
+                14, 137, 1, 182, 32, 73, 136, 127, 237, 218, 39, 11, 5, 243, 134, 95, 106, 158,
+                189, 161, 93, 114, 169, 113, 24, 23, 215, 128, 16, 106, 56, 90
+            ]
+        );
+        let expected = commit(&evaluations, &params);
+        assert_matrix_eq(&result.rows, &expected.rows);
+        assert_matrix_eq(&result.columns, &expected.columns);
+        assert_eq!(result, expected);
+
+        // a reader that ends early
+        let error =
+            commit_reader(io::Cursor::new(&evaluations[..1000]), 1 << 20, &params).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        let error = commit_reader(io::Cursor::new(&evaluations), 3, &params).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_debug_summary() {
+        let evaluations = vec![1; 1 << 20];
+        let commitment = commit(&evaluations, &PcsParams::default());
+        let debug = format!("{:?}", commitment);
+        assert!(
+            debug.starts_with("Commitment { root: 0e8901b62049..."),
+            "{}",
+            debug
+        );
+        assert!(debug.contains("rows: 2048x256"), "{}", debug);
+        assert!(debug.contains("columns: 2048x2048"), "{}", debug);
+        assert!(debug.contains("merkle_tree: 4096 nodes"), "{}", debug);
+        assert!(!debug.contains("BinaryFieldElement16"), "{}", debug);
+        assert!(debug.len() < 300, "{}", debug);
+
+        let proof = prove(
+            &commitment,
+            &evaluations,
+            &vec![1; 23],
+            &PcsParams::default(),
+        );
+        let debug = format!("{:?}", proof);
+        assert!(
+            debug.contains("evaluation_point: 23 coordinates"),
+            "{}",
+            debug
+        );
+        assert!(debug.contains("t_prime: 4096x8"), "{}", debug);
+        assert!(debug.contains("columns: 32x2048"), "{}", debug);
+        assert!(debug.contains("branches: 32x11"), "{}", debug);
+        assert!(!debug.contains("BinaryFieldElement16"), "{}", debug);
+    }
+
+    #[test]
+    fn test_commit_root_only() {
+        let evaluations = vec![1; 1 << 20];
+        let root = commit_root_only(&evaluations, &PcsParams::default());
+        assert_eq!(
+            root,
+            vec![
+                14, 137, 1, 182, 32, 73, 136, 127, 237, 218, 39, 11, 5, 243, 134, 95, 106, 158,
+                189, 161, 93, 114, 169, 113, 24, 23, 215, 128, 16, 106, 56, 90
+            ]
+        );
+
+        let evaluations: Vec<u8> = (0..1u32 << 12).map(|i| (i * 3) as u8).collect();
+        let params = PcsParams::default();
+        assert_eq!(
+            commit_root_only(&evaluations, &params),
+            get_root(&commit(&evaluations, &params).merkle_tree)
+        );
+    }
+
+    #[test]
+    fn test_prove() {
+        let evaluations = vec![1u8; 1 << 20];
+        let commitment = commit(&evaluations, &PcsParams::default());
+        let evaluation_point = vec![1; 23];
+        let result = prove(
+            &commitment,
+            &evaluations,
+            &evaluation_point,
+            &PcsParams::default(),
+        );
+
+        assert_eq!(result.evaluation_point.len(), 23);
+        assert_eq!(result.eval, vec![0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(result.t_prime[0], vec![1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            result.branches[7][4],
+            vec![
+                87, 16, 103, 115, 59, 231, 163, 189, 151, 96, 41, 109, 226, 231, 251, 42, 204, 154,
+                35, 52, 8, 58, 252, 189, 51, 41, 4, 29, 30, 31, 212, 86
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prove_is_deterministic() {
+        // nothing in commit or prove may depend on HashMap iteration order or thread scheduling
+        let evaluations: Vec<u8> = (0..1 << 16).map(|i| (i * 31 + 7) as u8).collect();
+        let evaluation_point: Vec<u128> = (0..19).map(|i| i * 11 + 5).collect();
+        let params = PcsParams::default();
+
+        let commitment = commit(&evaluations, &params);
+        assert_eq!(commit(&evaluations, &params), commitment);
+
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+        assert_eq!(
+            prove(&commitment, &evaluations, &evaluation_point, &params),
+            proof
+        );
+        assert_eq!(
+            prove(&commitment, &evaluations, &evaluation_point, &params).to_bytes(),
+            proof.to_bytes()
+        );
+    }
+
+    #[test]
+    fn test_eval_matches_evaluate_mle() {
+        // xorshift, a reproducible random input
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let evaluations: Vec<u8> = (0..1 << 13).map(|_| next() as u8).collect();
+        let evaluation_point: Vec<u128> = (0..16)
+            .map(|_| (next() as u128) << 64 | next() as u128)
+            .collect();
+        let params = PcsParams::default();
+
+        let commitment = commit(&evaluations, &params);
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+        let expected = evaluate_mle(&evaluations, &evaluation_point);
+        assert_ne!(expected, vec![0; 8]);
+        assert_eq!(proof.eval, expected);
+        assert!(verifier(&commitment, &proof, &evaluation_point, &params).is_ok());
+
+        // every bit set: the value is the sum of the tensor, the product of (1 + c) + c = 1
+        let ones = vec![0xFFu8; 1 << 13];
+        assert_eq!(
+            evaluate_mle(&ones, &evaluation_point),
+            vec![1, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_verifier_with_params() {
+        let evaluations = vec![1; 1 << 16];
+        let params = PcsParams {
+            expansion_factor: 4,
+            num_challenges: 16,
+            packing_factor: 16,
+            grinding_bits: 8,
+        };
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point = vec![1; 19];
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+        assert_eq!(proof.columns.len(), 16);
+        assert!(verifier(&commitment, &proof, &evaluation_point, &params).is_ok());
+    }
+
+    #[test]
+    fn test_verifier_pruned() {
+        let evaluations = vec![1; 1 << 20];
+        let commitment = commit(&evaluations, &PcsParams::default());
+        let evaluation_point = vec![1; 23];
+        let proof = prove(
+            &commitment,
+            &evaluations,
+            &evaluation_point,
+            &PcsParams::default(),
+        );
+
+        let root = commitment.root().to_vec();
+        let pruned = commitment.prune();
+        assert_eq!(pruned.root(), root.as_slice());
+        assert!(verifier_pruned(&pruned, &proof, &evaluation_point, &PcsParams::default()).is_ok());
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let evaluations = vec![1u8; 1 << 20];
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point = vec![1; 23];
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+
+        let proof_json = serde_json::to_string(&proof).unwrap();
+        let commitment_json = serde_json::to_string(&commitment).unwrap();
+        let proof: Proof = serde_json::from_str(&proof_json).unwrap();
+        let commitment: Commitment = serde_json::from_str(&commitment_json).unwrap();
+        assert!(verifier(&commitment, &proof, &evaluation_point, &params).is_ok());
+    }
+
+    #[test]
+    fn test_eval_as_u128() {
+        let evaluations: Vec<u8> = (0..1u32 << 12).map(|i| (i * 7 + 1) as u8).collect();
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point: Vec<u128> = (1..=15).collect();
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+        let eval = eval_as_u128(&proof);
+        assert_eq!(int_to_bigbin(eval), proof.eval);
+        assert_ne!(eval, 0);
+
+        // the zero polynomial evaluates to 0 in both implementations
+        let zeros = vec![0u8; 1 << 12];
+        let proof = prove(&commit(&zeros, &params), &zeros, &evaluation_point, &params);
+        assert_eq!(eval_as_u128(&proof), 0);
+    }
+
+    #[test]
+    fn test_proof_size_bytes() {
+        let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 9 + 4) as u8).collect();
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point: Vec<u128> = (6..25).collect();
+        let mut proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+
+        let size = proof_size_bytes(&proof);
+        assert_eq!(size.total(), proof.to_bytes().len());
+        assert_eq!(size.evaluation_point, 4 + 19 * 16);
+        // 512 columns of 512 elements, so each column is bigger than its branch of 9 SHA256 hashes
+        assert_eq!(size.columns, 4 + 32 * (4 + 512 * 2));
+        assert_eq!(size.branches, 4 + 32 * (4 + 9 * (4 + 32)));
+        assert_eq!(size.grinding_nonce, 9);
+
+        proof.grinding_nonce = None;
+        assert_eq!(proof_size_bytes(&proof).total(), proof.to_bytes().len());
+    }
+
+    #[test]
+    fn test_proof_bytes_round_trip() {
+        let evaluations = vec![1u8; 1 << 20];
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point = vec![1; 23];
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+
+        let bytes = proof.to_bytes();
+        let decoded = Proof::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert!(verifier(&commitment, &decoded, &evaluation_point, &params).is_ok());
+
+        // 32 columns of 64 elements and 32 branches of 12 hashes dominate, JSON is several times bigger
+        let json = serde_json::to_string(&proof).unwrap();
+        assert!(bytes.len() * 2 < json.len());
+
+        assert_eq!(
+            Proof::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ProofDecodeError::Truncated)
+        );
+        assert_eq!(
+            Proof::from_bytes(&bytes[..10]),
+            Err(ProofDecodeError::Truncated)
+        );
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert_eq!(
+            Proof::from_bytes(&padded),
+            Err(ProofDecodeError::TrailingBytes(1))
+        );
+    }
+
+    #[test]
+    fn test_batch_round_trip() {
+        let params = PcsParams::default();
+        let first = vec![1u8; 1 << 16];
+        let second: Vec<u8> = (0..1 << 16).map(|i| (i * 7 + 3) as u8).collect();
+        let polys: Vec<&[u8]> = vec![&first, &second];
+        let commitment = commit_batch(&polys, &params);
+        let evaluation_point: Vec<u128> = (0..19).map(|i| i * 3 + 1).collect();
+        let proof = prove_batch(&commitment, &polys, &evaluation_point, &params);
+
+        // each opening matches the opening of the polynomial committed alone
+        for (poly, eval) in polys.iter().zip(proof.evals.iter()) {
+            let single = prove(&commit(poly, &params), poly, &evaluation_point, &params);
+            assert_eq!(single.eval, *eval);
+        }
+        assert_ne!(proof.evals[0], proof.evals[1]);
+        assert!(verify_batch(&commitment, &proof, &evaluation_point, &params).is_ok());
+
+        let mut tampered = proof.clone();
+        tampered.evals[1][0] ^= 1;
+        assert!(verify_batch(&commitment, &tampered, &evaluation_point, &params).is_err());
+
+        let mut tampered = proof.clone();
+        tampered.columns[2][0] = tampered.columns[2][0] + BinaryFieldElement16::new(1);
+        assert_eq!(
+            verify_batch(&commitment, &tampered, &evaluation_point, &params),
+            Err(VerifyError::BranchMismatch { index: 2 })
+        );
+    }
+
+    #[test]
+    fn test_multi_point_round_trip() {
+        let params = PcsParams::default();
+        let evaluations: Vec<u8> = (0..1 << 16).map(|i| (i * 5 + 1) as u8).collect();
+        let commitment = commit(&evaluations, &params);
+        let points = vec![vec![1; 19], (0..19).map(|i| i * 3 + 2).collect()];
+        let proof = prove_multi(&commitment, &evaluations, &points, &params);
+
+        for (point, eval) in points.iter().zip(proof.evals.iter()) {
+            let single = prove(&commitment, &evaluations, point, &params);
+            assert_eq!(single.eval, *eval);
+        }
+        assert!(verify_multi(&commitment, &proof, &points, &params).is_ok());
+
+        let mut tampered = proof.clone();
+        tampered.evals[1][0] ^= 1;
+        assert!(verify_multi(&commitment, &tampered, &points, &params).is_err());
+
+        // a proof at other points, or at some of them, is not a proof at the verifier's points
+        let other_points = vec![points[0].clone(), (0..19).map(|i| i * 5 + 4).collect()];
+        let other = prove_multi(&commitment, &evaluations, &other_points, &params);
+        assert!(verify_multi(&commitment, &other, &other_points, &params).is_ok());
+        assert_eq!(
+            verify_multi(&commitment, &other, &points, &params),
+            Err(VerifyError::PointMismatch)
+        );
+        assert_eq!(
+            verify_multi(&commitment, &proof, &points[..1], &params),
+            Err(VerifyError::PointMismatch)
+        );
+
+        let mut tampered = proof.clone();
+        tampered.columns[2][0] = tampered.columns[2][0] + BinaryFieldElement16::new(1);
+        assert_eq!(
+            verify_multi(&commitment, &tampered, &points, &params),
+            Err(VerifyError::BranchMismatch { index: 2 })
+        );
+    }
+
+    #[test]
+    fn test_prover_view() {
+        let params = PcsParams::default();
+        let evaluations: Vec<u8> = (0..1 << 16).map(|i| (i * 5 + 1) as u8).collect();
+        let evaluation_point = vec![1; 19];
+        let commitment = commit(&evaluations, &params);
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+
+        let lean = commit_lean(&evaluations, &params);
+        assert_eq!(lean.root, commitment.root);
+        let view = commitment.into_prover_view();
+        assert_eq!(view.merkle_tree, lean.merkle_tree);
+
+        let view_proof = prove_view(&view, &evaluations, &evaluation_point, &params);
+        assert_eq!(view_proof.to_bytes(), proof.to_bytes());
+        let lean_proof = prove_view(&lean, &evaluations, &evaluation_point, &params);
+        assert_eq!(lean_proof.to_bytes(), proof.to_bytes());
+    }
+
+    #[test]
+    fn test_commit_padded() {
+        let params = PcsParams::default();
+        let evaluations: Vec<u8> = (0..12).map(|i| i * 17 + 1).collect();
+        let commitment = commit_padded(&evaluations, &params);
+        assert_eq!(commitment.original_len, 12);
+
+        // 96 bits need 7 coordinates
+        let evaluation_point: Vec<u128> = (0..7).map(|i| i * 3 + 2).collect();
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+        assert!(verifier(&commitment, &proof, &evaluation_point, &params).is_ok());
+
+        // 2 bytes are padded up to MIN_LOG_EVALUATION_COUNT bits at least
+        let tiny = commit_padded(&[0xab, 0xcd], &params);
+        let tiny_proof = prove(&tiny, &[0xab, 0xcd], &vec![3; 4], &params);
+        assert!(verifier(&tiny, &tiny_proof, &vec![3; 4], &params).is_ok());
+
+        // the same as padding by hand
+        let log_evaluation_count = proof.evaluation_point.len();
+        let mut padded = evaluations.clone();
+        padded.resize((1 << log_evaluation_count) / 8, 0);
+        let by_hand = commit(&padded, &params);
+        assert_eq!(by_hand.root, commitment.root);
+        let mut padded_point = evaluation_point.clone();
+        padded_point.resize(log_evaluation_count, 0);
+        assert_eq!(
+            prove(&by_hand, &padded, &padded_point, &params).eval,
+            proof.eval
+        );
+    }
+
+    #[test]
+    fn test_verifier_rejects_layout_of_another_commitment() {
+        let params = PcsParams::default();
+        let small: Vec<u8> = (0..1u32 << 12).map(|i| (i * 5 + 1) as u8).collect();
+        let large: Vec<u8> = (0..1u32 << 16).map(|i| (i * 7 + 3) as u8).collect();
+        let small_commitment = commit(&small, &params);
+        let large_commitment = commit(&large, &params);
+
+        // a proof of the large commitment at a zero-padded point, checked against the leaves
+        // of the small one, draws challenges past its columns and is rejected without indexing them
+        let point: Vec<u128> = (1..=15).collect();
+        let proof = prove(&large_commitment, &large, &point, &params);
+        let mixed = PrunedCommitment {
+            root: large_commitment.root.clone(),
+            packed_columns: small_commitment.packed_columns.clone(),
+        };
+        assert!(matches!(
+            verifier_pruned(&mixed, &proof, &point, &params),
+            Err(VerifyError::ChallengeOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parallel_prove_matches_sequential() {
+        let evaluations = vec![1u8; 1 << 20];
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point = vec![1; 23];
+
+        let parallel = prove(&commitment, &evaluations, &evaluation_point, &params);
+        let sequential = open::<Sha256Hasher>(
+            &ProverInputs::from(&commitment),
+            &evaluation_point,
+            &params,
+            &FiatShamirChallenger::<Sha256Hasher>::new(),
+            false,
+        );
+        assert_eq!(parallel, sequential);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_hiding_round_trip() {
+        let params = PcsParams::default();
+        let evaluations: Vec<u8> = (0..1 << 16).map(|i| (i * 5 + 1) as u8).collect();
+        let evaluation_point: Vec<u128> = (0..19).map(|i| i * 3 + 2).collect();
+        let first = commit_hiding(&evaluations, &params);
+        let second = commit_hiding(&evaluations, &params);
+        assert_ne!(first.root, second.root);
+
+        let expected = prove(
+            &commit(&evaluations, &params),
+            &evaluations,
+            &evaluation_point,
+            &params,
+        )
+        .eval;
+        let mut t_primes = vec![];
+        for commitment in [&first, &second] {
+            let proof = prove_hiding(commitment, &evaluations, &evaluation_point, &params);
+            assert_eq!(proof.proof.eval, expected);
+            assert!(verify_hiding(commitment, &proof, &evaluation_point, &params).is_ok());
+            // the plain verifier expects t_prime without the blinding half
+            assert!(verifier(commitment, &proof.proof, &evaluation_point, &params).is_err());
+
+            // the mask eval is bound by rho, and rho by the mask eval
+            let mut bad_mask = proof.clone();
+            bad_mask.mask_eval[0] ^= 1;
+            assert!(verify_hiding(commitment, &bad_mask, &evaluation_point, &params).is_err());
+            t_primes.push(proof.proof.t_prime);
+        }
+        // the same evaluations and point give unrelated t_primes, the data half included
+        let row_length = t_primes[0].len() / 2;
+        assert_ne!(t_primes[0][..row_length], t_primes[1][..row_length]);
+        assert!((0..row_length).all(|j| t_primes[0][j] != t_primes[1][j]));
+    }
+
+    #[test]
+    fn test_binius() {
+        let binius = Binius::default();
+        assert_eq!(binius.params.expansion_factor, EXPANSION_FACTOR);
+        assert_eq!(binius.params.num_challenges, NUM_CHALLENGES);
+        assert_eq!(binius.params.packing_factor, PACKING_FACTOR);
+
+        let evaluations: Vec<u8> = (0..1 << 16).map(|i| (i * 5 + 1) as u8).collect();
+        let evaluation_point: Vec<u128> = (0..19).map(|i| i * 3 + 2).collect();
+        let commitment = binius.commit(&evaluations);
+        assert_eq!(
+            commitment.root,
+            commit(&evaluations, &PcsParams::default()).root
+        );
+        let proof = binius.prove(&commitment, &evaluations, &evaluation_point);
+        assert!(binius
+            .verify(&commitment, &proof, &evaluation_point)
+            .is_ok());
+
+        let other_point: Vec<u128> = (0..19).map(|i| i * 7 + 1).collect();
+        let mut wrong = proof.clone();
+        wrong.eval = binius.prove(&commitment, &evaluations, &other_point).eval;
+        assert!(binius
+            .verify(&commitment, &wrong, &evaluation_point)
+            .is_err());
+    }
+
+    #[test]
+    fn test_verifier() {
+        let evaluations = vec![1; 1 << 20];
+        let commitment = commit(&evaluations, &PcsParams::default());
+        let evaluation_point = vec![1; 23];
+        let proof = prove(
+            &commitment,
+            &evaluations,
+            &evaluation_point,
+            &PcsParams::default(),
+        );
+        assert!(verifier(
+            &commitment,
+            &proof,
+            &evaluation_point,
+            &PcsParams::default()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_prove_compact() {
+        let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 13 + 4) as u8).collect();
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point: Vec<u128> = (7..26).collect();
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+        let compact = prove_compact(&commitment, &evaluations, &evaluation_point, &params);
+        assert!(verify_compact(&commitment.root, &compact, &evaluation_point, &params).is_ok());
+
+        // the same proof, with fewer nodes than the separate branches
+        let separate: usize = proof.branches.iter().map(|branch| branch.len()).sum();
+        assert!(compact.multi_branch.nodes.len() < separate);
+        assert_eq!(
+            compact.proof,
+            Proof {
+                branches: vec![],
+                ..proof
+            }
+        );
+
+        let mut bad_column = compact.clone();
+        bad_column.proof.columns[3][0] =
+            bad_column.proof.columns[3][0] + BinaryFieldElement16::new(1);
+        assert_eq!(
+            verify_compact(&commitment.root, &bad_column, &evaluation_point, &params),
+            Err(VerifyError::MultiBranchMismatch)
+        );
+        let mut bad_node = compact.clone();
+        bad_node.multi_branch.nodes.pop();
+        assert_eq!(
+            verify_compact(&commitment.root, &bad_node, &evaluation_point, &params),
+            Err(VerifyError::MultiBranchMismatch)
+        );
+        assert_eq!(
+            verify_compact(&commitment.root, &compact, &vec![1; 19], &params),
+            Err(VerifyError::PointMismatch)
+        );
     }
 
+    #[cfg(feature = "blake3")]
     #[test]
-    fn test_prove() {
-        let evaluations = vec![1u8; 1 << 20];
-        let commitment = commit(&evaluations);
+    fn test_blake3_round_trip() {
+        use super::super::merkle_tree::Blake3Hasher;
+
+        let evaluations = vec![1; 1 << 20];
+        let commitment = commit_with_hasher::<Blake3Hasher>(&evaluations, &PcsParams::default());
+        assert_ne!(
+            commitment.root,
+            commit(&evaluations, &PcsParams::default()).root
+        );
+
         let evaluation_point = vec![1; 23];
-        let result = prove(&commitment, &evaluations, &evaluation_point);
+        let proof = prove_with_hasher::<Blake3Hasher>(
+            &commitment,
+            &evaluations,
+            &evaluation_point,
+            &PcsParams::default(),
+        );
+        assert!(verifier_with_hasher::<Blake3Hasher>(
+            &commitment,
+            &proof,
+            &evaluation_point,
+            &PcsParams::default()
+        )
+        .is_ok());
+    }
 
-        assert_eq!(result.evaluation_point.len(), 23);
-        assert_eq!(result.eval, vec![0, 0, 0, 0, 0, 0, 0, 0]);
-        assert_eq!(result.t_prime[0], vec![1, 0, 0, 0, 0, 0, 0, 0]);
+    #[test]
+    fn test_truncated_hasher_round_trip() {
+        use super::super::merkle_tree::TruncatedSha256Hasher;
+        type H16 = TruncatedSha256Hasher<16>;
+
+        let evaluations = vec![1; 1 << 20];
+        let evaluation_point = vec![1; 23];
+        let params = PcsParams::default();
+        let commitment = commit_with_hasher::<H16>(&evaluations, &params);
+        assert_eq!(commitment.root.len(), 16);
+
+        let proof = prove_with_hasher::<H16>(&commitment, &evaluations, &evaluation_point, &params);
+        assert!(
+            verifier_with_hasher::<H16>(&commitment, &proof, &evaluation_point, &params).is_ok()
+        );
+
+        let full = prove(
+            &commit(&evaluations, &params),
+            &evaluations,
+            &evaluation_point,
+            &params,
+        );
+        let branch_bytes = |p: &Proof| {
+            p.branches
+                .iter()
+                .flatten()
+                .map(|node| node.len())
+                .sum::<usize>()
+        };
+        assert_eq!(branch_bytes(&proof) * 2, branch_bytes(&full));
+    }
+
+    #[test]
+    fn test_prove_to_cap() {
+        let params = PcsParams::default();
+        let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 9 + 2) as u8).collect();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point: Vec<u128> = (3..22).collect();
+        let full = prove(&commitment, &evaluations, &evaluation_point, &params);
+        let proof = prove_to_cap(&commitment, &evaluations, &evaluation_point, 2, &params);
+        let cap = commitment.cap(2);
+
+        // the same opening, each branch two siblings shorter
+        assert_eq!(proof.columns, full.columns);
+        for (branch, full_branch) in proof.branches.iter().zip(&full.branches) {
+            assert_eq!(*branch, full_branch[..full_branch.len() - 2]);
+        }
+        assert!(verify_to_cap(&cap, &proof, &evaluation_point, &params).is_ok());
+
+        // the full branches do not end at the cap, nor do the branches of another cap
         assert_eq!(
-            result.branches[7][4],
-            vec![
-                87, 16, 103, 115, 59, 231, 163, 189, 151, 96, 41, 109, 226, 231, 251, 42, 204, 154,
-                35, 52, 8, 58, 252, 189, 51, 41, 4, 29, 30, 31, 212, 86
-            ]
+            verify_to_cap(&cap, &full, &evaluation_point, &params),
+            Err(VerifyError::BranchMismatch { index: 0 })
+        );
+        let mut bad_cap = cap.clone();
+        bad_cap[1][0] ^= 1;
+        assert!(verify_to_cap(&bad_cap, &proof, &evaluation_point, &params).is_err());
+        let mut bad_column = proof.clone();
+        bad_column.columns[4][0] = bad_column.columns[4][0] + BinaryFieldElement16::new(1);
+        assert_eq!(
+            verify_to_cap(&cap, &bad_column, &evaluation_point, &params),
+            Err(VerifyError::BranchMismatch { index: 4 })
         );
     }
 
     #[test]
-    fn test_verifier() {
+    fn test_prove_checked() {
+        let evaluations = vec![1; 1 << 20];
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+
+        assert_eq!(
+            prove_checked(&commitment, &evaluations, &vec![1; 22], &params),
+            Err(ProveError::PointLength {
+                expected: 23,
+                actual: 22
+            })
+        );
+        assert_eq!(
+            prove_checked(&commitment, &evaluations[..1 << 19], &vec![1; 22], &params),
+            Err(ProveError::CommittedSize {
+                committed: 1 << 20,
+                evaluations: 1 << 19
+            })
+        );
+
+        // a padded commitment takes the point of its unpadded evaluations
+        let padded = commit_padded(&evaluations[..12], &params);
+        assert!(prove_checked(&padded, &evaluations[..12], &vec![1; 7], &params).is_ok());
+
+        let evaluation_point = vec![1; 23];
+        let proof = prove_checked(&commitment, &evaluations, &evaluation_point, &params).unwrap();
+        assert_eq!(
+            proof,
+            prove(&commitment, &evaluations, &evaluation_point, &params)
+        );
+    }
+
+    #[test]
+    fn test_security_bits() {
+        let params = PcsParams::default();
+        // 32 queries at delta = 7/16 give 26.6 bits, the grinding adds 16
+        let bits = security_bits(&params, 128);
+        assert!(bits > 42.0 && bits < 43.0, "{}", bits);
+        // the field bounds the estimate
+        assert!(security_bits(&params, 16) < 16.0);
+
+        let doubled = PcsParams {
+            num_challenges: 2 * params.num_challenges,
+            ..params
+        };
+        assert!(security_bits(&doubled, 128) > bits + 26.0);
+        let lower_rate = PcsParams {
+            expansion_factor: 2 * params.expansion_factor,
+            ..params
+        };
+        assert!(security_bits(&lower_rate, 128) > bits);
+        let no_grinding = PcsParams {
+            grinding_bits: 0,
+            ..params
+        };
+        assert!((security_bits(&no_grinding, 128) - (bits - 16.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_commit_elements() {
+        let params = PcsParams::default();
+        let elements: Vec<BinaryFieldElement16> = (0..1u32 << 16)
+            .map(|i| BinaryFieldElement16::new((i.wrapping_mul(0x9e37) >> 3) as u16))
+            .collect();
+        let evaluations: Vec<u8> = elements
+            .iter()
+            .flat_map(|e| e.value.to_le_bytes())
+            .collect();
+
+        let commitment = commit_elements(&elements, &params);
+        let from_bytes = commit(&evaluations, &params);
+        assert_eq!(commitment.root, from_bytes.root);
+        assert_matrix_eq(&commitment.rows, &from_bytes.rows);
+        assert_matrix_eq(&commitment.columns, &from_bytes.columns);
+        assert_eq!(commitment, from_bytes);
+
+        let evaluation_point: Vec<u128> = (1..=20).collect();
+        let proof = prove_elements(&commitment, &elements, &evaluation_point, &params);
+        assert_eq!(
+            proof,
+            prove(&from_bytes, &evaluations, &evaluation_point, &params)
+        );
+        assert!(verifier(&commitment, &proof, &evaluation_point, &params).is_ok());
+    }
+
+    #[test]
+    fn test_layout_for() {
+        let evaluations = vec![1; 1 << 20];
+        let params = PcsParams::default();
+        let layout = layout_for(evaluations.len() * 8, &params);
+        assert_eq!(layout.row_length, 4096);
+        assert_eq!(layout.row_count, 2048);
+        assert_eq!(layout.extended_row_length, 2048);
+
+        let commitment = commit(&evaluations, &params);
+        assert_eq!(commitment.rows.len(), layout.row_count);
+        assert_eq!(commitment.rows[0].len(), layout.packed_row_length);
+        assert_eq!(commitment.columns.len(), layout.extended_row_length);
+        assert_eq!(commitment.columns[0].len(), layout.row_count);
+        assert_eq!(commitment.packed_columns.len(), layout.leaf_count);
+        assert_eq!(commitment.merkle_tree.len(), 2 * layout.leaf_count);
+    }
+
+    #[test]
+    fn test_verify_merkle_openings_and_algebraic() {
+        let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 7 + 3) as u8).collect();
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point: Vec<u128> = (1..=19).collect();
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+        assert!(verify_merkle_openings(&commitment, &proof, &params).is_ok());
+        assert!(verify_algebraic(&commitment, &proof, &evaluation_point, &params).is_ok());
+
+        // the opened columns are hashed into the leaves of their challenges
+        let mut bad_column = proof.clone();
+        bad_column.columns[0][0] = bad_column.columns[0][0] + BinaryFieldElement16::new(1);
+        assert_eq!(
+            verify_merkle_openings(&commitment, &bad_column, &params),
+            Err(VerifyError::BranchMismatch { index: 0 })
+        );
+        assert_eq!(
+            verifier(&commitment, &bad_column, &evaluation_point, &params),
+            Err(VerifyError::BranchMismatch { index: 0 })
+        );
+
+        // a prover lying about t_prime opens the committed columns of the challenges it draws,
+        // which the Merkle check accepts and the algebraic check does not
+        let mut t_prime = proof.t_prime.clone();
+        t_prime[0][0] ^= 1;
+        let statement = query_transcript::<Sha256Hasher>(
+            &commitment.root,
+            &proof.evaluation_point,
+            &proof.eval,
+            &t_prime,
+            None,
+        );
+        let bad_t_prime = open_queried(
+            &ProverInputs::from(&commitment),
+            &proof.evaluation_point,
+            Opening {
+                t_prime,
+                eval: proof.eval.clone(),
+                grinding_nonce: grind_nonce(&statement, &params),
+            },
+            0,
+            &params,
+            &FiatShamirChallenger::<Sha256Hasher>::new(),
+            false,
+        );
+        assert!(verify_merkle_openings(&commitment, &bad_t_prime, &params).is_ok());
+        assert_eq!(
+            verify_algebraic(&commitment, &bad_t_prime, &evaluation_point, &params),
+            Err(VerifyError::TPrimeMismatch)
+        );
+        assert_eq!(
+            verifier(&commitment, &bad_t_prime, &evaluation_point, &params),
+            Err(VerifyError::TPrimeMismatch)
+        );
+
+        // and the branches are not part of the algebraic check
+        let mut bad_branch = proof.clone();
+        bad_branch.branches[3][0][0] ^= 1;
+        assert_eq!(
+            verify_merkle_openings(&commitment, &bad_branch, &params),
+            Err(VerifyError::BranchMismatch { index: 3 })
+        );
+        assert!(verify_algebraic(&commitment, &bad_branch, &evaluation_point, &params).is_ok());
+        assert_eq!(
+            verifier(&commitment, &bad_branch, &evaluation_point, &params),
+            Err(VerifyError::BranchMismatch { index: 3 })
+        );
+
+        // a truncated sibling is rejected as malformed before any hashing
+        let mut truncated = proof.clone();
+        truncated.branches[5][2].truncate(3);
+        assert_eq!(
+            verify_merkle_openings(&commitment, &truncated, &params),
+            Err(VerifyError::MalformedBranch {
+                index: 5,
+                error: BranchError::SiblingLength {
+                    level: 2,
+                    expected: 32,
+                    actual: 3
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_verifier_with_challenges() {
+        let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 3 + 5) as u8).collect();
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point: Vec<u128> = (4..23).collect();
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+
+        // the Fiat-Shamir challenges give the verifier result
+        let challenges = query_transcript::<Sha256Hasher>(
+            &commitment.root,
+            &proof.evaluation_point,
+            &proof.eval,
+            &proof.t_prime,
+            proof.grinding_nonce,
+        )
+        .distinct_challenge_indices(commitment.columns.len(), params.num_challenges)
+        .unwrap();
+        assert_eq!(
+            verifier_with_challenges(&commitment, &proof, &evaluation_point, &challenges, &params),
+            verifier(&commitment, &proof, &evaluation_point, &params)
+        );
+        // the fixed challenges still open the right columns, so only the eval check fails,
+        // while verifier draws other challenges from the changed eval
+        let mut bad_eval = proof.clone();
+        bad_eval.eval[0] ^= 1;
+        assert_eq!(
+            verifier_with_challenges(
+                &commitment,
+                &bad_eval,
+                &evaluation_point,
+                &challenges,
+                &params
+            ),
+            Err(VerifyError::EvalMismatch)
+        );
+        assert!(verifier(&commitment, &bad_eval, &evaluation_point, &params).is_err());
+
+        // a proof opening other columns, chosen outside of the PCS
+        let external: Vec<u16> = vec![0, 7, 300, 511, 7];
+        let mut external_proof = proof.clone();
+        external_proof.columns = external
+            .iter()
+            .map(|&c| commitment.columns[c as usize].clone())
+            .collect();
+        external_proof.branches = external
+            .iter()
+            .map(|&c| get_branch(&commitment.merkle_tree, c.into()))
+            .collect();
+        assert!(verifier_with_challenges(
+            &commitment,
+            &external_proof,
+            &evaluation_point,
+            &external,
+            &params
+        )
+        .is_ok());
+        assert_eq!(
+            verifier_with_challenges(
+                &commitment,
+                &external_proof,
+                &evaluation_point,
+                &challenges,
+                &params
+            ),
+            Err(VerifyError::MalformedProof)
+        );
+        let mut out_of_range = external.clone();
+        out_of_range[2] = 512;
+        assert_eq!(
+            verifier_with_challenges(
+                &commitment,
+                &external_proof,
+                &evaluation_point,
+                &out_of_range,
+                &params
+            ),
+            Err(VerifyError::ChallengeOutOfRange {
+                index: 2,
+                challenge: 512,
+                extended_row_length: 512
+            })
+        );
+    }
+
+    #[test]
+    fn test_prove_gathers_in_draw_order() {
+        let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 13 + 1) as u8).collect();
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point: Vec<u128> = (3..22).collect();
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+
+        let challenges = query_transcript::<Sha256Hasher>(
+            &commitment.root,
+            &proof.evaluation_point,
+            &proof.eval,
+            &proof.t_prime,
+            proof.grinding_nonce,
+        )
+        .distinct_challenge_indices(commitment.columns.len(), params.num_challenges)
+        .unwrap();
+        // the draw order is not sorted, the proof still follows it
+        assert_ne!(challenge_index_set(&challenges), challenges);
+        for (i, &c) in challenges.iter().enumerate() {
+            assert_eq!(proof.columns[i], commitment.columns[c as usize]);
+            assert_eq!(
+                proof.branches[i],
+                get_branch(&commitment.merkle_tree, c.into())
+            );
+        }
+        assert!(verifier(&commitment, &proof, &evaluation_point, &params).is_ok());
+    }
+
+    #[test]
+    fn test_prove_profiled() {
+        let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 11 + 7) as u8).collect();
+        let params = PcsParams::default();
+        let evaluation_point: Vec<u128> = (2..21).collect();
+        let (commitment, proof, profile) = prove_profiled(&evaluations, &evaluation_point, &params);
+
+        // the same commitment and proof as commit and prove
+        let expected = commit(&evaluations, &params);
+        assert_eq!(commitment.root, expected.root);
+        assert_eq!(
+            proof,
+            prove(&expected, &evaluations, &evaluation_point, &params)
+        );
+        assert!(verifier(&commitment, &proof, &evaluation_point, &params).is_ok());
+
+        for stage in [
+            profile.pack,
+            profile.extend,
+            profile.merkle,
+            profile.t_prime,
+            profile.eval,
+            profile.grind,
+            profile.branches,
+        ] {
+            assert!(stage > Duration::ZERO);
+        }
+        assert!(profile.stages() <= profile.total);
+        assert!(profile.stages() * 10 >= profile.total * 9);
+    }
+
+    #[test]
+    fn test_verify_with_context() {
+        let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 17 + 9) as u8).collect();
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let context: VerifierContext = VerifierContext::new(&commitment, &params).unwrap();
+        assert_eq!(context.log_evaluation_count, 19);
+
+        // two proofs at different points, one context
+        let point_a: Vec<u128> = (1..20).collect();
+        let point_b: Vec<u128> = (100..119).map(|i| i * 31).collect();
+        let proof_a = prove(&commitment, &evaluations, &point_a, &params);
+        let proof_b = prove(&commitment, &evaluations, &point_b, &params);
+        assert!(verify_with_context(&context, &proof_a, &point_a).is_ok());
+        assert!(verify_with_context(&context, &proof_b, &point_b).is_ok());
+
+        let mut bad_column = proof_b.clone();
+        bad_column.columns[4][1] = bad_column.columns[4][1] + BinaryFieldElement16::new(1);
+        assert_eq!(
+            verify_with_context(&context, &bad_column, &point_b),
+            Err(VerifyError::ColumnMismatch { index: 4 })
+        );
+        let mut bad_eval = proof_a.clone();
+        bad_eval.eval[0] ^= 1;
+        assert!(verify_with_context(&context, &bad_eval, &point_a).is_err());
+
+        // the packed columns are authenticated once, when the context is built
+        let mut tampered = commitment.clone();
+        tampered.packed_columns[3][0] ^= 1;
+        assert_eq!(
+            VerifierContext::<Sha256Hasher>::new(&tampered, &params).err(),
+            Some(BiniusError::Verify(VerifyError::RootMismatch))
+        );
+    }
+
+    #[test]
+    fn test_verify_standalone() {
+        let params = PcsParams::default();
+        let evaluation_point: Vec<u128> = (5..24).collect();
+        let (root, bytes) = {
+            let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 5 + 2) as u8).collect();
+            let commitment = commit(&evaluations, &params);
+            let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+            (commitment.root, proof.to_bytes())
+        };
+        // only the root and the serialized proof are left
+        let proof = Proof::from_bytes(&bytes).unwrap();
+        assert!(verify_standalone(&root, &proof, &evaluation_point, &params).is_ok());
+
+        let mut bad_column = proof.clone();
+        bad_column.columns[2][0] = bad_column.columns[2][0] + BinaryFieldElement16::new(1);
+        assert_eq!(
+            verify_standalone(&root, &bad_column, &evaluation_point, &params),
+            Err(VerifyError::BranchMismatch { index: 2 })
+        );
+        let mut bad_root = root.clone();
+        bad_root[0] ^= 1;
+        assert!(verify_standalone(&bad_root, &proof, &evaluation_point, &params).is_err());
+    }
+
+    #[test]
+    fn test_commit_tiny_inputs() {
+        let params = PcsParams::default();
+        assert_eq!(min_evaluation_len(&params), 128);
+        let evaluations: Vec<u8> = (0..128u32).map(|i| (i * 3 + 1) as u8).collect();
+        assert!(commit_checked(&evaluations, &params).is_ok());
+        assert_eq!(
+            commit_checked(&evaluations[..64], &params),
+            Err(BiniusError::TooManyChallenges {
+                num_challenges: 32,
+                extended_row_length: 16
+            })
+        );
+
+        // 1 and 2 bytes are below 2^MIN_LOG_EVALUATION_COUNT bits
+        for tiny in [&[0xa5u8][..], &[0x12, 0x34]] {
+            assert_eq!(
+                commit_checked(tiny, &params),
+                Err(BiniusError::InvalidLength {
+                    expected: 64,
+                    actual: tiny.len() * 8
+                })
+            );
+            // commit_padded commits to them at min_evaluation_len
+            let commitment = commit_padded(tiny, &params);
+            assert_eq!(commitment.original_len, tiny.len());
+            assert_eq!(commitment.rows.len() * commitment.rows[0].len() * 2, 128);
+            let evaluation_point: Vec<u128> = (1..=(tiny.len() as u128 * 8).trailing_zeros()
+                as u128)
+                .map(|i| i * 7)
+                .collect();
+            let proof = prove(&commitment, tiny, &evaluation_point, &params);
+            assert!(verifier(&commitment, &proof, &evaluation_point, &params).is_ok());
+        }
+
+        // fewer challenges allow smaller commitments
+        let few_challenges = PcsParams {
+            num_challenges: 8,
+            ..params
+        };
+        assert_eq!(min_evaluation_len(&few_challenges), 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot commit to 1 bytes")]
+    fn test_commit_rejects_one_byte() {
+        commit(&[1], &PcsParams::default());
+    }
+
+    #[test]
+    fn test_commit_checked() {
+        let params = PcsParams::default();
+        let evaluations = vec![1; 1 << 12];
+        assert_eq!(
+            commit_checked(&evaluations, &params),
+            Ok(commit(&evaluations, &params))
+        );
+        assert_eq!(
+            commit_checked(&evaluations[..3], &params),
+            Err(BiniusError::NotPowerOfTwo(24))
+        );
+        assert_eq!(
+            commit_checked(&evaluations[..4], &params),
+            Err(BiniusError::InvalidLength {
+                expected: 64,
+                actual: 32
+            })
+        );
+        // 2^6 bits: rows of 16 bits, 8 columns after extension, fewer than the 32 challenges
+        assert_eq!(
+            commit_checked(&evaluations[..8], &params),
+            Err(BiniusError::TooManyChallenges {
+                num_challenges: 32,
+                extended_row_length: 8
+            })
+        );
+        let bad_expansion = PcsParams {
+            expansion_factor: 3,
+            ..params
+        };
+        assert_eq!(
+            commit_checked(&evaluations, &bad_expansion),
+            Err(BiniusError::NotPowerOfTwo(3))
+        );
+    }
+
+    #[test]
+    fn test_binius_error_paths() {
+        // the step errors convert with ?, so the whole flow returns BiniusError
+        fn round_trip(
+            evaluations: &[u8],
+            point: &Vec<u128>,
+            tamper: impl Fn(&mut Proof),
+        ) -> Result<(), BiniusError> {
+            let params = PcsParams::default();
+            let commitment = commit_checked(evaluations, &params)?;
+            let mut proof = prove_checked(&commitment, evaluations, point, &params)?;
+            tamper(&mut proof);
+            verifier(&commitment, &proof, point, &params)?;
+            Ok(())
+        }
+        let evaluations: Vec<u8> = (0..1u32 << 12).map(|i| (i * 11) as u8).collect();
+        let point: Vec<u128> = (1..=15).collect();
+
+        assert_eq!(round_trip(&evaluations, &point, |_| {}), Ok(()));
+        assert_eq!(
+            round_trip(&evaluations, &point[..14].to_vec(), |_| {}),
+            Err(BiniusError::InvalidLength {
+                expected: 15,
+                actual: 14
+            })
+        );
+        assert_eq!(
+            round_trip(&evaluations, &point, |proof| proof.branches[1][0][0] ^= 1),
+            Err(BiniusError::BranchMismatch { index: 1 })
+        );
+        // a tampered opened column no longer hashes to the leaf of its challenge
+        assert_eq!(
+            round_trip(&evaluations, &point, |proof| proof.columns[0][0] =
+                proof.columns[0][0] + BinaryFieldElement16::new(1)),
+            Err(BiniusError::BranchMismatch { index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_verifier_rejects_missing_grinding_nonce() {
         let evaluations = vec![1; 1 << 20];
-        let commitment = commit(&evaluations);
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
         let evaluation_point = vec![1; 23];
-        let proof = prove(&commitment, &evaluations, &evaluation_point);
-        assert!(verifier(&commitment, &proof, &evaluation_point));
+        let mut proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+        let statement = query_transcript::<Sha256Hasher>(
+            &commitment.root,
+            &proof.evaluation_point,
+            &proof.eval,
+            &proof.t_prime,
+            None,
+        );
+        assert!(statement.check_grind(proof.grinding_nonce.unwrap(), params.grinding_bits));
+        // the nonce covers the statement, not only the root
+        assert!(!check_grind_with_hasher::<Sha256Hasher>(
+            &commitment.root,
+            proof.grinding_nonce.unwrap(),
+            params.grinding_bits
+        ));
+
+        proof.grinding_nonce = None;
+        assert_eq!(
+            verifier(&commitment, &proof, &evaluation_point, &params),
+            Err(VerifyError::GrindingMismatch)
+        );
+
+        // with grinding disabled the proof carries no nonce, which params that grind reject
+        let no_grinding = PcsParams {
+            grinding_bits: 0,
+            ..params
+        };
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &no_grinding);
+        assert_eq!(proof.grinding_nonce, None);
+        assert!(verifier(&commitment, &proof, &evaluation_point, &no_grinding).is_ok());
+        assert_eq!(
+            verifier(&commitment, &proof, &evaluation_point, &params),
+            Err(VerifyError::GrindingMismatch)
+        );
+    }
+
+    #[test]
+    fn test_verifier_rejects_tampered_proof() {
+        let evaluations = vec![1; 1 << 16];
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point = vec![1; 19];
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+
+        let mut tampered = prove(&commitment, &evaluations, &evaluation_point, &params);
+        tampered.branches[0][0][0] ^= 1;
+        assert_eq!(
+            verifier(&commitment, &tampered, &evaluation_point, &params),
+            Err(VerifyError::BranchMismatch { index: 0 })
+        );
+
+        tampered = proof;
+        tampered.columns.pop();
+        assert_eq!(
+            verifier(&commitment, &tampered, &evaluation_point, &params),
+            Err(VerifyError::MalformedProof)
+        );
+    }
+
+    #[test]
+    fn test_challenges_depend_on_evaluation_point() {
+        let root = vec![7; 32];
+        let eval = vec![0; 8];
+        let t_prime = vec![vec![1, 0, 0, 0, 0, 0, 0, 0]];
+        let challenges = |evaluation_point: &Vec<u128>| {
+            query_transcript::<Sha256Hasher>(&root, evaluation_point, &eval, &t_prime, Some(3))
+                .distinct_challenge_indices(2048, NUM_CHALLENGES)
+                .unwrap()
+        };
+        assert_ne!(challenges(&vec![1; 23]), challenges(&vec![2; 23]));
+    }
+
+    #[test]
+    fn test_verifiers_reject_other_point() {
+        let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 11 + 3) as u8).collect();
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point: Vec<u128> = (3..22).collect();
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+        let mut other_point = evaluation_point.clone();
+        other_point[5] ^= 1;
+
+        // a valid proof is only accepted at the point it was made at
+        assert!(verifier(&commitment, &proof, &evaluation_point, &params).is_ok());
+        let rejected = Err(VerifyError::PointMismatch);
+        assert_eq!(
+            verifier(&commitment, &proof, &other_point, &params),
+            rejected
+        );
+        assert_eq!(
+            verify_algebraic(&commitment, &proof, &other_point, &params),
+            rejected
+        );
+        assert_eq!(
+            verify_standalone(&commitment.root, &proof, &other_point, &params),
+            rejected
+        );
+        let context: VerifierContext = VerifierContext::new(&commitment, &params).unwrap();
+        assert_eq!(
+            verify_with_context(&context, &proof, &other_point),
+            rejected
+        );
+        let fiat_shamir = FiatShamirChallenger::<Sha256Hasher>::new();
+        assert_eq!(
+            verifier_with_challenger(&commitment, &proof, &other_point, &params, &fiat_shamir),
+            rejected
+        );
+        assert_eq!(
+            verifier_with_challenges(&commitment, &proof, &other_point, &[0, 1], &params),
+            rejected
+        );
+        // a longer point is not a padding of the proof's
+        let mut longer = evaluation_point.clone();
+        longer.push(0);
+        assert_eq!(verifier(&commitment, &proof, &longer, &params), rejected);
+
+        // the point of a commit_padded proof is compared after the same zero padding
+        let padded = commit_padded(&evaluations[..12], &params);
+        let short_point: Vec<u128> = (0..7).collect();
+        let padded_proof = prove(&padded, &evaluations[..12], &short_point, &params);
+        assert!(verifier(&padded, &padded_proof, &short_point, &params).is_ok());
+        assert!(verifier(
+            &padded,
+            &padded_proof,
+            &padded_proof.evaluation_point,
+            &params
+        )
+        .is_ok());
+        assert_eq!(
+            verifier(&padded, &padded_proof, &(1..8).collect(), &params),
+            rejected
+        );
+    }
+
+    #[test]
+    fn test_fixed_challenger() {
+        let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 5 + 9) as u8).collect();
+        let commitment = commit(&evaluations, &PcsParams::default());
+        let last = (commitment.columns.len() - 1) as u16;
+        let indices: Vec<u16> = vec![0, 1, 200, last];
+        let params = PcsParams {
+            num_challenges: indices.len(),
+            ..PcsParams::default()
+        };
+        let evaluation_point: Vec<u128> = (6..25).collect();
+
+        // the first and the last column are opened and verified
+        let fixed = FixedChallenger {
+            indices: indices.clone(),
+        };
+        let proof = prove_with_challenger(
+            &commitment,
+            &evaluations,
+            &evaluation_point,
+            &params,
+            &fixed,
+        );
+        for (i, &c) in indices.iter().enumerate() {
+            assert_eq!(proof.columns[i], commitment.columns[c as usize]);
+        }
+        assert!(
+            verifier_with_challenger(&commitment, &proof, &evaluation_point, &params, &fixed)
+                .is_ok()
+        );
+        // the transcript draws other columns
+        assert!(verifier(&commitment, &proof, &evaluation_point, &params).is_err());
+
+        // FiatShamirChallenger is what prove and verifier use
+        let fiat_shamir = FiatShamirChallenger::<Sha256Hasher>::new();
+        let proof = prove_with_challenger(
+            &commitment,
+            &evaluations,
+            &evaluation_point,
+            &params,
+            &fiat_shamir,
+        );
+        assert_eq!(
+            proof,
+            prove(&commitment, &evaluations, &evaluation_point, &params)
+        );
+        assert!(verifier_with_challenger(
+            &commitment,
+            &proof,
+            &evaluation_point,
+            &params,
+            &fiat_shamir
+        )
+        .is_ok());
+
+        // a fixed set must fit the params and the columns
+        let statement = QueryStatement {
+            root: &commitment.root,
+            evaluation_point: &proof.evaluation_point,
+            eval: &proof.eval,
+            t_prime: &proof.t_prime,
+            grinding_nonce: proof.grinding_nonce,
+        };
+        assert_eq!(
+            fixed.challenges(&statement, commitment.columns.len(), 3),
+            Err(ChallengeError::FixedCount {
+                expected: 3,
+                actual: 4
+            })
+        );
+        let out_of_range = FixedChallenger {
+            indices: vec![0, 1, 2, last + 1],
+        };
+        assert_eq!(
+            verifier_with_challenger(
+                &commitment,
+                &proof,
+                &evaluation_point,
+                &params,
+                &out_of_range
+            ),
+            Err(VerifyError::Challenges(ChallengeError::OutOfRange {
+                challenge: last + 1,
+                extended_row_length: commitment.columns.len()
+            }))
+        );
+    }
+
+    #[test]
+    fn test_required_point_len() {
+        assert_eq!(required_point_len(&vec![0u8; 1 << 20]), 23);
+        assert_eq!(required_point_len(&vec![0u8; 1 << 16]), 19);
+    }
+
+    #[test]
+    fn test_column_accessors() {
+        let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 13 + 1) as u8).collect();
+        let commitment = commit(&evaluations, &PcsParams::default());
+        let last = commitment.columns.len() - 1;
+        let indices: Vec<u16> = vec![0, 42, last as u16];
+        let params = PcsParams {
+            num_challenges: indices.len(),
+            ..PcsParams::default()
+        };
+        let proof = prove_with_challenger(
+            &commitment,
+            &evaluations,
+            &(0..19).collect(),
+            &params,
+            &FixedChallenger {
+                indices: indices.clone(),
+            },
+        );
+
+        // the bytes the verifier rebuilds from the opened columns are the committed ones
+        for (i, &c) in indices.iter().enumerate() {
+            let packed_column: Vec<u8> = proof.columns[i].iter().copied().collect();
+            assert_eq!(commitment.column_bytes(c as usize), packed_column);
+            assert_eq!(
+                commitment.column_elements(c as usize),
+                &proof.columns[i][..]
+            );
+        }
+        assert_eq!(
+            commitment.column_bytes(last).len(),
+            commitment.columns[last].len() * 2
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "column 512 is out of the 512 columns of the commitment")]
+    fn test_column_bytes_out_of_range() {
+        let evaluations: Vec<u8> = vec![3; 1 << 16];
+        commit(&evaluations, &PcsParams::default()).column_bytes(512);
+    }
+
+    #[test]
+    fn test_verify_eval_only() {
+        let evaluations: Vec<u8> = (0..1u32 << 16).map(|i| (i * 17 + 3) as u8).collect();
+        let params = PcsParams::default();
+        let commitment = commit(&evaluations, &params);
+        let evaluation_point: Vec<u128> = (3..22).collect();
+        let proof = prove(&commitment, &evaluations, &evaluation_point, &params);
+        assert_eq!(verify_eval_only(&proof, &evaluation_point, &params), Ok(()));
+
+        let mut bad_eval = proof.clone();
+        bad_eval.eval[0] ^= 1;
+        assert_eq!(
+            verify_eval_only(&bad_eval, &evaluation_point, &params),
+            Err(VerifyError::EvalMismatch)
+        );
+        let mut other_point = evaluation_point.clone();
+        other_point[0] ^= 1;
+        assert_eq!(
+            verify_eval_only(&proof, &other_point, &params),
+            Err(VerifyError::EvalMismatch)
+        );
+        let mut short_t_prime = proof.clone();
+        short_t_prime.t_prime.pop();
+        assert_eq!(
+            verify_eval_only(&short_t_prime, &evaluation_point, &params),
+            Err(VerifyError::MalformedProof)
+        );
+
+        // the columns are not authenticated: a tampered one still passes, only verifier rejects it
+        let mut bad_column = proof.clone();
+        bad_column.columns[0][0] = bad_column.columns[0][0] + BinaryFieldElement16::new(1);
+        assert_eq!(
+            verify_eval_only(&bad_column, &evaluation_point, &params),
+            Ok(())
+        );
+        assert!(verifier(&commitment, &bad_column, &evaluation_point, &params).is_err());
     }
 }
@@ -4,27 +4,74 @@
 //! The operations include packing the evaluations into rows, extending the rows, computing the t_prime, and computing the evaluation.
 //! In detail, the functions in this module are:
 //! 1. choose_row_length_and_count: Choose the row length and row count based on the log of the evaluation count.
-//! 2. pack_rows: Pack the evaluations into rows.
-//! 3. extend_rows: Extend the rows using the Fast-Fourier extension.
-//! 4. evaluation_tensor_product: Compute the tensor product of the evaluations.
-//! 5. xor_along_axis: Perform XOR along rows or columns.
+//! 2. pack_rows: Pack the evaluations into rows (pack packs one row in a given BitOrder, pack_rows_checked and pack_row_checked validate the arguments).
+//! 3. extend_rows: Extend the rows using the Fast-Fourier extension (extend_rows_parallel extends them across threads).
+//! 4. evaluation_tensor_product: Compute the tensor product of the evaluations (evaluation_tensor_product_parallel doubles it across threads).
+//! 5. xor_along_axis: Perform XOR along rows or columns (try_xor_along_axis returns a BiniusError for a bad axis, xor_along_axis_parallel splits large matrices across threads, xor_along_axis_4d reduces 4D tensors).
 //! 6. transpose_bits: Transpose the matrix in the bit-level.
-//! 7. transpose: Transpose the matrix
-//! 8. computed_tprimes: Compute the t_prime.
-//! 9. multisubset: Compute the multisubset sum.
+//! 7. transpose: Transpose the matrix (transpose_matrix transposes a matrix of any element type, transpose_parallel tiles it across threads).
+//! 8. computed_tprimes: Compute the t_prime (computed_tprimes_parallel computes its columns across threads, computed_tprimes_into reuses caller-owned buffers).
+//! 9. multisubset: Compute the multisubset sum (multisubset_windowed takes the window size).
 //! 10. transpose_3d: Transpose the 3D matrix.
+//! 11. evaluations_to_coeffs: Convert the packed rows into their polynomial coefficients (coeffs_to_evaluations converts back).
 
-use super::binary_field16::{big_mul, int_to_bigbin, uint16s_to_bits};
+use super::binary_field16::{big_mul, int_to_bigbin, uint16s_to_bits, BitOrder};
 // not use cache
 // use super::binary_ntt::extend;
 // use cache
 use super::binary_field16::BinaryFieldElement16 as B16;
-use super::binary_ntt_cache::{extend, WiEvalCache};
+use super::binary_ntt_cache::{additive_ntt_inplace, extend, inv_additive_ntt, WiEvalCache};
+use super::error::BiniusError;
+use rayon::prelude::*;
 use std::convert::TryFrom;
+use std::fmt;
+use std::ops::BitXor;
+
+// below this many rows, spawning rayon tasks costs more than the extensions themselves
+const PARALLEL_EXTEND_THRESHOLD: usize = 16;
+// below this many elements, xor_along_axis is faster than splitting the matrix across workers
+const PARALLEL_XOR_THRESHOLD: usize = 1 << 14;
+// below this many bits (rows_as_bits_transpose rows * bits per row), computed_tprimes stays on one thread
+const PARALLEL_TPRIME_THRESHOLD: usize = 1 << 12;
+// below this many tensor entries, a doubling step of evaluation_tensor_product stays on one thread
+const PARALLEL_TENSOR_THRESHOLD: usize = 1 << 10;
+// below this many elements, transpose_parallel falls back to transpose
+const PARALLEL_TRANSPOSE_THRESHOLD: usize = 1 << 14;
+// the side of the square tiles of transpose_parallel, 32 x 32 B16s are 2 KiB and stay in L1
+const TRANSPOSE_TILE: usize = 32;
+
+// the smallest supported log2 of the evaluation count: 2^6 bits are 4 rows of one 16-bit element,
+// below that a row is shorter than a packed element
+pub const MIN_LOG_EVALUATION_COUNT: usize = 6;
+
+/** Why the evaluations cannot be laid out as a matrix */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    // fewer than 2^MIN_LOG_EVALUATION_COUNT bits, pad them first (pcs::commit_padded)
+    TooFewEvaluations { log_evaluation_count: usize },
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::TooFewEvaluations {
+                log_evaluation_count,
+            } => write!(
+                f,
+                "2^{} evaluation bits are fewer than the minimum 2^{}, pad the evaluations",
+                log_evaluation_count, MIN_LOG_EVALUATION_COUNT
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
 
 /** transfrom the evaluations into a specific matrix
 
 transform the evaluations into a matrix with row length = 16 bits
+    the smallest supported input is 2^MIN_LOG_EVALUATION_COUNT bits (8 bytes), this panics below it,
+    see try_choose_row_length_and_count
 
 Args:
     evaluations: log(size), size = bits of evaluations
@@ -33,17 +80,67 @@ Returns:
     log(row length), log(row count), row length, row count
  */
 pub fn choose_row_length_and_count(log_evaluation_count: usize) -> (usize, usize, usize, usize) {
+    try_choose_row_length_and_count(log_evaluation_count).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/** choose_row_length_and_count, with an error instead of a panic for too small inputs */
+pub fn try_choose_row_length_and_count(
+    log_evaluation_count: usize,
+) -> Result<(usize, usize, usize, usize), LayoutError> {
+    if log_evaluation_count < MIN_LOG_EVALUATION_COUNT {
+        return Err(LayoutError::TooFewEvaluations {
+            log_evaluation_count,
+        });
+    }
     let log_row_length = (log_evaluation_count + 2) / 2;
     let log_row_count = (log_evaluation_count - 1) / 2;
     let row_length = 1 << log_row_length;
     let row_count = 1 << log_row_count;
-    (log_row_length, log_row_count, row_length, row_count)
+    Ok((log_row_length, log_row_count, row_length, row_count))
+}
+
+/** pack bytes into BinaryFieldElement16s
+
+every packing_factor bits of the row become one element, the element's bit i is the row's i-th bit
+    of the chunk under bit_order, Msb0 bytes are bit-reversed first so both orders give the same
+    element for the same sequence of bits
+
+Args:
+    bytes: the row, row_length / 8 bytes
+    row_length: the number of bits in the row
+    packing_factor: the number of bits in a unit16
+    bit_order: how bytes holds its bits
+
+Returns:
+    the row as a list of BinaryFieldElement16s, panics with the PackError message for a packing_factor other than 16
+*/
+pub fn pack(
+    bytes: &[u8],
+    row_length: usize,
+    packing_factor: usize,
+    bit_order: BitOrder,
+) -> Vec<B16> {
+    if packing_factor != 16 {
+        panic!("{}", PackError::PackingFactor(packing_factor));
+    }
+    let bytes_per_element = packing_factor / 8;
+    bytes[..row_length / 8]
+        .chunks(bytes_per_element)
+        .map(|chunk| {
+            let chunk: [u8; 2] = match bit_order {
+                BitOrder::Lsb0 => chunk.try_into().unwrap(),
+                BitOrder::Msb0 => [chunk[0].reverse_bits(), chunk[1].reverse_bits()],
+            };
+            B16::new(u16::from_le_bytes(chunk))
+        })
+        .collect()
 }
 
 /** row packing
 
 perform packing for each row, packing every 16 bits into a unit16, so each row is a list of uint16s
     and the 16 is controlled by packing_factor, and to make later calculation easier, we use BinaryFieldElement16s to represent the unit16s
+    the evaluations are Lsb0, see BitOrder
 
 Args:
     evaluations: the evaluations
@@ -54,46 +151,176 @@ Args:
 Returns:
     a list of rows, each row is a list of BinaryFieldElement16s
  */
+// Original implementation
+// pub fn pack_rows(
+//     evaluations: &[u8],
+//     row_count: usize,
+//     row_length: usize,
+//     packing_factor: usize,
+// ) -> Vec<Vec<B16>> {
+//     let mut rows = Vec::with_capacity(row_count);
+//     let mut packed_row_length = row_length / packing_factor;
+//     for i in 0..row_count {
+//         let mut packed_row = Vec::with_capacity(packed_row_length);
+//         for j in 0..packed_row_length {
+//             packed_row.push(B16::new(u16::from_le_bytes(
+//                 evaluations[i * row_length / 8 + j * packing_factor / 8
+//                     ..i * row_length / 8 + (j + 1) * packing_factor / 8]
+//                     .try_into()
+//                     .unwrap(),
+//             )));
+//         }
+//         rows.push(packed_row);
+//     }
+//     rows
+// }
 pub fn pack_rows(
     evaluations: &[u8],
     row_count: usize,
     row_length: usize,
     packing_factor: usize,
 ) -> Vec<Vec<B16>> {
-    let mut rows = Vec::with_capacity(row_count);
-    let mut packed_row_length = row_length / packing_factor;
-
-    // use B16 to represent the unit16s
-    for i in 0..row_count {
-        let mut packed_row = Vec::with_capacity(packed_row_length);
-
-        for j in 0..packed_row_length {
-            // let flipped: Vec<u8>= evaluations[i * row_length /8+ j * packing_factor/8..i * row_length/8 +(j + 1) * packing_factor/8].iter().map(|&byte|byte.reverse_bits()).collect();
-            // packed_row.push(B16::new(u16::from_le_bytes(flipped.try_into().unwrap())));
-            packed_row.push(B16::new(u16::from_le_bytes(
-                evaluations[i * row_length / 8 + j * packing_factor / 8
-                    ..i * row_length / 8 + (j + 1) * packing_factor / 8]
-                    .try_into()
-                    .unwrap(),
-            )));
+    evaluations
+        .chunks(row_length / 8)
+        .take(row_count)
+        .map(|row| pack(row, row_length, packing_factor, BitOrder::Lsb0))
+        .collect()
+}
+
+/** Why pack_rows_checked cannot pack the evaluations */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackError {
+    // pack reads packing_factor / 8 bytes into a BinaryFieldElement16, so only 16 fits
+    PackingFactor(usize),
+    // a row does not split into whole packed elements
+    RowLength {
+        row_length: usize,
+        packing_factor: usize,
+    },
+    // the evaluations are shorter than row_count rows
+    TooFewEvaluations {
+        needed: usize,
+        available: usize,
+    },
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackError::PackingFactor(packing_factor) => write!(
+                f,
+                "packing_factor must be 16, the bits of a BinaryFieldElement16, got {}",
+                packing_factor
+            ),
+            PackError::RowLength {
+                row_length,
+                packing_factor,
+            } => write!(
+                f,
+                "row length {} is not a multiple of packing_factor {}",
+                row_length, packing_factor
+            ),
+            PackError::TooFewEvaluations { needed, available } => write!(
+                f,
+                "packing the rows needs {} evaluation bytes, got {}",
+                needed, available
+            ),
         }
-        rows.push(packed_row);
     }
-    rows
+}
+
+impl std::error::Error for PackError {}
+
+/** pack_rows, with an error instead of an out-of-bounds slice
+
+Args:
+    evaluations: the evaluations
+    row_count: number of rows
+    row_length: the number of bits in a row
+    packing_factor: the number of bits in a unit16, 16
+
+Returns:
+    the rows pack_rows returns, or the PackError describing the mismatched arguments
+*/
+pub fn pack_rows_checked(
+    evaluations: &[u8],
+    row_count: usize,
+    row_length: usize,
+    packing_factor: usize,
+) -> Result<Vec<Vec<B16>>, PackError> {
+    check_packing(row_length, packing_factor)?;
+    let needed = row_count * row_length / 8;
+    if evaluations.len() < needed {
+        return Err(PackError::TooFewEvaluations {
+            needed,
+            available: evaluations.len(),
+        });
+    }
+    Ok(pack_rows(
+        evaluations,
+        row_count,
+        row_length,
+        packing_factor,
+    ))
 }
 
 // similar logic as above, but return type is Vec<B16> instead of Vec<Vec<B16>>
 // and the inputs are all Vec<u8>
+// the verifier packs the rows of transpose_bits, which are Msb0, so the bytes are bit-reversed
+// to give the elements the committer's Lsb0 bit order
+// Original implementation
+// pub fn pack_row(evaluations: &[u8], row_length: usize, packing_factor: usize) -> Vec<B16> {
+//     let mut packed_row = Vec::with_capacity(row_length / packing_factor);
+//     for j in 0..row_length / packing_factor {
+//         let flipped: Vec<u8> = evaluations[j * packing_factor / 8..(j + 1) * packing_factor / 8]
+//             .iter()
+//             .map(|&byte| byte.reverse_bits())
+//             .collect();
+//         packed_row.push(B16::new(u16::from_le_bytes(flipped.try_into().unwrap())));
+//     }
+//     packed_row
+// }
 pub fn pack_row(evaluations: &[u8], row_length: usize, packing_factor: usize) -> Vec<B16> {
-    let mut packed_row = Vec::with_capacity(row_length / packing_factor);
-    for j in 0..row_length / packing_factor {
-        let flipped: Vec<u8> = evaluations[j * packing_factor / 8..(j + 1) * packing_factor / 8]
-            .iter()
-            .map(|&byte| byte.reverse_bits())
-            .collect();
-        packed_row.push(B16::new(u16::from_le_bytes(flipped.try_into().unwrap())));
+    pack(evaluations, row_length, packing_factor, BitOrder::Msb0)
+}
+
+/** pack_row, with an error instead of a panic
+
+Args:
+    evaluations: the row, row_length / 8 bytes
+    row_length: the number of bits in the row
+    packing_factor: the number of bits in a unit16, 16
+
+Returns:
+    the row pack_row returns, or the PackError describing the mismatched arguments
+*/
+pub fn pack_row_checked(
+    evaluations: &[u8],
+    row_length: usize,
+    packing_factor: usize,
+) -> Result<Vec<B16>, PackError> {
+    check_packing(row_length, packing_factor)?;
+    if evaluations.len() < row_length / 8 {
+        return Err(PackError::TooFewEvaluations {
+            needed: row_length / 8,
+            available: evaluations.len(),
+        });
+    }
+    Ok(pack_row(evaluations, row_length, packing_factor))
+}
+
+// the packers only produce BinaryFieldElement16s, other packing factors need another element type
+fn check_packing(row_length: usize, packing_factor: usize) -> Result<(), PackError> {
+    if packing_factor != 16 {
+        return Err(PackError::PackingFactor(packing_factor));
+    }
+    if row_length % packing_factor != 0 {
+        return Err(PackError::RowLength {
+            row_length,
+            packing_factor,
+        });
     }
-    packed_row
+    Ok(())
 }
 
 /** Fast-Fourier extend the rows
@@ -116,6 +343,84 @@ pub fn extend_rows(rows: &Vec<Vec<B16>>, expansion_factor: usize) -> Vec<Vec<B16
         .collect()
 }
 
+/** Fast-Fourier extend the rows in parallel
+
+same as extend_rows, but each row is extended on a rayon worker,
+    rows are independent and the Wi-eval cache is shared read-only, so the output is identical to extend_rows
+    small inputs (fewer than PARALLEL_EXTEND_THRESHOLD rows) fall back to extend_rows
+
+Args:
+    rows: the packed rows, each row is a list of uint16s
+    expansion_factor: EXPANSION_FACTOR, after extension, the row length will be row_length * EXPANSION_FACTOR
+
+Returns:
+    the extended rows, each row is a list of uint16s
+ */
+pub fn extend_rows_parallel(rows: &Vec<Vec<B16>>, expansion_factor: usize) -> Vec<Vec<B16>> {
+    if rows.len() < PARALLEL_EXTEND_THRESHOLD {
+        return extend_rows(rows, expansion_factor);
+    }
+    rows.par_iter()
+        .map(|row| extend(row, expansion_factor))
+        .collect()
+}
+
+/** Convert the committed evaluations into the coefficients of their rows
+
+the evaluations are packed into rows as commit does (choose_row_length_and_count, pack_rows),
+    each packed row is the evaluation of a polynomial of degree < packed_row_length on the points 0..packed_row_length,
+    inv_additive_ntt gives its coefficients in the novel polynomial basis, the basis additive_ntt evaluates,
+    these are the coefficients extend_rows runs additive_ntt on over the larger domain
+    the monomial coefficients of the multilinear polynomial in the bits are a different basis,
+    a cross-check against a multilinear library still has to change the basis
+
+Args:
+    evaluations: the evaluations, a power-of-two number of bytes, at least 2^MIN_LOG_EVALUATION_COUNT bits
+
+Returns:
+    the coefficients of every row, row after row, row_count * row_length / 16 elements
+*/
+pub fn evaluations_to_coeffs(evaluations: &[u8]) -> Vec<B16> {
+    let (_, row_count, row_length) = coeffs_layout(evaluations.len() * 8);
+    pack_rows(evaluations, row_count, row_length, 16)
+        .iter()
+        .flat_map(|row| inv_additive_ntt(row, 0))
+        .collect()
+}
+
+/** Convert the coefficients of the rows back into the committed evaluations, the inverse of evaluations_to_coeffs
+
+Args:
+    coeffs: the coefficients of every row, row after row, as evaluations_to_coeffs returns them
+
+Returns:
+    the evaluations, 2 bytes per coefficient
+*/
+pub fn coeffs_to_evaluations(coeffs: &[B16]) -> Vec<u8> {
+    let (packed_row_length, _, _) = coeffs_layout(coeffs.len() * 16);
+    coeffs
+        .chunks(packed_row_length)
+        .flat_map(|row| {
+            let mut row = row.to_vec();
+            additive_ntt_inplace(&mut row, 0);
+            row
+        })
+        .flat_map(|element| element.value.to_le_bytes())
+        .collect()
+}
+
+// packed row length, row count and row length (in bits) of evaluation_bits committed bits
+fn coeffs_layout(evaluation_bits: usize) -> (usize, usize, usize) {
+    assert!(
+        evaluation_bits.is_power_of_two(),
+        "the evaluations must be a power-of-two number of bits, got {}",
+        evaluation_bits
+    );
+    let (_, _, row_length, row_count) =
+        choose_row_length_and_count(evaluation_bits.trailing_zeros() as usize);
+    (row_length / 16, row_count, row_length)
+}
+
 /** calculate the tensor product of evaluations
 
 all possible results of walking through pt and at each step taking either coord or 1-coord
@@ -178,6 +483,40 @@ pub fn evaluation_tensor_product(eval_point: &Vec<u128>) -> Vec<Vec<u16>> {
     o
 }
 
+/** calculate the tensor product of evaluations in parallel
+
+same result as evaluation_tensor_product, each doubling step multiplies and combines the entries of o
+    on rayon workers, the layout stays new_o followed by o_times_coord.
+    steps on fewer than PARALLEL_TENSOR_THRESHOLD entries stay on one thread
+
+Args:
+    evaluation_point: the evaluation point, a list of uint128s
+
+Returns:
+    field element: the result of the tensor product, a 2^k-long vector of Vector(u16)
+*/
+pub fn evaluation_tensor_product_parallel(eval_point: &Vec<u128>) -> Vec<Vec<u16>> {
+    let mut o = vec![int_to_bigbin(1)];
+
+    for coord in eval_point {
+        let int_bin = int_to_bigbin(*coord);
+        let combine = |x: &Vec<u16>| {
+            let y = big_mul(x, &int_bin);
+            let combined: Vec<u16> = x.iter().zip(y.iter()).map(|(a, b)| a ^ b).collect();
+            (combined, y)
+        };
+        let (mut new_o, o_times_coord): (Vec<Vec<u16>>, Vec<Vec<u16>>) =
+            if o.len() < PARALLEL_TENSOR_THRESHOLD {
+                o.iter().map(combine).unzip()
+            } else {
+                o.par_iter().map(combine).unzip()
+            };
+        new_o.extend(o_times_coord);
+        o = new_o;
+    }
+    o
+}
+
 /** XOR along axis
 
 XOR along rows or columns, if axis = 0, then XOR along rows, if axis = 1, then XOR along columns
@@ -251,8 +590,82 @@ pub fn xor_along_axis(values: &[Vec<u16>], axis: usize) -> Vec<u16> {
     result
 }
 
-fn xor_along_axis_4d(values: &Vec<Vec<Vec<Vec<u16>>>>, axis: usize) -> Vec<Vec<Vec<u16>>> {
-    let mut result: Vec<Vec<Vec<u16>>> = Vec::new();
+/** xor_along_axis, with an error instead of a panic for an empty matrix or an axis other than 0 and 1 */
+pub fn try_xor_along_axis(values: &[Vec<u16>], axis: usize) -> Result<Vec<u16>, BiniusError> {
+    if axis > 1 {
+        return Err(BiniusError::UnsupportedAxis(axis));
+    }
+    if values.is_empty() || values[0].is_empty() {
+        return Err(BiniusError::InvalidLength {
+            expected: 1,
+            actual: 0,
+        });
+    }
+    Ok(xor_along_axis(values, axis))
+}
+
+/** XOR along the axis in parallel
+
+same result as xor_along_axis, axis 1 reduces every row on a rayon worker,
+    axis 0 reduces chunks of rows on the workers, then XORs the partial results together.
+    XOR is associative and commutative, so the chunking does not change the result.
+    small matrices (fewer than PARALLEL_XOR_THRESHOLD elements) fall back to xor_along_axis
+
+Args:
+    values: the matrix
+    axis: the axis, 0 or 1
+
+Returns:
+    the result of XOR along the axis
+*/
+pub fn xor_along_axis_parallel(values: &[Vec<u16>], axis: usize) -> Vec<u16> {
+    let (rows, cols) = (values.len(), values[0].len());
+    if rows * cols < PARALLEL_XOR_THRESHOLD {
+        return xor_along_axis(values, axis);
+    }
+
+    match axis {
+        0 => {
+            let chunk_rows = (rows / rayon::current_num_threads()).max(1);
+            values
+                .par_chunks(chunk_rows)
+                .map(|chunk| xor_along_axis(chunk, 0))
+                .reduce(
+                    || vec![0u16; cols],
+                    |mut acc, partial| {
+                        for (res, val) in acc.iter_mut().zip(partial.iter()) {
+                            *res ^= val;
+                        }
+                        acc
+                    },
+                )
+        }
+        1 => values
+            .par_iter()
+            .map(|row| row.iter().fold(0u16, |acc, &val| acc ^ val))
+            .collect(),
+        _ => panic!("Unsupported axis"),
+    }
+}
+
+/** XOR along an axis of a 4D tensor
+
+the 4D counterpart of xor_along_axis: axis a XORs the entries that only differ in index a,
+    so the result is the tensor without dimension a, e.g. for axis 2
+    result[i][j][k] = values[i][j][0][k] ^ values[i][j][1][k] ^ ...
+
+Args:
+    values: the tensor, every dimension non-empty
+    axis: the axis, 0 to 3
+
+Returns:
+    the 3D result of XOR along the axis
+*/
+pub fn xor_along_axis_4d<T: Copy + BitXor<Output = T>>(
+    values: &Vec<Vec<Vec<Vec<T>>>>,
+    axis: usize,
+) -> Vec<Vec<Vec<T>>> {
+    let mut result: Vec<Vec<Vec<T>>> = Vec::new();
     if axis == 0 {
         for i in 0..values[0].len() {
             let mut row = Vec::new();
@@ -261,7 +674,7 @@ fn xor_along_axis_4d(values: &Vec<Vec<Vec<Vec<u16>>>>, axis: usize) -> Vec<Vec<V
                 for k in 0..values[0][0][0].len() {
                     let mut res = values[0][i][j][k];
                     for l in 1..values.len() {
-                        res ^= values[l][i][j][k];
+                        res = res ^ values[l][i][j][k];
                     }
                     col.push(res);
                 }
@@ -277,7 +690,7 @@ fn xor_along_axis_4d(values: &Vec<Vec<Vec<Vec<u16>>>>, axis: usize) -> Vec<Vec<V
                 for k in 0..values[0][0][0].len() {
                     let mut res = values[i][0][j][k];
                     for l in 1..values[0].len() {
-                        res ^= values[i][l][j][k];
+                        res = res ^ values[i][l][j][k];
                     }
                     col.push(res);
                 }
@@ -293,7 +706,7 @@ fn xor_along_axis_4d(values: &Vec<Vec<Vec<Vec<u16>>>>, axis: usize) -> Vec<Vec<V
                 for k in 0..values[0][0][0].len() {
                     let mut res = values[i][j][0][k];
                     for l in 1..values[0][0].len() {
-                        res ^= values[i][j][l][k];
+                        res = res ^ values[i][j][l][k];
                     }
                     col.push(res);
                 }
@@ -309,7 +722,7 @@ fn xor_along_axis_4d(values: &Vec<Vec<Vec<Vec<u16>>>>, axis: usize) -> Vec<Vec<V
                 for k in 0..values[0][0].len() {
                     let mut res = values[i][j][k][0];
                     for l in 1..values[0][0][0].len() {
-                        res ^= values[i][j][k][l];
+                        res = res ^ values[i][j][k][l];
                     }
                     col.push(res);
                 }
@@ -327,6 +740,9 @@ fn xor_along_axis_4d(values: &Vec<Vec<Vec<Vec<u16>>>>, axis: usize) -> Vec<Vec<V
 
 ragarding the input as bits, transpose the bits
 
+bit order: output[j] packs column j MSB-first, row i is bit 7 - (i % 8) of byte i / 8,
+    when the row count is not a multiple of 8 the unused low bits of the last byte are 0
+
 Args:
     input: the input, a list of list of u8, representing the bits
 
@@ -374,15 +790,53 @@ pub fn transpose_bits(input: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
     for i in 0..rows {
         for j in 0..cols {
             // optimization trick: avoid using get_unchecked_mut() and directly use unsafe code
+            // MSB-first: (rows - 1 - i) % 8 only matched 7 - (i % 8) when rows was a multiple of 8
             unsafe {
                 *output.get_unchecked_mut(j).get_unchecked_mut(i / 8) |=
-                    (*input.get_unchecked(i).get_unchecked(j) as u8) << ((rows - 1 - i) % 8);
+                    (*input.get_unchecked(i).get_unchecked(j) as u8) << (7 - (i % 8));
             }
         }
     }
 
     output
 }
+
+/** undo transpose_bits
+
+Args:
+    input: the output of transpose_bits, each row packs a column MSB-first
+    rows: the number of rows given to transpose_bits
+
+Returns:
+    the bits, one u8 (0 or 1) per bit, rows x input.len()
+*/
+pub fn untranspose_bits(input: &[Vec<u8>], rows: usize) -> Vec<Vec<u8>> {
+    let mut output = vec![vec![0u8; input.len()]; rows];
+    for (j, column) in input.iter().enumerate() {
+        for i in 0..rows {
+            output[i][j] = (column[i / 8] >> (7 - (i % 8))) & 1;
+        }
+    }
+    output
+}
+/** transpose a matrix of any element type
+
+Args:
+    input: the input, a list of rows of the same length
+
+Returns:
+    the output, output[j][i] = input[i][j]
+*/
+pub fn transpose_matrix<T: Copy + Default>(input: &[Vec<T>]) -> Vec<Vec<T>> {
+    let mut output = vec![vec![T::default(); input.len()]; input[0].len()];
+    for i in 0..input.len() {
+        for j in 0..input[0].len() {
+            output[j][i] = input[i][j];
+        }
+    }
+    output
+}
+
 /** transpose the matrix
 
 different from the transpose_bits, this function transpose the matrix
@@ -393,13 +847,54 @@ Args:
 Returns:
     the output, a transposed list of list of B16
 */
+// Original implementation
+// pub fn transpose(input: &Vec<Vec<B16>>) -> Vec<Vec<B16>> {
+//     let mut output = vec![vec![B16::new(0); input.len()]; input[0].len()];
+//     for i in 0..input.len() {
+//         for j in 0..input[0].len() {
+//             output[j][i] = input[i][j];
+//         }
+//     }
+//     output
+// }
 pub fn transpose(input: &Vec<Vec<B16>>) -> Vec<Vec<B16>> {
-    let mut output = vec![vec![B16::new(0); input.len()]; input[0].len()];
-    for i in 0..input.len() {
-        for j in 0..input[0].len() {
-            output[j][i] = input[i][j];
-        }
+    transpose_matrix(input)
+}
+
+/** transpose the matrix in tiles, across threads
+
+each rayon task owns TRANSPOSE_TILE output rows (input columns) and fills them tile by tile,
+    reading TRANSPOSE_TILE consecutive elements of an input row at a time, so both sides of a tile
+    stay in cache instead of striding over the whole output for every input element,
+    small inputs (fewer than PARALLEL_TRANSPOSE_THRESHOLD elements) fall back to transpose
+
+Args:
+    input: the input, a list of rows of the same length
+
+Returns:
+    the output, the same as transpose
+*/
+pub fn transpose_parallel(input: &Vec<Vec<B16>>) -> Vec<Vec<B16>> {
+    let row_count = input.len();
+    let column_count = input[0].len();
+    if row_count * column_count < PARALLEL_TRANSPOSE_THRESHOLD {
+        return transpose(input);
     }
+    let mut output = vec![vec![B16::new(0); row_count]; column_count];
+    output
+        .par_chunks_mut(TRANSPOSE_TILE)
+        .enumerate()
+        .for_each(|(tile, output_rows)| {
+            let j0 = tile * TRANSPOSE_TILE;
+            for i0 in (0..row_count).step_by(TRANSPOSE_TILE) {
+                for i in i0..(i0 + TRANSPOSE_TILE).min(row_count) {
+                    let input_row = &input[i][j0..j0 + output_rows.len()];
+                    for (output_row, &element) in output_rows.iter_mut().zip(input_row) {
+                        output_row[i] = element;
+                    }
+                }
+            }
+        });
     output
 }
 
@@ -518,6 +1013,91 @@ pub fn computed_tprimes(
     t_prime
 }
 
+/** compute t_prime in parallel
+
+same result as computed_tprimes, each column j of t_prime only reads column j of row_combination,
+    so every column is computed by its own rayon task with its own accumulators, then the columns are
+    written back into t_prime. small inputs (fewer than PARALLEL_TPRIME_THRESHOLD bits) are computed on one thread
+
+Args:
+    rows_as_bits_transpose: the transposed rows, bit-packed
+    row_combination: the row combination, one field element (a list of uint16s) per row
+
+Returns:
+    t_prime
+*/
+pub fn computed_tprimes_parallel(
+    rows_as_bits_transpose: &Vec<Vec<u8>>,
+    row_combination: &Vec<Vec<u16>>,
+) -> Vec<Vec<u16>> {
+    let mut t_prime = Vec::new();
+    let mut scratch = Vec::new();
+    computed_tprimes_into(
+        &mut t_prime,
+        &mut scratch,
+        rows_as_bits_transpose,
+        row_combination,
+    );
+    t_prime
+}
+
+/** compute t_prime into caller-owned buffers
+
+same result as computed_tprimes_parallel, written into out, with scratch holding the accumulators
+    of every column. both are resized to the inputs, the rows they already hold keep their allocations,
+    so a prover or verifier handling many proofs of one size allocates them only once
+
+Args:
+    out: receives t_prime, one row per transposed row
+    scratch: the column accumulators, its content on return is unspecified
+    rows_as_bits_transpose: the transposed rows, bit-packed
+    row_combination: the row combination, one field element (a list of uint16s) per row
+*/
+pub fn computed_tprimes_into(
+    out: &mut Vec<Vec<u16>>,
+    scratch: &mut Vec<Vec<u16>>,
+    rows_as_bits_transpose: &Vec<Vec<u8>>,
+    row_combination: &Vec<Vec<u16>>,
+) {
+    let m = rows_as_bits_transpose.len();
+    let num_bits = rows_as_bits_transpose[0].len() * 8;
+    let k = row_combination[0].len();
+    reset_matrix(scratch, k, m);
+
+    let fill_column = |(j, column): (usize, &mut Vec<u16>)| {
+        for (i, res) in column.iter_mut().enumerate() {
+            for bit_pos in 0..num_bits {
+                let byte_index = bit_pos / 8;
+                let bit_index = 7 - (bit_pos % 8);
+                if (rows_as_bits_transpose[i][byte_index] >> bit_index) & 1 == 1 {
+                    *res ^= row_combination[bit_pos][j];
+                }
+            }
+        }
+    };
+    if m * num_bits < PARALLEL_TPRIME_THRESHOLD {
+        scratch.iter_mut().enumerate().for_each(fill_column);
+    } else {
+        scratch.par_iter_mut().enumerate().for_each(fill_column);
+    }
+
+    reset_matrix(out, m, k);
+    for (j, column) in scratch.iter().enumerate() {
+        for (i, &res) in column.iter().enumerate() {
+            out[i][j] = res;
+        }
+    }
+}
+
+// resize matrix to rows x cols of zeros, keeping the allocations of the rows it already has
+fn reset_matrix(matrix: &mut Vec<Vec<u16>>, rows: usize, cols: usize) {
+    matrix.resize_with(rows, Vec::new);
+    for row in matrix.iter_mut() {
+        row.clear();
+        row.resize(cols, 0);
+    }
+}
+
 /** transpose the 3D matrix
 
 similar to np.transpose(column_bits, (0,2,1)) in python,
@@ -557,30 +1137,56 @@ pub fn transpose_3d(matrix: &Vec<Vec<Vec<u8>>>, order: (usize, usize, usize)) ->
 Given a list of N objects, and a list of length-N bitvectors representing subsets of those objects,
     compute the xor-sum of each subset. Uses the main subroutine of Pippenger-style algorithms, see: https://ethresear.ch/t/7238
 
+the values only need XOR, so they can be u16 limbs (the row_combination as Vec<Vec<u16>>)
+    or whole field elements, e.g. u128s wrapped as vec![x], without an int_to_bigbin round trip
+
 Args:
-    values: the values(row_combination, Vec<Vec<u16>)
+    values: the values(row_combination, Vec<Vec<u16>>)
     bits: the bits(transposed_column_bits, Vec<Vec<Vec<u8>>)
 */
-pub fn multisubset(values: &Vec<Vec<u16>>, bits: &Vec<Vec<Vec<u8>>>) -> Vec<Vec<Vec<u16>>> {
-    let GROUPING = 4;
-    let mut subsets = vec![vec![vec![0u16; values[0].len()]; 16]; values.len() / GROUPING];
+pub fn multisubset<T: Copy + BitXor<Output = T> + Default>(
+    values: &[Vec<T>],
+    bits: &Vec<Vec<Vec<u8>>>,
+) -> Vec<Vec<Vec<T>>> {
+    multisubset_windowed(values, bits, 4)
+}
+
+/** Mutisubset sum with a configurable window
+
+same as multisubset, the values are split in windows of grouping values and the 2^grouping
+    subset sums of each window are precomputed, a larger window means fewer XORs per subset but
+    more precomputation, the best grouping depends on the number of values
 
-    for i in 0..GROUPING {
-        for j in (0..values.len()).step_by(GROUPING) {
-            subsets[j / GROUPING][1 << i] = values[j + i].clone();
+Args:
+    values: the values(row_combination, Vec<Vec<u16>), values.len() a multiple of grouping
+    bits: the bits(transposed_column_bits, Vec<Vec<Vec<u8>>)
+    grouping: the window size, multisubset uses 4
+*/
+pub fn multisubset_windowed<T: Copy + BitXor<Output = T> + Default>(
+    values: &[Vec<T>],
+    bits: &Vec<Vec<Vec<u8>>>,
+    grouping: usize,
+) -> Vec<Vec<Vec<T>>> {
+    assert_eq!(values.len() % grouping, 0);
+    let mut subsets =
+        vec![vec![vec![T::default(); values[0].len()]; 1 << grouping]; values.len() / grouping];
+
+    for i in 0..grouping {
+        for j in (0..values.len()).step_by(grouping) {
+            subsets[j / grouping][1 << i] = values[j + i].clone();
         }
     }
 
     // generate the subsets
     let mut top_p_of_2 = 2;
-    for i in 3..1 << GROUPING {
+    for i in 3..1 << grouping {
         if (i & (i - 1)) == 0 {
             top_p_of_2 = i;
         } else {
-            for j in (0..values.len()).step_by(GROUPING) {
+            for j in (0..values.len()).step_by(grouping) {
                 for k in 0..values[0].len() {
-                    subsets[j / GROUPING][i][k] = subsets[j / GROUPING][top_p_of_2][k]
-                        ^ subsets[j / GROUPING][i - top_p_of_2][k];
+                    subsets[j / grouping][i][k] = subsets[j / grouping][top_p_of_2][k]
+                        ^ subsets[j / grouping][i - top_p_of_2][k];
                 }
             }
         }
@@ -593,7 +1199,7 @@ pub fn multisubset(values: &Vec<Vec<u16>>, bits: &Vec<Vec<Vec<u8>>>) -> Vec<Vec<
             matrix
                 .iter()
                 .map(|row| {
-                    row.chunks(4)
+                    row.chunks(grouping)
                         .map(|chunk| chunk.iter().rev().fold(0, |acc, &bit| (acc << 1) | bit))
                         .collect()
                 })
@@ -602,7 +1208,7 @@ pub fn multisubset(values: &Vec<Vec<u16>>, bits: &Vec<Vec<Vec<u8>>>) -> Vec<Vec<
         .collect();
 
     // use the index_columns to select the elements from subsets
-    let selected_elements: Vec<Vec<Vec<Vec<u16>>>> = index_columns
+    let selected_elements: Vec<Vec<Vec<Vec<T>>>> = index_columns
         .iter()
         .map(|outer| {
             outer
@@ -618,11 +1224,69 @@ pub fn multisubset(values: &Vec<Vec<u16>>, bits: &Vec<Vec<Vec<u8>>>) -> Vec<Vec<
         })
         .collect();
 
-    // XOR along axis 3
+    // XOR the windows together: selected_elements is (matrices, rows, windows, limbs), so axis 2
     let o = xor_along_axis_4d(&selected_elements, 2);
     o
 }
 
+/** Assert that two matrices are equal, reporting the first differing entry
+
+assert_eq! on a whole matrix prints both matrices, which for the PCS is megabytes,
+    this reports the shapes when they differ, or the first (row, column) whose entries differ
+
+Args:
+    actual: the computed matrix
+    expected: the expected matrix
+*/
+#[cfg(test)]
+pub(crate) fn assert_matrix_eq<T: PartialEq + fmt::Debug>(actual: &[Vec<T>], expected: &[Vec<T>]) {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "the matrices have a different row count"
+    );
+    for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+        assert_eq!(a.len(), e.len(), "row {} has a different length", i);
+        if let Some(j) = a.iter().zip(e).position(|(x, y)| x != y) {
+            panic!(
+                "matrices differ at [{}][{}]: {:?} != {:?}",
+                i, j, a[j], e[j]
+            );
+        }
+    }
+}
+
+/** Assert that two 3D tensors are equal, reporting the first differing entry, see assert_matrix_eq */
+#[cfg(test)]
+pub(crate) fn assert_tensor_eq<T: PartialEq + fmt::Debug>(
+    actual: &[Vec<Vec<T>>],
+    expected: &[Vec<Vec<T>>],
+) {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "the tensors have a different length"
+    );
+    for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+        assert_eq!(a.len(), e.len(), "matrix {} has a different row count", i);
+        for (j, (row_a, row_e)) in a.iter().zip(e).enumerate() {
+            assert_eq!(
+                row_a.len(),
+                row_e.len(),
+                "row [{}][{}] has a different length",
+                i,
+                j
+            );
+            if let Some(k) = row_a.iter().zip(row_e).position(|(x, y)| x != y) {
+                panic!(
+                    "tensors differ at [{}][{}][{}]: {:?} != {:?}",
+                    i, j, k, row_a[k], row_e[k]
+                );
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::vec;
@@ -638,6 +1302,23 @@ mod tests {
         assert_eq!(row_count, 4);
     }
 
+    #[test]
+    fn test_choose_row_length_and_count_small_inputs() {
+        for log_evaluation_count in [0, 1, 2] {
+            assert_eq!(
+                try_choose_row_length_and_count(log_evaluation_count),
+                Err(LayoutError::TooFewEvaluations {
+                    log_evaluation_count
+                })
+            );
+        }
+        assert_eq!(
+            try_choose_row_length_and_count(MIN_LOG_EVALUATION_COUNT),
+            Ok((4, 2, 16, 4))
+        );
+        assert!(std::panic::catch_unwind(|| choose_row_length_and_count(0)).is_err());
+    }
+
     #[test]
     fn test_extend() {
         let rows = vec![
@@ -655,16 +1336,91 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extend_rows_parallel() {
+        let rows: Vec<Vec<B16>> = (0..64)
+            .map(|i| (0..16).map(|j| B16::new((i * 16 + j) as u16)).collect())
+            .collect();
+        assert_matrix_eq(&extend_rows_parallel(&rows, 8), &extend_rows(&rows, 8));
+    }
+
+    #[test]
+    fn test_evaluations_to_coeffs() {
+        let evaluations: Vec<u8> = (0..1u32 << 10).map(|i| (i * 37 + 11) as u8).collect();
+        let coeffs = evaluations_to_coeffs(&evaluations);
+        // 2^13 bits are 64 rows of 128 bits, 8 elements each
+        assert_eq!(coeffs.len(), 512);
+        assert_eq!(coeffs_to_evaluations(&coeffs), evaluations);
+
+        // the coefficients of a row are what extend evaluates on the larger domain
+        let rows = pack_rows(&evaluations, 64, 128, 16);
+        let extended = extend_rows(&rows, 2);
+        for (i, row) in coeffs.chunks(8).enumerate() {
+            let mut padded = row.to_vec();
+            padded.resize(16, B16::new(0));
+            additive_ntt_inplace(&mut padded, 0);
+            assert_eq!(padded, extended[i]);
+        }
+
+        // a constant row is the constant polynomial
+        let constant: Vec<u8> = [0x34, 0x12].repeat(512);
+        let coeffs = evaluations_to_coeffs(&constant);
+        for row in coeffs.chunks(8) {
+            assert_eq!(row[0], B16::new(0x1234));
+            assert!(row[1..].iter().all(|&c| c == B16::new(0)));
+        }
+        assert_eq!(coeffs_to_evaluations(&coeffs), constant);
+    }
+
+    #[test]
+    #[should_panic(expected = "matrices differ at [1][0]: 7 != 8")]
+    fn test_assert_matrix_eq_reports_first_difference() {
+        assert_matrix_eq(&[vec![1, 2], vec![7, 3]], &[vec![1, 2], vec![8, 4]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "tensors differ at [0][1][2]")]
+    fn test_assert_tensor_eq_reports_first_difference() {
+        assert_tensor_eq(
+            &[vec![vec![0; 3], vec![0, 0, 1]]],
+            &[vec![vec![0; 3], vec![0; 3]]],
+        );
+    }
+
     #[test]
     fn test_evaluation_tensor_product() {
         let eval_point = vec![2, 5];
         let result = evaluation_tensor_product(&eval_point);
-        // i need to compare the equality of two vectors of Vec(Vec(u16)), the following code is not working
-        // assert_eq!(result, vec![int_to_bigbin(12), int_to_bigbin(8), int_to_bigbin(15), int_to_bigbin(10)]);
-        assert_eq!(result[0], int_to_bigbin(12));
-        assert_eq!(result[1], int_to_bigbin(8));
-        assert_eq!(result[2], int_to_bigbin(15));
-        assert_eq!(result[3], int_to_bigbin(10));
+        assert_matrix_eq(
+            &result,
+            &[
+                int_to_bigbin(12),
+                int_to_bigbin(8),
+                int_to_bigbin(15),
+                int_to_bigbin(10),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_evaluation_tensor_product_parallel() {
+        let result = evaluation_tensor_product_parallel(&vec![2, 5]);
+        assert_matrix_eq(
+            &result,
+            &[
+                int_to_bigbin(12),
+                int_to_bigbin(8),
+                int_to_bigbin(15),
+                int_to_bigbin(10),
+            ],
+        );
+
+        // 13 coordinates, the last steps are above PARALLEL_TENSOR_THRESHOLD
+        let eval_point: Vec<u128> = (0..13).map(|i| (i * 0x9e37_79b9 + 7) << (i * 5)).collect();
+        assert_matrix_eq(
+            &evaluation_tensor_product_parallel(&eval_point),
+            &evaluation_tensor_product(&eval_point),
+        );
     }
 
     #[test]
@@ -676,6 +1432,48 @@ mod tests {
         assert_eq!(result, vec![0, 7]);
     }
 
+    #[test]
+    fn test_xor_along_axis_parallel() {
+        let values = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        assert_eq!(xor_along_axis_parallel(&values, 0), vec![5, 7, 5]);
+        assert_eq!(xor_along_axis_parallel(&values, 1), vec![0, 7]);
+
+        // large enough for the parallel path, with an odd number of rows
+        let values: Vec<Vec<u16>> = (0..1025u32)
+            .map(|i| {
+                (0..24u32)
+                    .map(|j| (i * 31 + j * 7 + i * j) as u16)
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            xor_along_axis_parallel(&values, 0),
+            xor_along_axis(&values, 0)
+        );
+        assert_eq!(
+            xor_along_axis_parallel(&values, 1),
+            xor_along_axis(&values, 1)
+        );
+    }
+
+    #[test]
+    fn test_try_xor_along_axis() {
+        let values = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        assert_eq!(try_xor_along_axis(&values, 0), Ok(vec![5, 7, 5]));
+        assert_eq!(try_xor_along_axis(&values, 1), Ok(vec![0, 7]));
+        assert_eq!(
+            try_xor_along_axis(&values, 2),
+            Err(BiniusError::UnsupportedAxis(2))
+        );
+        assert_eq!(
+            try_xor_along_axis(&[], 0),
+            Err(BiniusError::InvalidLength {
+                expected: 1,
+                actual: 0
+            })
+        );
+    }
+
     #[test]
     // fn test_transpose_bits() {
     //     let data = vec![
@@ -707,6 +1505,28 @@ mod tests {
         assert_eq!(output[1], [137]);
     }
 
+    #[test]
+    fn test_transpose_bits_round_trip() {
+        for rows in [5, 7, 9, 12] {
+            let input: Vec<Vec<u8>> = (0..rows)
+                .map(|i| {
+                    (0..16)
+                        .map(|j| ((i * 7 + j * 3 + i * j) % 3 == 0) as u8)
+                        .collect()
+                })
+                .collect();
+            let output = transpose_bits(input.clone());
+            assert_eq!(output[0].len(), (rows + 7) / 8);
+            assert_eq!(untranspose_bits(&output, rows), input);
+        }
+
+        // row 0 is the MSB of the first byte whatever the row count
+        let mut input = vec![vec![0u8; 16]; 12];
+        input[0][0] = 1;
+        input[9][0] = 1;
+        assert_eq!(transpose_bits(input)[0], [0b1000_0000, 0b0100_0000]);
+    }
+
     #[test]
     fn test_transpose() {
         let data = vec![
@@ -714,8 +1534,39 @@ mod tests {
             vec![B16::new(9), B16::new(15)],
         ];
         let output = transpose(&data);
-        assert_eq!(output[0], [B16::new(1), B16::new(9)]);
-        assert_eq!(output[1], [B16::new(3), B16::new(15)]);
+        assert_matrix_eq(
+            &output,
+            &[
+                vec![B16::new(1), B16::new(9)],
+                vec![B16::new(3), B16::new(15)],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_transpose_parallel() {
+        // test_transpose, below the threshold
+        let data = vec![
+            vec![B16::new(1), B16::new(3)],
+            vec![B16::new(9), B16::new(15)],
+        ];
+        assert_matrix_eq(&transpose_parallel(&data), &transpose(&data));
+
+        // above the threshold, with partial tiles on both sides
+        let data: Vec<Vec<B16>> = (0..100)
+            .map(|i| (0..333).map(|j| B16::new((i * 333 + j) as u16)).collect())
+            .collect();
+        let output = transpose_parallel(&data);
+        assert_matrix_eq(&output, &transpose(&data));
+        assert_matrix_eq(&transpose_parallel(&output), &data);
+    }
+
+    #[test]
+    fn test_transpose_matrix() {
+        let data: Vec<Vec<u32>> = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        let output = transpose_matrix(&data);
+        assert_eq!(output, vec![vec![1, 4], vec![2, 5], vec![3, 6]]);
+        assert_eq!(transpose_matrix(&output), data);
     }
 
     #[test]
@@ -736,6 +1587,171 @@ mod tests {
         assert_eq!(result[0], [4, 0, 0, 0, 0, 0, 0, 0]);
     }
 
+    #[test]
+    fn test_computed_tprimes_parallel() {
+        // 128 rows of 64 bits: 64 transposed rows of 128 bits, above PARALLEL_TPRIME_THRESHOLD
+        let eval_point: Vec<u128> = (0..7).map(|i| i * 11 + 3).collect();
+        let rows: Vec<Vec<B16>> = (0..128u32)
+            .map(|i| {
+                (0..4u32)
+                    .map(|j| B16::new((i * 97 + j * 13 + i * j) as u16))
+                    .collect()
+            })
+            .collect();
+
+        let rows_as_bits_transpose =
+            transpose_bits(rows.iter().map(|row| uint16s_to_bits(row)).collect());
+        let row_combination = evaluation_tensor_product(&eval_point);
+        assert_matrix_eq(
+            &computed_tprimes_parallel(&rows_as_bits_transpose, &row_combination),
+            &computed_tprimes(&rows_as_bits_transpose, &row_combination),
+        );
+    }
+
+    #[test]
+    fn test_computed_tprimes_into() {
+        let tprime_inputs = |num_rows: u32, log_rows: u128| {
+            let rows: Vec<Vec<B16>> = (0..num_rows)
+                .map(|i| {
+                    (0..4u32)
+                        .map(|j| B16::new((i * 31 + j * 7 + i * j) as u16))
+                        .collect()
+                })
+                .collect();
+            let eval_point: Vec<u128> = (0..log_rows).map(|i| i * 5 + 1).collect();
+            (
+                transpose_bits(rows.iter().map(|row| uint16s_to_bits(row)).collect()),
+                evaluation_tensor_product(&eval_point),
+            )
+        };
+
+        // the parallel path, the single-thread path, then the parallel one again, in the same buffers
+        let mut out = Vec::new();
+        let mut scratch = Vec::new();
+        for (num_rows, log_rows) in [(128, 7), (8, 3), (256, 8), (128, 7)] {
+            let (rows_as_bits_transpose, row_combination) = tprime_inputs(num_rows, log_rows);
+            computed_tprimes_into(
+                &mut out,
+                &mut scratch,
+                &rows_as_bits_transpose,
+                &row_combination,
+            );
+            assert_matrix_eq(
+                &out,
+                &computed_tprimes(&rows_as_bits_transpose, &row_combination),
+            );
+            assert_matrix_eq(
+                &out,
+                &computed_tprimes_parallel(&rows_as_bits_transpose, &row_combination),
+            );
+        }
+    }
+
+    #[test]
+    fn test_xor_along_axis_4d() {
+        // entry [a][b][c][d] is the single bit 8a + 4b + 2c + d, so each XOR is the OR of two bits
+        let bit = |a: usize, b: usize, c: usize, d: usize| 1u16 << (8 * a + 4 * b + 2 * c + d);
+        let values: Vec<Vec<Vec<Vec<u16>>>> = (0..2)
+            .map(|a| {
+                (0..2)
+                    .map(|b| {
+                        (0..2)
+                            .map(|c| (0..2).map(|d| bit(a, b, c, d)).collect())
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+        let expected = |f: &dyn Fn(usize, usize, usize, usize) -> u16| -> Vec<Vec<Vec<u16>>> {
+            (0..2)
+                .map(|x| {
+                    (0..2)
+                        .map(|y| (0..2).map(|z| f(x, y, z, 0) | f(x, y, z, 1)).collect())
+                        .collect()
+                })
+                .collect()
+        };
+
+        assert_tensor_eq(
+            &xor_along_axis_4d(&values, 0),
+            &expected(&|b, c, d, a| bit(a, b, c, d)),
+        );
+        assert_tensor_eq(
+            &xor_along_axis_4d(&values, 1),
+            &expected(&|a, c, d, b| bit(a, b, c, d)),
+        );
+        assert_tensor_eq(
+            &xor_along_axis_4d(&values, 2),
+            &expected(&|a, b, d, c| bit(a, b, c, d)),
+        );
+        assert_tensor_eq(
+            &xor_along_axis_4d(&values, 3),
+            &expected(&|a, b, c, d| bit(a, b, c, d)),
+        );
+        // a few entries spelled out
+        assert_eq!(xor_along_axis_4d(&values, 0)[0][0][0], 0x0101);
+        assert_eq!(xor_along_axis_4d(&values, 3)[1][1][1], 0xc000);
+    }
+
+    #[test]
+    fn test_multisubset_windowed() {
+        let values: Vec<Vec<u16>> = (0..8u16)
+            .map(|i| (0..8u16).map(|k| i * 1031 + k * 17 + 1).collect())
+            .collect();
+        // 2 matrices of 3 subsets of the 8 values
+        let bits: Vec<Vec<Vec<u8>>> = (0..2u8)
+            .map(|m| {
+                (0..3u8)
+                    .map(|r| (0..8u8).map(|i| (m + r * i + i / 3) % 2).collect())
+                    .collect()
+            })
+            .collect();
+
+        let expected: Vec<Vec<Vec<u16>>> = bits
+            .iter()
+            .map(|matrix| {
+                matrix
+                    .iter()
+                    .map(|row| {
+                        (0..8).fold(vec![0u16; 8], |acc, k| {
+                            acc.iter()
+                                .enumerate()
+                                .map(|(i, &a)| if row[k] == 1 { a ^ values[k][i] } else { a })
+                                .collect()
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(multisubset_windowed(&values, &bits, 2), expected);
+        assert_eq!(multisubset_windowed(&values, &bits, 4), expected);
+        assert_eq!(multisubset(&values, &bits), expected);
+    }
+
+    #[test]
+    fn test_multisubset_u128() {
+        let values: Vec<u128> = (0..8u128)
+            .map(|i| (i * 0x1234_5678_9abc + 5) << (i * 9))
+            .collect();
+        let bits: Vec<Vec<Vec<u8>>> = (0..2u8)
+            .map(|m| {
+                (0..3u8)
+                    .map(|r| (0..8u8).map(|i| (m + r * i + i / 3) % 2).collect())
+                    .collect()
+            })
+            .collect();
+
+        let limbs: Vec<Vec<u16>> = values.iter().map(|&x| int_to_bigbin(x)).collect();
+        let wide: Vec<Vec<u128>> = values.iter().map(|&x| vec![x]).collect();
+        let from_limbs = multisubset(&limbs, &bits);
+        let from_wide = multisubset(&wide, &bits);
+        for (matrix_limbs, matrix_wide) in from_limbs.iter().zip(from_wide.iter()) {
+            for (row_limbs, row_wide) in matrix_limbs.iter().zip(matrix_wide.iter()) {
+                assert_eq!(*row_limbs, int_to_bigbin(row_wide[0]));
+            }
+        }
+    }
+
     #[test]
     fn test_pack_row() {
         // data =  [1 1 0 1 0 0 0 0 0 0 1 0 1 0 0 0]
@@ -753,4 +1769,82 @@ mod tests {
         assert_eq!(result[1], [B16::new(1027)]);
         assert_eq!(result[2], [B16::new(1541)]);
     }
+
+    #[test]
+    fn test_pack_rows_checked() {
+        let data = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+        assert_eq!(
+            pack_rows_checked(&data, 8, 16, 16),
+            Ok(pack_rows(&data, 8, 16, 16))
+        );
+        assert_eq!(
+            pack_rows_checked(&data, 4, 32, 12),
+            Err(PackError::PackingFactor(12))
+        );
+        assert_eq!(
+            pack_rows_checked(&data, 4, 24, 16),
+            Err(PackError::RowLength {
+                row_length: 24,
+                packing_factor: 16
+            })
+        );
+        assert_eq!(
+            pack_rows_checked(&data, 16, 16, 16),
+            Err(PackError::TooFewEvaluations {
+                needed: 32,
+                available: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_pack_row_checked() {
+        let data = vec![0b11010000, 0b00101000, 1, 2];
+        assert_eq!(pack_row_checked(&data, 32, 16), Ok(pack_row(&data, 32, 16)));
+        assert_eq!(
+            pack_row_checked(&data, 32, 8),
+            Err(PackError::PackingFactor(8))
+        );
+        assert_eq!(
+            pack_row_checked(&data, 48, 16),
+            Err(PackError::TooFewEvaluations {
+                needed: 6,
+                available: 4
+            })
+        );
+        assert_eq!(
+            pack_row_checked(&data, 8, 16),
+            Err(PackError::RowLength {
+                row_length: 8,
+                packing_factor: 16
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "packing_factor must be 16, the bits of a BinaryFieldElement16, got 8"
+    )]
+    fn test_pack_rejects_other_packing_factors() {
+        pack(&[1, 2, 3, 4], 32, 8, BitOrder::Lsb0);
+    }
+
+    #[test]
+    fn test_pack_bit_order() {
+        // bits, LSB-first per byte: 0000 1011 0001 0100
+        let data = vec![0b11010000, 0b00101000];
+        assert_eq!(pack_rows(&data, 1, 16, 16), [[B16::new(0x28d0)]]);
+        assert_eq!(pack(&data, 16, 16, BitOrder::Lsb0), [B16::new(0x28d0)]);
+        // the same bytes read MSB-first: 1101 0000 0010 1000
+        assert_eq!(pack_row(&data, 16, 16), [B16::new(5131)]);
+        assert_eq!(pack(&data, 16, 16, BitOrder::Msb0), [B16::new(5131)]);
+
+        // transpose_bits packs MSB-first, so pack_row recovers the element whose bits were transposed
+        let element = B16::new(0x28d0);
+        let bits: Vec<Vec<u8>> = uint16s_to_bits(&vec![element])
+            .iter()
+            .map(|&bit| vec![bit])
+            .collect();
+        assert_eq!(pack_row(&transpose_bits(bits)[0], 16, 16), [element]);
+    }
 }
@@ -0,0 +1,21 @@
+// the vanilla and the simd pcs on the same inputs, run with `cargo test --features simd --test pcs_consistency`
+#![cfg(all(feature = "vanilla", feature = "simd"))]
+
+use binius_rust::bench::deterministic_evaluations;
+use binius_rust::consistency::assert_pcs_consistent;
+
+#[test]
+fn test_pcs_consistent_on_bench_inputs() {
+    for (log_bytes, seed) in [(12, 0), (14, 1), (16, 2)] {
+        let evaluations = deterministic_evaluations(log_bytes, seed);
+        assert_pcs_consistent(&evaluations, &vec![1; log_bytes + 3]);
+    }
+}
+
+#[test]
+fn test_pcs_consistent_on_other_points() {
+    let evaluations = deterministic_evaluations(13, 3);
+    assert_pcs_consistent(&evaluations, &vec![0; 16]);
+    assert_pcs_consistent(&evaluations, &(1..=16).collect());
+    assert_pcs_consistent(&evaluations, &(0..16).map(|i| 0x9e37_79b9_u128 << i).collect());
+}